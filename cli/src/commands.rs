@@ -0,0 +1,368 @@
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use pivox_client::{
+    activity_log_address, bond_address, contract_address, decode_activity_log, decode_bond, decode_contract,
+    decode_legacy_contract, decode_milestone_account, decode_milestone_template, decode_payment_receipt,
+    decode_profile, decode_vault_account, instructions, layout_version, milestone_address, milestone_template_address,
+    offer, profile_address, receipt_address, vault_account_address, BonusSource, Contract, ContractAddresses, MilestoneAccount,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::{
+    output,
+    terms::{MilestonesFile, SignedOffer, TemplateFile, TermsFile},
+    tx::Session,
+    BondCommand, Cli, Command, ContractArgs, MilestoneCommand, OfferCommand, ReceiptCommand, SubcontractCommand, TemplateCommand,
+    TermsArgs,
+};
+
+pub fn run(cli: &Cli) -> Result<()> {
+    let session = Session::new(cli)?;
+    let me = session.pubkey();
+
+    let ixs = match &cli.command {
+        Command::Offer(OfferCommand::Create { freelancer, mint, terms, expires_in, nonce, out }) => {
+            let terms = TermsFile::load(terms)?;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+            let mut signed = SignedOffer {
+                client: me,
+                freelancer: *freelancer,
+                mint: *mint,
+                expires_at: now.as_secs() as i64 + expires_in,
+                nonce: nonce.unwrap_or(now.as_millis() as u64),
+                terms,
+                signature: String::new(),
+            };
+            let message = offer::offer_message(&signed.offer_terms())?;
+            signed.signature = session.signer.sign_message(&message).to_string();
+
+            let json = serde_json::to_string_pretty(&signed)?;
+            match out {
+                Some(path) => {
+                    fs::write(path, json).with_context(|| format!("writing {}", path.display()))?;
+                    output::print_offer_written(cli.output, path, &signed);
+                }
+                None => println!("{json}"),
+            }
+            return Ok(());
+        }
+        Command::Offer(OfferCommand::Accept { offer: path }) => {
+            let signed = SignedOffer::load(path)?;
+            if signed.freelancer != me {
+                bail!("offer is addressed to {}, not {}", signed.freelancer, me);
+            }
+            let terms = signed.offer_terms();
+            let message = offer::offer_message(&terms)?;
+            let signature: [u8; 64] = signed.signature()?.into();
+            vec![
+                offer::ed25519_instruction(&signed.client, &signature, &message),
+                instructions::accept_offer(terms),
+            ]
+        }
+        Command::Propose { freelancer, terms } => {
+            let (_, terms_hash) = load_terms(&session, terms, 0)?;
+            vec![instructions::propose(&me, freelancer, terms_hash)]
+        }
+        Command::Approve { client, mint, terms, deposit } => {
+            let addresses = ContractAddresses::new(client, &me, mint);
+            match load_terms(&session, terms, *deposit)?.0 {
+                Approval::Terms(args) => vec![instructions::approve(&addresses, args)],
+                Approval::Template(args) => vec![instructions::approve_from_template(&addresses, args)],
+            }
+        }
+        Command::Template(TemplateCommand::Create { name, file }) => {
+            let template = TemplateFile::load(file)?;
+            vec![instructions::create_milestone_template(&me, name, template.milestones()?)]
+        }
+        Command::Template(TemplateCommand::Close { name }) => {
+            vec![instructions::close_milestone_template(&me, name)]
+        }
+        Command::Template(TemplateCommand::Show { creator, name }) => {
+            let address = milestone_template_address(creator, name).0;
+            let data = session
+                .rpc
+                .get_account_data(&address)
+                .with_context(|| format!("fetching template {address}"))?;
+            output::print_template(cli.output, &address, &decode_milestone_template(&data)?);
+            return Ok(());
+        }
+        Command::Deposit { contract, amount, milestone: None } => {
+            let addresses = contract.addresses();
+            vec![instructions::deposit_funds(&addresses, &me, amount.unwrap_or_default())]
+        }
+        Command::Deposit { contract, amount, milestone: Some(index) } => {
+            let (addresses, _) = fetch_addresses(&session, contract)?;
+            let amount = match amount {
+                Some(amount) => *amount,
+                None => fetch_milestone(&session, &addresses, *index)?.escrow()?,
+            };
+            vec![instructions::fund_milestone(&addresses, &me, *index, amount)]
+        }
+        Command::Milestone(MilestoneCommand::Submit(args)) => {
+            let (addresses, state) = fetch_addresses(&session, &args.contract)?;
+            let milestone = fetch_milestone(&session, &addresses, args.index)?;
+            let prerequisites = state.prerequisites(args.index as u8, &milestone);
+            vec![instructions::freelancer_submit_milestone(&addresses, args.index, &prerequisites)]
+        }
+        Command::Milestone(MilestoneCommand::Approve(args)) => {
+            let (addresses, state) = fetch_addresses(&session, &args.contract)?;
+            let milestone = fetch_milestone(&session, &addresses, args.index)?;
+            let prerequisites = state.prerequisites(args.index as u8, &milestone);
+            vec![instructions::client_approve_milestone(&addresses, args.index, &prerequisites)]
+        }
+        Command::Milestone(MilestoneCommand::Confirm(args)) => {
+            let (addresses, _) = fetch_addresses(&session, &args.contract)?;
+            vec![instructions::freelancer_confirm_milestone(&addresses, args.index)]
+        }
+        Command::Milestone(MilestoneCommand::Add { contract, file }) => {
+            let (addresses, state) = fetch_addresses(&session, contract)?;
+            let milestones = MilestonesFile::load(file)?.milestones();
+            vec![instructions::add_milestones(&addresses, state.milestone_count as u8, milestones)]
+        }
+        Command::Milestone(MilestoneCommand::Cancel(args)) => {
+            let (addresses, state) = fetch_addresses(&session, &args.contract)?;
+            vec![instructions::cancel_milestone(&addresses, args.index, state.receipt_count)]
+        }
+        Command::Milestone(MilestoneCommand::Release { contract, index, invoice }) => {
+            let (addresses, state) = fetch_addresses(&session, contract)?;
+            let milestone = fetch_milestone(&session, &addresses, *index)?;
+            let payees = state.payees_for(&milestone);
+            let price_feed = milestone.pricing.map(|pricing| pricing.feed);
+            vec![instructions::release_milestone_payment(
+                &addresses,
+                &me,
+                *index,
+                payees,
+                price_feed,
+                state.receipt_count,
+                invoice,
+            )]
+        }
+        Command::Cancel { contract, invoice } => {
+            let (addresses, state) = fetch_addresses(&session, contract)?;
+            vec![instructions::complete_or_cancel_contract(&addresses, &me, state.receipt_count, invoice)]
+        }
+        Command::Withdraw { contract } => {
+            let (addresses, state) = fetch_addresses(&session, contract)?;
+            vec![instructions::withdraw_funds(&addresses, &me, state.receipt_count)]
+        }
+        Command::Reassign { contract, new_client, new_freelancer, bonded } => {
+            let (addresses, state) = fetch_addresses(&session, contract)?;
+            let new_client = new_client.unwrap_or(addresses.client);
+            let new_freelancer = new_freelancer.unwrap_or(addresses.freelancer);
+            vec![instructions::reassign_party(&addresses, &new_client, &new_freelancer, *bonded, state.milestone_count)]
+        }
+        Command::Bonus { contract, amount, memo, from_escrow } => {
+            let addresses = contract.addresses();
+            let source = if *from_escrow { BonusSource::EscrowSurplus } else { BonusSource::Direct };
+            vec![instructions::pay_bonus(&addresses, *amount, source, memo)]
+        }
+        Command::Bond(BondCommand::Slash { contract, amount, reason }) => {
+            let addresses = contract.addresses();
+            vec![instructions::slash_bond(&addresses, &me, *amount, (*reason).into())]
+        }
+        Command::Bond(BondCommand::Return { contract }) => {
+            let addresses = contract.addresses();
+            vec![instructions::return_bond(&addresses, &me)]
+        }
+        Command::Bond(BondCommand::Show { parties }) => {
+            let address = bond_address(&contract_address(&parties.client, &parties.freelancer).0).0;
+            let data = session
+                .rpc
+                .get_account_data(&address)
+                .with_context(|| format!("no bond was posted for this contract ({address})"))?;
+            output::print_bond(cli.output, &address, &decode_bond(&data)?);
+            return Ok(());
+        }
+        Command::Subcontract(SubcontractCommand::Fund { parent, index, subcontractor }) => {
+            let (parent, _) = fetch_addresses(&session, parent)?;
+            let child = ContractAddresses::new(&parent.freelancer, subcontractor, &parent.mint);
+            vec![instructions::fund_subcontract(&parent, &child, *index)]
+        }
+        Command::Subcontract(SubcontractCommand::Settle { parent, subcontractor, with_consent }) => {
+            let (parent, _) = fetch_addresses(&session, parent)?;
+            let child = ContractAddresses::new(&parent.freelancer, subcontractor, &parent.mint);
+            let child_state = fetch_contract(&session, &child.contract)?;
+            let link = child_state.parent.context("contract is not a subcontract")?;
+            let receipt_number = child_state.receipt_count;
+            vec![instructions::settle_subcontract(&parent, link.milestone_index, &child, *with_consent, receipt_number)]
+        }
+        Command::Reconcile { contract } => {
+            let (addresses, state) = fetch_addresses(&session, contract)?;
+            vec![instructions::reconcile(&addresses, &me, state.receipt_count)]
+        }
+        Command::Migrate { contract } => {
+            let addresses = contract.addresses();
+            let data = session
+                .rpc
+                .get_account_data(&addresses.contract)
+                .with_context(|| format!("fetching contract {}", addresses.contract))?;
+            let legacy_milestones = match layout_version::<Contract>(&data)? {
+                0 => decode_legacy_contract(&data)?.milestones.len() as u8,
+                _ => 0,
+            };
+            vec![instructions::migrate(&addresses, &me, legacy_milestones)]
+        }
+        Command::Receipt(ReceiptCommand::List { parties }) => {
+            let contract = fetch_contract(&session, &contract_address(&parties.client, &parties.freelancer).0)?;
+            let mut receipts = vec![];
+            for number in 0..contract.receipt_count {
+                let address = receipt_address(&contract.id, number).0;
+                // Closed receipts are gone; skip them.
+                if let Ok(data) = session.rpc.get_account_data(&address) {
+                    receipts.push((address, decode_payment_receipt(&data)?));
+                }
+            }
+            output::print_receipts(cli.output, &receipts);
+            return Ok(());
+        }
+        Command::Receipt(ReceiptCommand::Close { receipt }) => {
+            vec![instructions::close_payment_receipt(&me, receipt)]
+        }
+        Command::Show { parties } => return show(cli, &session, &parties.client, &parties.freelancer),
+        Command::Activity { parties } => {
+            let address = activity_log_address(&contract_address(&parties.client, &parties.freelancer).0).0;
+            let data = session
+                .rpc
+                .get_account_data(&address)
+                .with_context(|| format!("fetching activity log {address}"))?;
+            output::print_activity(cli.output, &address, &decode_activity_log(&data)?);
+            return Ok(());
+        }
+        Command::Profile { wallet } => {
+            let wallet = wallet.unwrap_or(me);
+            let address = profile_address(&wallet).0;
+            let data = session
+                .rpc
+                .get_account_data(&address)
+                .with_context(|| format!("no profile for {wallet}; it is created by its first release or termination"))?;
+            output::print_profile(cli.output, &address, &decode_profile(&data)?);
+            return Ok(());
+        }
+        Command::Sign { transaction } => {
+            let outcome = session.sign_encoded(transaction)?;
+            output::print_outcome(cli.output, &outcome);
+            return Ok(());
+        }
+        Command::Send { transaction } => {
+            let outcome = session.send_encoded(transaction)?;
+            output::print_outcome(cli.output, &outcome);
+            return Ok(());
+        }
+    };
+
+    let outcome = session.process(&ixs)?;
+    output::print_outcome(cli.output, &outcome);
+    Ok(())
+}
+
+/// How the freelancer approves a proposal: with the milestones from the terms file or from a template.
+enum Approval {
+    Terms(instructions::ApproveArgs),
+    Template(instructions::TemplateApproveArgs),
+}
+
+/// Reads `args` into the approval the freelancer signs with `deposit`, and the hash the client proposes
+/// those terms under.
+fn load_terms(session: &Session, args: &TermsArgs, deposit: u64) -> Result<(Approval, [u8; 32])> {
+    let terms = TermsFile::load(&args.terms)?;
+    match (args.template, args.budget) {
+        (Some(template), Some(total_budget)) => {
+            let data = session
+                .rpc
+                .get_account_data(&template)
+                .with_context(|| format!("fetching template {template}"))?;
+            let milestones = decode_milestone_template(&data)?.instantiate(total_budget)?;
+            let approval = instructions::TemplateApproveArgs {
+                client_share: terms.client_share,
+                freelancer_share: terms.freelancer_share,
+                initial_payment: terms.initial_payment,
+                project_start: terms.project_start as i128,
+                project_duration: terms.project_duration,
+                dispute_resolution: terms.dispute_resolution.clone(),
+                ordering: terms.ordering.into(),
+                release_policy: terms.release_policy.into(),
+                payees: terms.payees(),
+                bond_amount: args.bond,
+                deposit,
+                template,
+                total_budget,
+                milestone_count: milestones.len() as u8,
+            };
+            let hash = approval.terms_hash(&milestones)?;
+            Ok((Approval::Template(approval), hash))
+        }
+        _ => {
+            let approval = instructions::ApproveArgs {
+                client_share: terms.client_share,
+                freelancer_share: terms.freelancer_share,
+                initial_payment: terms.initial_payment,
+                project_start: terms.project_start as i128,
+                project_duration: terms.project_duration,
+                dispute_resolution: terms.dispute_resolution.clone(),
+                ordering: terms.ordering.into(),
+                release_policy: terms.release_policy.into(),
+                payees: terms.payees(),
+                bond_amount: args.bond,
+                deposit,
+                milestones: terms.milestones(),
+            };
+            let hash = approval.terms_hash()?;
+            Ok((Approval::Terms(approval), hash))
+        }
+    }
+}
+
+fn fetch_contract(session: &Session, address: &Pubkey) -> Result<Contract> {
+    let data = session
+        .rpc
+        .get_account_data(address)
+        .with_context(|| format!("fetching contract {address}"))?;
+    Ok(decode_contract(&data)?)
+}
+
+/// The contract's addresses, with the milestone addresses of a reassigned contract, and its current state.
+fn fetch_addresses(session: &Session, args: &ContractArgs) -> Result<(ContractAddresses, Contract)> {
+    let addresses = args.addresses();
+    let state = fetch_contract(session, &addresses.contract)?;
+    Ok((addresses.with_contract_id(state.id), state))
+}
+
+fn fetch_milestone(session: &Session, addresses: &ContractAddresses, index: u64) -> Result<MilestoneAccount> {
+    let address = addresses.milestone(index as u8);
+    let data = session
+        .rpc
+        .get_account_data(&address)
+        .with_context(|| format!("fetching milestone {index} ({address})"))?;
+    Ok(decode_milestone_account(&data)?)
+}
+
+fn show(cli: &Cli, session: &Session, client: &Pubkey, freelancer: &Pubkey) -> Result<()> {
+    let contract_key = contract_address(client, freelancer).0;
+    let contract = fetch_contract(session, &contract_key)?;
+
+    let vault = session
+        .rpc
+        .get_account_data(&vault_account_address(client, freelancer).0)
+        .ok()
+        .map(|data| decode_vault_account(&data))
+        .transpose()?;
+
+    let milestones = (0..contract.milestone_count)
+        .map(|index| {
+            let address = milestone_address(&contract.id, index as u8).0;
+            let data = session
+                .rpc
+                .get_account_data(&address)
+                .with_context(|| format!("fetching milestone {index} ({address})"))?;
+            Ok(decode_milestone_account(&data)?.milestone)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    output::print_contract(cli.output, &contract_key, &contract, &milestones, vault.as_ref());
+    Ok(())
+}
@@ -0,0 +1,415 @@
+mod commands;
+mod output;
+mod terms;
+mod tx;
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+#[derive(Parser)]
+#[command(name = "pivox", version, about = "Operate PIVOX milestone contracts from the command line")]
+pub struct Cli {
+    /// RPC URL or moniker: localhost, devnet, testnet, mainnet-beta
+    #[arg(long, short = 'u', global = true, env = "PIVOX_RPC_URL", default_value = "localhost")]
+    pub url: String,
+
+    /// Keypair file used to sign; defaults to the Solana CLI keypair
+    #[arg(long, short = 'k', global = true, env = "PIVOX_KEYPAIR")]
+    pub keypair: Option<PathBuf>,
+
+    #[arg(long, short = 'o', global = true, value_enum, default_value_t = OutputFormat::Display)]
+    pub output: OutputFormat,
+
+    /// Sign the transaction without sending it (offline signing); requires --blockhash
+    #[arg(long, global = true, requires = "blockhash")]
+    pub sign_only: bool,
+
+    /// Recent blockhash to sign with when using --sign-only
+    #[arg(long, global = true)]
+    pub blockhash: Option<Hash>,
+
+    /// Fee payer, when it is someone other than the signing keypair
+    #[arg(long, global = true)]
+    pub fee_payer: Option<Pubkey>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Display,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Client-signed offers that the freelancer accepts in one transaction
+    #[command(subcommand)]
+    Offer(OfferCommand),
+
+    /// Client proposes a contract to a freelancer, committing to the terms the freelancer has to approve it with
+    Propose {
+        #[arg(long)]
+        freelancer: Pubkey,
+        #[command(flatten)]
+        terms: TermsArgs,
+    },
+
+    /// Freelancer accepts a proposal with the contract terms, creating the contract, its vault and the vault's
+    /// token account
+    Approve {
+        #[arg(long)]
+        client: Pubkey,
+        #[arg(long)]
+        mint: Pubkey,
+        #[command(flatten)]
+        terms: TermsArgs,
+        /// First deposit from the client's token account. The client co-signs, so combine with --sign-only and
+        /// `pivox sign`
+        #[arg(long, default_value_t = 0)]
+        deposit: u64,
+    },
+
+    /// Reusable milestone schedules
+    #[command(subcommand)]
+    Template(TemplateCommand),
+
+    /// Deposit milestone funds into the contract vault
+    Deposit {
+        #[command(flatten)]
+        contract: ContractArgs,
+        /// Defaults to the milestone's escrow with --milestone
+        #[arg(long, required_unless_present = "milestone")]
+        amount: Option<u64>,
+        /// Fund only this milestone, so it can be submitted before the rest are funded
+        #[arg(long)]
+        milestone: Option<u64>,
+    },
+
+    /// Milestone lifecycle and payment release
+    #[command(subcommand)]
+    Milestone(MilestoneCommand),
+
+    /// Approve completing or cancelling the contract; it terminates once both parties have approved
+    Cancel {
+        #[command(flatten)]
+        contract: ContractArgs,
+        /// Invoice reference for the receipt of the final payout
+        #[arg(long, default_value = "")]
+        invoice: String,
+    },
+
+    /// Withdraw your share of what termination left in the vault
+    Withdraw {
+        #[command(flatten)]
+        contract: ContractArgs,
+    },
+
+    /// Hand the contract to a new client or freelancer. Needs the signatures of both current parties and the
+    /// incoming wallet, so combine with --sign-only and `pivox sign`
+    Reassign {
+        #[command(flatten)]
+        contract: ContractArgs,
+        #[arg(long, conflicts_with = "new_freelancer", required_unless_present = "new_freelancer")]
+        new_client: Option<Pubkey>,
+        #[arg(long)]
+        new_freelancer: Option<Pubkey>,
+        /// The freelancer posted a performance bond, which moves with the contract
+        #[arg(long)]
+        bonded: bool,
+    },
+
+    /// Client pays the freelancer a bonus outside the milestone schedule
+    Bonus {
+        #[command(flatten)]
+        contract: ContractArgs,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, default_value = "")]
+        memo: String,
+        /// Pay from escrow beyond what unreleased milestones need instead of the client's wallet
+        #[arg(long)]
+        from_escrow: bool,
+    },
+
+    /// Freelancer performance bonds
+    #[command(subcommand)]
+    Bond(BondCommand),
+
+    /// Payment receipts issued for releases, termination withdrawals and refunds
+    #[command(subcommand)]
+    Receipt(ReceiptCommand),
+
+    /// Subcontract part of a milestone to another freelancer
+    #[command(subcommand)]
+    Subcontract(SubcontractCommand),
+
+    /// Square the vault ledger with its token account, refunding untracked tokens to the client
+    Reconcile {
+        #[command(flatten)]
+        contract: ContractArgs,
+    },
+
+    /// Upgrade a contract's accounts written by an older program version to the current layout
+    Migrate {
+        #[command(flatten)]
+        contract: ContractArgs,
+    },
+
+    /// Show a contract, its milestones and its vault
+    Show {
+        #[command(flatten)]
+        parties: PartyArgs,
+    },
+
+    /// Show a contract's on-chain activity log, oldest first
+    Activity {
+        #[command(flatten)]
+        parties: PartyArgs,
+    },
+
+    /// Show a wallet's reputation profile
+    Profile {
+        /// Wallet to look up; defaults to the keypair
+        wallet: Option<Pubkey>,
+    },
+
+    /// Add the keypair's signature to a transaction produced with --sign-only
+    Sign {
+        /// Base64 encoded transaction
+        transaction: String,
+    },
+
+    /// Send a fully signed transaction produced with --sign-only
+    Send {
+        /// Base64 encoded transaction
+        transaction: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum OfferCommand {
+    /// Sign offer terms as the client; works fully offline
+    Create {
+        #[arg(long)]
+        freelancer: Pubkey,
+        #[arg(long)]
+        mint: Pubkey,
+        /// JSON terms file
+        #[arg(long)]
+        terms: PathBuf,
+        /// Seconds until the offer expires
+        #[arg(long, default_value_t = 7 * 24 * 60 * 60)]
+        expires_in: i64,
+        /// Offer nonce; defaults to the current unix time in milliseconds
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Write the signed offer here instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Accept a signed offer as the freelancer
+    Accept {
+        /// Signed offer file produced by `pivox offer create`
+        offer: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommand {
+    /// Create a template owned by the keypair
+    Create {
+        #[arg(long)]
+        name: String,
+        /// JSON template file
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Close a template and reclaim its rent
+    Close {
+        #[arg(long)]
+        name: String,
+    },
+    /// Show a template
+    Show {
+        #[arg(long)]
+        creator: Pubkey,
+        #[arg(long)]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BondCommand {
+    /// Slash the freelancer's bond to the client
+    Slash {
+        #[command(flatten)]
+        contract: ContractArgs,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, value_enum)]
+        reason: SlashReasonArg,
+    },
+    /// Return what is left of the bond to the freelancer after termination
+    Return {
+        #[command(flatten)]
+        contract: ContractArgs,
+    },
+    /// Show a contract's bond
+    Show {
+        #[command(flatten)]
+        parties: PartyArgs,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReceiptCommand {
+    /// List a contract's open receipts
+    List {
+        #[command(flatten)]
+        parties: PartyArgs,
+    },
+    /// Close a receipt and reclaim its rent; only the wallet that paid the rent can
+    Close {
+        receipt: Pubkey,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SubcontractCommand {
+    /// As the parent milestone's freelancer, fund a contract you are the client of from that milestone. The
+    /// parent contract's client must also sign, so combine with --sign-only and `pivox sign`
+    Fund {
+        /// The parent contract, where the keypair is the freelancer
+        #[command(flatten)]
+        parent: ContractArgs,
+        #[arg(long)]
+        index: u8,
+        /// The subcontractor on the child contract
+        #[arg(long)]
+        subcontractor: Pubkey,
+    },
+    /// Close the subcontract and take back what it has not paid out. The subcontractor must also sign while
+    /// any of its milestones are unreleased, so combine with --sign-only and `pivox sign`
+    Settle {
+        #[command(flatten)]
+        parent: ContractArgs,
+        #[arg(long)]
+        subcontractor: Pubkey,
+        /// The subcontractor co-signs
+        #[arg(long)]
+        with_consent: bool,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SlashReasonArg {
+    /// Client: the deadline passed with milestones never submitted
+    MissedDeadline,
+    /// Client: nothing submitted for the grace period after the deadline
+    Abandoned,
+    /// Client, with the freelancer co-signing: the freelancer lost a dispute. Combine with --sign-only and
+    /// `pivox sign`
+    DisputeLost,
+}
+
+impl From<SlashReasonArg> for pivox_client::SlashReason {
+    fn from(reason: SlashReasonArg) -> Self {
+        match reason {
+            SlashReasonArg::MissedDeadline => pivox_client::SlashReason::MissedDeadline,
+            SlashReasonArg::Abandoned => pivox_client::SlashReason::Abandoned,
+            SlashReasonArg::DisputeLost => pivox_client::SlashReason::DisputeLost,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum MilestoneCommand {
+    /// Freelancer submits a milestone
+    Submit(MilestoneArgs),
+    /// Client approves a submitted milestone
+    Approve(MilestoneArgs),
+    /// Freelancer confirms the client's approval
+    Confirm(MilestoneArgs),
+    /// Client and freelancer add milestones to a running contract. Needs both signatures, so combine with
+    /// --sign-only and `pivox sign`
+    Add {
+        #[command(flatten)]
+        contract: ContractArgs,
+        /// JSON file with a `milestones` list, shaped like the terms file's
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Client and freelancer drop an unreleased milestone, refunding its escrow to the client. Needs both
+    /// signatures, so combine with --sign-only and `pivox sign`
+    Cancel(MilestoneArgs),
+    /// Release an approved milestone's payment to the freelancer
+    Release {
+        #[command(flatten)]
+        contract: ContractArgs,
+        #[arg(long)]
+        index: u64,
+        /// Invoice reference recorded on the payment receipt
+        #[arg(long, default_value = "")]
+        invoice: String,
+    },
+}
+
+#[derive(Args)]
+pub struct PartyArgs {
+    #[arg(long)]
+    pub client: Pubkey,
+    #[arg(long)]
+    pub freelancer: Pubkey,
+}
+
+/// The terms of a proposal; `propose` and `approve` must be given the same ones.
+#[derive(Args)]
+pub struct TermsArgs {
+    /// JSON terms file
+    #[arg(long)]
+    pub terms: PathBuf,
+    /// Take the milestones from this template instead of the terms file
+    #[arg(long, requires = "budget")]
+    pub template: Option<Pubkey>,
+    /// Total budget the template's milestones are scaled to
+    #[arg(long, requires = "template")]
+    pub budget: Option<u64>,
+    /// Performance bond the freelancer posts from their token account
+    #[arg(long, default_value_t = 0)]
+    pub bond: u64,
+}
+
+#[derive(Args)]
+pub struct ContractArgs {
+    #[command(flatten)]
+    pub parties: PartyArgs,
+    #[arg(long)]
+    pub mint: Pubkey,
+}
+
+impl ContractArgs {
+    pub fn addresses(&self) -> pivox_client::ContractAddresses {
+        pivox_client::ContractAddresses::new(&self.parties.client, &self.parties.freelancer, &self.mint)
+    }
+}
+
+#[derive(Args)]
+pub struct MilestoneArgs {
+    #[command(flatten)]
+    pub contract: ContractArgs,
+    #[arg(long)]
+    pub index: u64,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = commands::run(&cli) {
+        output::print_error(cli.output, &err);
+        std::process::exit(1);
+    }
+}
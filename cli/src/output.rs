@@ -0,0 +1,427 @@
+use std::path::Path;
+
+use pivox_client::{
+    ActivityLog, Bond, Contract, Milestone, MilestoneTemplate, Payee, PaymentReceipt, PayoutKind, Profile, ReceiptKind,
+    ReleasePolicy, TemplateAmount, VaultAccount,
+};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{terms::SignedOffer, tx::Outcome, OutputFormat};
+
+fn print_json(value: &Value) {
+    println!("{}", serde_json::to_string_pretty(value).expect("json value serializes"));
+}
+
+pub fn print_error(format: OutputFormat, err: &anyhow::Error) {
+    match format {
+        OutputFormat::Json => print_json(&json!({ "error": format!("{err:#}") })),
+        OutputFormat::Display => eprintln!("Error: {err:#}"),
+    }
+}
+
+pub fn print_outcome(format: OutputFormat, outcome: &Outcome) {
+    match (format, outcome) {
+        (OutputFormat::Json, Outcome::Sent(signature)) => {
+            print_json(&json!({ "signature": signature.to_string() }))
+        }
+        (OutputFormat::Display, Outcome::Sent(signature)) => println!("Signature: {signature}"),
+        (OutputFormat::Json, Outcome::Signed { transaction, signers }) => {
+            let signers: Vec<Value> = signers
+                .iter()
+                .map(|(key, sig)| json!({ "pubkey": key.to_string(), "signature": sig.map(|s| s.to_string()) }))
+                .collect();
+            print_json(&json!({ "transaction": transaction, "signers": signers }));
+        }
+        (OutputFormat::Display, Outcome::Signed { transaction, signers }) => {
+            println!("Signers:");
+            for (key, sig) in signers {
+                match sig {
+                    Some(sig) => println!("  {key}={sig}"),
+                    None => println!("  {key} (missing)"),
+                }
+            }
+            println!("Transaction:\n{transaction}");
+        }
+    }
+}
+
+pub fn print_offer_written(format: OutputFormat, path: &Path, offer: &SignedOffer) {
+    match format {
+        OutputFormat::Json => print_json(&json!({
+            "offer": path.display().to_string(),
+            "client": offer.client.to_string(),
+            "freelancer": offer.freelancer.to_string(),
+            "nonce": offer.nonce,
+            "expires_at": offer.expires_at,
+        })),
+        OutputFormat::Display => println!(
+            "Offer for {} written to {} (nonce {}, expires at {})",
+            offer.freelancer,
+            path.display(),
+            offer.nonce,
+            offer.expires_at
+        ),
+    }
+}
+
+pub fn print_contract(
+    format: OutputFormat,
+    address: &Pubkey,
+    contract: &Contract,
+    milestones: &[Milestone],
+    vault: Option<&VaultAccount>,
+) {
+    match format {
+        OutputFormat::Json => print_json(&contract_json(address, contract, milestones, vault)),
+        OutputFormat::Display => {
+            println!("Contract:        {address}");
+            println!("Status:          {}", contract.status);
+            println!("Client:          {}", contract.client);
+            println!("Freelancer:      {}", contract.freelancer);
+            println!("Shares:          client {}% / freelancer {}%", contract.client_share, contract.freelancer_share);
+            println!("Project start:   {}", contract.project_start);
+            println!("Duration:        {}s", contract.project_duration);
+            println!("Ordering:        {:?}", contract.ordering);
+            println!("Release policy:  {:?}", contract.release_policy);
+            if !contract.payees.is_empty() {
+                println!("Payees:          {}", payee_list(&contract.payees));
+            }
+            if let Some(parent) = contract.parent {
+                println!("Subcontract of:  {} milestone {}", parent.contract, parent.milestone_index);
+            }
+            println!(
+                "Termination:     client {} / freelancer {}",
+                approval(contract.client_approved),
+                approval(contract.freelancer_approved)
+            );
+            if let Some(vault) = vault {
+                println!("Vault balance:   {}", vault.balance);
+                println!("Vault status:    {}", vault.vault_status);
+                println!(
+                    "Vault ledger:    deposited {} / released {} / refunded {} / written off {}",
+                    vault.total_deposited, vault.total_released, vault.total_refunded, vault.total_written_off
+                );
+            }
+            println!("Milestones:");
+            for (index, m) in milestones.iter().enumerate() {
+                println!(
+                    "  [{index}] {:<32} {:>16}  funded={} submitted={} approved={} confirmed={} released={} cancelled={}",
+                    m.description,
+                    m.amount,
+                    m.funded,
+                    m.freelancer_submitted,
+                    m.client_approved,
+                    m.freelancer_confirmed,
+                    m.is_released,
+                    m.cancelled
+                );
+                if !m.depends_on.is_empty() {
+                    println!("      depends on {:?}", m.depends_on);
+                }
+                if !m.payees.is_empty() {
+                    println!("      paid to {}", payee_list(&m.payees));
+                }
+                if let Some(link) = m.subcontract {
+                    let state = if link.settled { "settled" } else { "open" };
+                    println!("      subcontracted to {} for {} ({state})", link.child, link.amount);
+                }
+                if let Some(p) = m.pricing {
+                    println!(
+                        "      priced at {} quote units via {} (reference {}, slippage {}bps, staleness {}s, confidence {}bps)",
+                        p.quote_amount, p.feed, p.reference_amount, p.max_slippage_bps, p.max_staleness, p.max_confidence_bps
+                    );
+                }
+            }
+            if !contract.payouts.is_empty() {
+                println!("Payouts:");
+                for p in &contract.payouts {
+                    println!("  {:>12}  {:<14} {:>16}  {}", p.paid_at, payout_kind(p.kind), p.amount, p.memo);
+                }
+            }
+        }
+    }
+}
+
+pub fn print_template(format: OutputFormat, address: &Pubkey, template: &MilestoneTemplate) {
+    match format {
+        OutputFormat::Json => {
+            let milestones: Vec<Value> = template
+                .milestones
+                .iter()
+                .map(|m| match m.amount {
+                    TemplateAmount::Percent(percent) => json!({ "description": m.description, "percent": percent }),
+                    TemplateAmount::Fixed(amount) => json!({ "description": m.description, "amount": amount }),
+                })
+                .collect();
+            print_json(&json!({
+                "address": address.to_string(),
+                "creator": template.creator.to_string(),
+                "name": template.name,
+                "milestones": milestones,
+            }));
+        }
+        OutputFormat::Display => {
+            println!("Template:        {address}");
+            println!("Name:            {}", template.name);
+            println!("Creator:         {}", template.creator);
+            println!("Milestones:");
+            for (index, m) in template.milestones.iter().enumerate() {
+                match m.amount {
+                    TemplateAmount::Percent(percent) => println!("  [{index}] {:<32} {:>15}%", m.description, percent),
+                    TemplateAmount::Fixed(amount) => println!("  [{index}] {:<32} {:>16}", m.description, amount),
+                }
+            }
+        }
+    }
+}
+
+pub fn print_profile(format: OutputFormat, address: &Pubkey, profile: &Profile) {
+    let on_time_rate = profile.on_time_rate_bps();
+    match format {
+        OutputFormat::Json => print_json(&json!({
+            "address": address.to_string(),
+            "owner": profile.owner.to_string(),
+            "contracts_completed": profile.contracts_completed,
+            "contracts_cancelled": profile.contracts_cancelled,
+            "volume_paid": profile.volume_paid,
+            "volume_earned": profile.volume_earned,
+            "milestones_delivered": profile.milestones_delivered,
+            "milestones_on_time": profile.milestones_on_time,
+            "on_time_rate_bps": on_time_rate,
+            "disputes_lost": profile.disputes_lost,
+        })),
+        OutputFormat::Display => {
+            println!("Profile:         {address}");
+            println!("Owner:           {}", profile.owner);
+            println!(
+                "Contracts:       {} completed / {} cancelled",
+                profile.contracts_completed, profile.contracts_cancelled
+            );
+            println!("Volume:          paid {} / earned {}", profile.volume_paid, profile.volume_earned);
+            match on_time_rate {
+                Some(bps) => println!(
+                    "On time:         {}/{} milestones ({}.{:02}%)",
+                    profile.milestones_on_time,
+                    profile.milestones_delivered,
+                    bps / 100,
+                    bps % 100
+                ),
+                None => println!("On time:         no deliveries yet"),
+            }
+            println!("Disputes lost:   {}", profile.disputes_lost);
+        }
+    }
+}
+
+pub fn print_bond(format: OutputFormat, address: &Pubkey, bond: &Bond) {
+    match format {
+        OutputFormat::Json => print_json(&json!({
+            "address": address.to_string(),
+            "contract": bond.contract.to_string(),
+            "client": bond.client.to_string(),
+            "freelancer": bond.freelancer.to_string(),
+            "posted": bond.posted,
+            "slashed": bond.slashed,
+            "returned": bond.returned,
+            "remaining": bond.remaining(),
+        })),
+        OutputFormat::Display => {
+            println!("Bond:            {address}");
+            println!("Contract:        {}", bond.contract);
+            println!("Freelancer:      {}", bond.freelancer);
+            println!(
+                "Amounts:         posted {} / slashed {} / returned {} / remaining {}",
+                bond.posted,
+                bond.slashed,
+                bond.returned,
+                bond.remaining()
+            );
+        }
+    }
+}
+
+pub fn print_receipts(format: OutputFormat, receipts: &[(Pubkey, PaymentReceipt)]) {
+    match format {
+        OutputFormat::Json => print_json(&Value::Array(
+            receipts
+                .iter()
+                .map(|(address, r)| {
+                    json!({
+                        "address": address.to_string(),
+                        "number": r.number,
+                        "contract": r.contract.to_string(),
+                        "kind": format!("{:?}", r.kind),
+                        "milestone_index": r.milestone_index,
+                        "payer": r.payer.to_string(),
+                        "payee": r.payee.to_string(),
+                        "mint": r.mint.to_string(),
+                        "amount": r.amount,
+                        "paid_at": r.paid_at,
+                        "invoice": r.invoice,
+                        "rent_payer": r.rent_payer.to_string(),
+                    })
+                })
+                .collect(),
+        )),
+        OutputFormat::Display => {
+            if receipts.is_empty() {
+                println!("No open receipts");
+            }
+            for (address, r) in receipts {
+                let kind = match (r.kind, r.milestone_index) {
+                    (ReceiptKind::MilestoneRelease, Some(index)) => format!("milestone {index}"),
+                    (kind, _) => format!("{kind:?}").to_lowercase(),
+                };
+                println!(
+                    "#{:<4} {address}  {:>12}  {:<14} {:>16}  {} -> {}  {}",
+                    r.number, r.paid_at, kind, r.amount, r.payer, r.payee, r.invoice
+                );
+            }
+        }
+    }
+}
+
+pub fn print_activity(format: OutputFormat, address: &Pubkey, log: &ActivityLog) {
+    match format {
+        OutputFormat::Json => print_json(&json!({
+            "address": address.to_string(),
+            "contract": log.contract.to_string(),
+            "total": log.total,
+            "entries": log
+                .chronological()
+                .map(|e| {
+                    json!({
+                        "slot": e.slot,
+                        "actor": e.actor.to_string(),
+                        "action": format!("{:?}", e.action),
+                        "milestone_index": e.milestone_index,
+                        "amount": e.amount,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })),
+        OutputFormat::Display => {
+            println!("Activity log:    {address}");
+            println!("Contract:        {}", log.contract);
+            println!("Entries:         {} of {} recorded", log.entries.len(), log.total);
+            for e in log.chronological() {
+                let milestone = e.milestone_index.map(|i| format!("milestone {i}")).unwrap_or_default();
+                println!("  {:>12}  {:<20} {:<12} {:>16}  {}", e.slot, format!("{:?}", e.action), milestone, e.amount, e.actor);
+            }
+        }
+    }
+}
+
+fn payout_kind(kind: PayoutKind) -> String {
+    match kind {
+        PayoutKind::Milestone { index } => format!("milestone {index}"),
+        PayoutKind::Bonus => "bonus".to_string(),
+        PayoutKind::Termination => "termination".to_string(),
+    }
+}
+
+fn release_policy(policy: ReleasePolicy) -> &'static str {
+    match policy {
+        ReleasePolicy::ClientApproval => "client_approval",
+        ReleasePolicy::BothConfirmed => "both_confirmed",
+        ReleasePolicy::FreelancerOnly => "freelancer_only",
+        ReleasePolicy::Permissionless => "permissionless",
+    }
+}
+
+fn payee_list(payees: &[Payee]) -> String {
+    payees.iter().map(|p| format!("{} {}bps", p.wallet, p.bps)).collect::<Vec<_>>().join(", ")
+}
+
+fn payees_json(payees: &[Payee]) -> Vec<Value> {
+    payees.iter().map(|p| json!({ "wallet": p.wallet.to_string(), "bps": p.bps })).collect()
+}
+
+fn approval(approved: bool) -> &'static str {
+    if approved {
+        "approved"
+    } else {
+        "pending"
+    }
+}
+
+fn contract_json(address: &Pubkey, contract: &Contract, milestones: &[Milestone], vault: Option<&VaultAccount>) -> Value {
+    let milestones: Vec<Value> = milestones
+        .iter()
+        .map(|m| {
+            json!({
+                "description": m.description,
+                "amount": m.amount,
+                "funded": m.funded,
+                "freelancer_submitted": m.freelancer_submitted,
+                "client_approved": m.client_approved,
+                "freelancer_confirmed": m.freelancer_confirmed,
+                "is_released": m.is_released,
+                "cancelled": m.cancelled,
+                "depends_on": m.depends_on,
+                "payees": payees_json(&m.payees),
+                "subcontract": m.subcontract.map(|link| json!({
+                    "child": link.child.to_string(),
+                    "amount": link.amount,
+                    "settled": link.settled,
+                })),
+                "pricing": m.pricing.map(|p| json!({
+                    "feed": p.feed.to_string(),
+                    "quote_amount": p.quote_amount,
+                    "reference_amount": p.reference_amount,
+                    "max_slippage_bps": p.max_slippage_bps,
+                    "max_staleness": p.max_staleness,
+                    "max_confidence_bps": p.max_confidence_bps,
+                })),
+            })
+        })
+        .collect();
+
+    let payouts: Vec<Value> = contract
+        .payouts
+        .iter()
+        .map(|p| {
+            json!({
+                "kind": payout_kind(p.kind),
+                "amount": p.amount,
+                "paid_at": p.paid_at,
+                "memo": p.memo,
+            })
+        })
+        .collect();
+
+    json!({
+        "address": address.to_string(),
+        "client": contract.client.to_string(),
+        "freelancer": contract.freelancer.to_string(),
+        "status": contract.status,
+        "client_share": contract.client_share,
+        "freelancer_share": contract.freelancer_share,
+        "initial_payment": contract.initial_payment,
+        "project_start": contract.project_start.to_string(),
+        "project_duration": contract.project_duration,
+        "dispute_resolution": contract.dispute_resolution,
+        "client_approved": contract.client_approved,
+        "freelancer_approved": contract.freelancer_approved,
+        "ordering": format!("{:?}", contract.ordering).to_lowercase(),
+        "release_policy": release_policy(contract.release_policy),
+        "payees": payees_json(&contract.payees),
+        "parent": contract.parent.map(|parent| json!({
+            "contract": parent.contract.to_string(),
+            "milestone_index": parent.milestone_index,
+        })),
+        "milestones": milestones,
+        "payouts": payouts,
+        "vault": vault.map(|v| json!({
+            "balance": v.balance,
+            "status": v.vault_status,
+            "client_share": v.client_share,
+            "freelancer_share": v.freelancer_share,
+            "total_deposited": v.total_deposited,
+            "total_released": v.total_released,
+            "total_refunded": v.total_refunded,
+            "total_written_off": v.total_written_off,
+        })),
+    })
+}
@@ -0,0 +1,303 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use pivox_client::{
+    Milestone, MilestoneOrdering, OfferTerms, Payee, QuotePricing, ReleasePolicy, TemplateAmount, TemplateMilestone,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+/// Contract terms as written by operators, e.g.
+///
+/// ```json
+/// {
+///   "client_share": 50,
+///   "freelancer_share": 50,
+///   "initial_payment": 0,
+///   "project_start": 1767225600,
+///   "project_duration": 2592000,
+///   "dispute_resolution": "Arbitration by ...",
+///   "ordering": "sequential",
+///   "release_policy": "both_confirmed",
+///   "payees": [{ "wallet": "<PUBKEY>", "bps": 7000 }, { "wallet": "<PUBKEY>", "bps": 3000 }],
+///   "milestones": [{ "description": "Design", "amount": 50000000 }]
+/// }
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TermsFile {
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    #[serde(default)]
+    pub initial_payment: u64,
+    pub project_start: i64,
+    pub project_duration: u64,
+    pub dispute_resolution: String,
+    #[serde(default)]
+    pub ordering: OrderingTerms,
+    #[serde(default)]
+    pub release_policy: ReleasePolicyTerms,
+    /// Release split in basis points summing to 10000; left out to pay the freelancer.
+    #[serde(default)]
+    pub payees: Vec<PayeeTerms>,
+    /// Left out when the milestones come from a template.
+    #[serde(default)]
+    pub milestones: Vec<MilestoneTerms>,
+}
+
+/// Milestone ordering policy: `any` (default), `sequential`, or `dependencies` to honour each
+/// milestone's `depends_on`.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderingTerms {
+    #[default]
+    Any,
+    Sequential,
+    Dependencies,
+}
+
+impl From<OrderingTerms> for MilestoneOrdering {
+    fn from(ordering: OrderingTerms) -> Self {
+        match ordering {
+            OrderingTerms::Any => MilestoneOrdering::Any,
+            OrderingTerms::Sequential => MilestoneOrdering::Sequential,
+            OrderingTerms::Dependencies => MilestoneOrdering::Dependencies,
+        }
+    }
+}
+
+/// Who releases approved milestones: `client_approval` (default), `both_confirmed`, `freelancer_only` or
+/// `permissionless`.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleasePolicyTerms {
+    #[default]
+    ClientApproval,
+    BothConfirmed,
+    FreelancerOnly,
+    Permissionless,
+}
+
+impl From<ReleasePolicyTerms> for ReleasePolicy {
+    fn from(policy: ReleasePolicyTerms) -> Self {
+        match policy {
+            ReleasePolicyTerms::ClientApproval => ReleasePolicy::ClientApproval,
+            ReleasePolicyTerms::BothConfirmed => ReleasePolicy::BothConfirmed,
+            ReleasePolicyTerms::FreelancerOnly => ReleasePolicy::FreelancerOnly,
+            ReleasePolicyTerms::Permissionless => ReleasePolicy::Permissionless,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MilestoneTerms {
+    pub description: String,
+    pub amount: u64,
+    /// Prerequisite milestone indices, for `"ordering": "dependencies"`.
+    #[serde(default)]
+    pub depends_on: Vec<u8>,
+    /// Overrides the contract's payees for this milestone.
+    #[serde(default)]
+    pub payees: Vec<PayeeTerms>,
+    /// Agrees the milestone in a quote currency, paid at the feed's price on release; `amount` is then the
+    /// token escrow and must cover `reference_amount` plus the slippage bound.
+    #[serde(default)]
+    pub pricing: Option<PricingTerms>,
+}
+
+/// e.g. `{ "feed": "<PYTH PRICE ACCOUNT>", "quote_amount": 500000000, "reference_amount": 3200000000,
+/// "max_slippage_bps": 1000, "max_staleness": 60, "max_confidence_bps": 100 }`, for 500 USD.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PricingTerms {
+    #[serde(with = "pubkey_string")]
+    pub feed: Pubkey,
+    pub quote_amount: u64,
+    pub reference_amount: u64,
+    pub max_slippage_bps: u16,
+    pub max_staleness: u32,
+    pub max_confidence_bps: u16,
+}
+
+impl From<PricingTerms> for QuotePricing {
+    fn from(p: PricingTerms) -> Self {
+        QuotePricing {
+            feed: p.feed,
+            quote_amount: p.quote_amount,
+            reference_amount: p.reference_amount,
+            max_slippage_bps: p.max_slippage_bps,
+            max_staleness: p.max_staleness,
+            max_confidence_bps: p.max_confidence_bps,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PayeeTerms {
+    #[serde(with = "pubkey_string")]
+    pub wallet: Pubkey,
+    pub bps: u16,
+}
+
+fn payees(terms: &[PayeeTerms]) -> Vec<Payee> {
+    terms.iter().map(|p| Payee { wallet: p.wallet, bps: p.bps }).collect()
+}
+
+impl TermsFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading terms file {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("parsing terms file {}", path.display()))
+    }
+
+    pub fn payees(&self) -> Vec<Payee> {
+        payees(&self.payees)
+    }
+
+    pub fn milestones(&self) -> Vec<Milestone> {
+        milestones(&self.milestones)
+    }
+}
+
+fn milestones(terms: &[MilestoneTerms]) -> Vec<Milestone> {
+    terms
+        .iter()
+        .map(|m| Milestone {
+            description: m.description.clone(),
+            amount: m.amount,
+            freelancer_submitted: false,
+            client_approved: false,
+            freelancer_confirmed: false,
+            is_released: false,
+            depends_on: m.depends_on.clone(),
+            subcontract: None,
+            payees: payees(&m.payees),
+            pricing: m.pricing.map(Into::into),
+            funded: false,
+            cancelled: false,
+        })
+        .collect()
+}
+
+/// Milestones to add to a running contract, in the same shape as the terms file's, e.g.
+///
+/// ```json
+/// { "milestones": [{ "description": "Phase 2", "amount": 25000000, "depends_on": [1] }] }
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MilestonesFile {
+    pub milestones: Vec<MilestoneTerms>,
+}
+
+impl MilestonesFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading milestones file {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("parsing milestones file {}", path.display()))
+    }
+
+    pub fn milestones(&self) -> Vec<Milestone> {
+        milestones(&self.milestones)
+    }
+}
+
+/// A milestone template, e.g.
+///
+/// ```json
+/// {
+///   "milestones": [
+///     { "description": "Kick-off fee", "amount": 1000000 },
+///     { "description": "Design", "percent": 40 },
+///     { "description": "Development", "percent": 60 }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TemplateFile {
+    pub milestones: Vec<TemplateMilestoneTerms>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TemplateMilestoneTerms {
+    pub description: String,
+    #[serde(default)]
+    pub percent: Option<u8>,
+    #[serde(default)]
+    pub amount: Option<u64>,
+}
+
+impl TemplateFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading template file {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("parsing template file {}", path.display()))
+    }
+
+    pub fn milestones(&self) -> Result<Vec<TemplateMilestone>> {
+        self.milestones
+            .iter()
+            .map(|m| {
+                let amount = match (m.percent, m.amount) {
+                    (Some(percent), None) => TemplateAmount::Percent(percent),
+                    (None, Some(amount)) => TemplateAmount::Fixed(amount),
+                    _ => bail!("milestone {:?} needs exactly one of `percent` or `amount`", m.description),
+                };
+                Ok(TemplateMilestone { description: m.description.clone(), amount })
+            })
+            .collect()
+    }
+}
+
+/// An offer signed by the client, handed to the freelancer out of band.
+#[derive(Serialize, Deserialize)]
+pub struct SignedOffer {
+    #[serde(with = "pubkey_string")]
+    pub client: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub freelancer: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub mint: Pubkey,
+    pub expires_at: i64,
+    pub nonce: u64,
+    pub terms: TermsFile,
+    pub signature: String,
+}
+
+impl SignedOffer {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading offer {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("parsing offer {}", path.display()))
+    }
+
+    pub fn offer_terms(&self) -> OfferTerms {
+        OfferTerms {
+            client: self.client,
+            freelancer: self.freelancer,
+            mint: self.mint,
+            client_share: self.terms.client_share,
+            freelancer_share: self.terms.freelancer_share,
+            initial_payment: self.terms.initial_payment,
+            project_start: self.terms.project_start as i128,
+            project_duration: self.terms.project_duration,
+            dispute_resolution: self.terms.dispute_resolution.clone(),
+            ordering: self.terms.ordering.into(),
+            release_policy: self.terms.release_policy.into(),
+            payees: self.terms.payees(),
+            milestones: self.terms.milestones(),
+            expires_at: self.expires_at,
+            nonce: self.nonce,
+        }
+    }
+
+    pub fn signature(&self) -> Result<Signature> {
+        self.signature.parse().context("invalid offer signature")
+    }
+}
+
+mod pubkey_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&key.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::Cli;
+
+/// What happened to a transaction: either it landed, or it was signed for someone else to finish.
+pub enum Outcome {
+    Sent(Signature),
+    Signed {
+        transaction: String,
+        signers: Vec<(Pubkey, Option<Signature>)>,
+    },
+}
+
+pub struct Session {
+    pub rpc: RpcClient,
+    pub signer: Keypair,
+    sign_only: bool,
+    blockhash: Option<Hash>,
+    fee_payer: Option<Pubkey>,
+}
+
+impl Session {
+    pub fn new(cli: &Cli) -> Result<Self> {
+        let keypair_path = match &cli.keypair {
+            Some(path) => path.clone(),
+            None => default_keypair_path()?,
+        };
+        let signer = read_keypair_file(&keypair_path)
+            .map_err(|e| anyhow!("reading keypair {}: {e}", keypair_path.display()))?;
+
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(rpc_url(&cli.url), CommitmentConfig::confirmed()),
+            signer,
+            sign_only: cli.sign_only,
+            blockhash: cli.blockhash,
+            fee_payer: cli.fee_payer,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    /// Signs `instructions` with the session keypair and sends them, or with `--sign-only` returns the
+    /// partially signed transaction without touching the network.
+    pub fn process(&self, instructions: &[Instruction]) -> Result<Outcome> {
+        let payer = self.fee_payer.unwrap_or_else(|| self.signer.pubkey());
+        let mut tx = Transaction::new_with_payer(instructions, Some(&payer));
+
+        let blockhash = match self.blockhash {
+            Some(hash) => hash,
+            None if self.sign_only => bail!("--sign-only requires --blockhash"),
+            None => self.rpc.get_latest_blockhash().context("fetching blockhash")?,
+        };
+        tx.try_partial_sign(&[&self.signer], blockhash)
+            .context("keypair is not a signer of this transaction")?;
+
+        self.finish(tx)
+    }
+
+    /// Adds the session keypair's signature to an encoded transaction.
+    pub fn sign_encoded(&self, encoded: &str) -> Result<Outcome> {
+        let mut tx = decode_transaction(encoded)?;
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[&self.signer], blockhash)
+            .context("keypair is not a signer of this transaction")?;
+        Ok(signed(&tx))
+    }
+
+    pub fn send_encoded(&self, encoded: &str) -> Result<Outcome> {
+        let tx = decode_transaction(encoded)?;
+        tx.verify().context("transaction is not fully signed")?;
+        self.send(&tx)
+    }
+
+    fn finish(&self, tx: Transaction) -> Result<Outcome> {
+        if self.sign_only || !tx.is_signed() {
+            return Ok(signed(&tx));
+        }
+        self.send(&tx)
+    }
+
+    fn send(&self, tx: &Transaction) -> Result<Outcome> {
+        self.rpc
+            .send_and_confirm_transaction(tx)
+            .map(Outcome::Sent)
+            .map_err(explain_error)
+    }
+}
+
+fn signed(tx: &Transaction) -> Outcome {
+    let required = tx.message.header.num_required_signatures as usize;
+    let signers = tx.message.account_keys[..required]
+        .iter()
+        .zip(&tx.signatures)
+        .map(|(key, sig)| (*key, (*sig != Signature::default()).then_some(*sig)))
+        .collect();
+
+    Outcome::Signed {
+        transaction: STANDARD.encode(bincode::serialize(tx).expect("transaction serializes")),
+        signers,
+    }
+}
+
+fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let bytes = STANDARD.decode(encoded.trim()).context("transaction is not valid base64")?;
+    bincode::deserialize(&bytes).context("transaction could not be decoded")
+}
+
+// Turns `Custom(n)` program failures into the PIVOX error name and message.
+fn explain_error(err: ClientError) -> anyhow::Error {
+    if let Some(TransactionError::InstructionError(index, InstructionError::Custom(code))) =
+        err.get_transaction_error()
+    {
+        if let Some(pivox_err) = pivox_client::decode_error(code) {
+            return anyhow!("instruction {index} failed: {pivox_err}");
+        }
+    }
+    anyhow!(err)
+}
+
+fn rpc_url(url: &str) -> String {
+    match url {
+        "localhost" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        other => other,
+    }
+    .to_string()
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").context("HOME is not set; pass --keypair")?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
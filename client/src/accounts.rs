@@ -0,0 +1,64 @@
+use anchor_lang::AccountDeserialize;
+use pivox_contract::state::{
+    decode_v0, ActivityLog, Bond, ConsumedOffer, Contract, ContractV0, MilestoneAccount, MilestoneApproval,
+    MilestoneTemplate, PaymentReceipt, Profile, VaultAccount, Versioned,
+};
+
+use crate::error::ClientError;
+
+/// Decodes raw account data, checking the anchor discriminator of `T`.
+pub fn decode_account<T: AccountDeserialize>(data: &[u8]) -> Result<T, ClientError> {
+    let mut data = data;
+    T::try_deserialize(&mut data).map_err(ClientError::AccountDecode)
+}
+
+pub fn decode_contract(data: &[u8]) -> Result<Contract, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_vault_account(data: &[u8]) -> Result<VaultAccount, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_milestone_approval(data: &[u8]) -> Result<MilestoneApproval, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_consumed_offer(data: &[u8]) -> Result<ConsumedOffer, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_milestone_template(data: &[u8]) -> Result<MilestoneTemplate, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_profile(data: &[u8]) -> Result<Profile, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_bond(data: &[u8]) -> Result<Bond, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_payment_receipt(data: &[u8]) -> Result<PaymentReceipt, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_activity_log(data: &[u8]) -> Result<ActivityLog, ClientError> {
+    decode_account(data)
+}
+
+pub fn decode_milestone_account(data: &[u8]) -> Result<MilestoneAccount, ClientError> {
+    decode_account(data)
+}
+
+/// Layout version of a contract, vault or approval account's raw data. Anything older than
+/// `LAYOUT_VERSION` has to be migrated before the `decode_*` functions above can read it.
+pub fn layout_version<T: Versioned>(data: &[u8]) -> Result<u8, ClientError> {
+    pivox_contract::state::layout_version::<T>(data).map_err(ClientError::AccountDecode)
+}
+
+/// Decodes a contract still in layout 0, e.g. to count the milestones `migrate` has to move out of it.
+pub fn decode_legacy_contract(data: &[u8]) -> Result<ContractV0, ClientError> {
+    decode_v0::<Contract>(data).map_err(ClientError::AccountDecode)
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use pivox_contract::{
+    instructions::{
+        milestone_signature::ErrorCode as SignatureError, BonusError, ContractError, MigrateError, MilestoneError,
+        OfferError, ReassignError, SubcontractError, VaultDepositError, VaultWithdrawError,
+    },
+    state::{BondError, LedgerError, PayeeError, PriceError, ReceiptError, TemplateError, TermsError},
+};
+
+#[derive(Debug)]
+pub enum ClientError {
+    AccountDecode(anchor_lang::error::Error),
+    Serialize(anchor_lang::error::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::AccountDecode(e) => write!(f, "failed to decode account: {e}"),
+            ClientError::Serialize(e) => write!(f, "failed to serialize: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// A custom error returned by the PIVOX program, decoded from its numeric code.
+#[derive(Debug, Clone, Copy)]
+pub enum PivoxError {
+    Signature(SignatureError),
+    Milestone(MilestoneError),
+    Contract(ContractError),
+    VaultDeposit(VaultDepositError),
+    VaultWithdraw(VaultWithdrawError),
+    Offer(OfferError),
+    Ledger(LedgerError),
+    Template(TemplateError),
+    Bond(BondError),
+    Bonus(BonusError),
+    Reassign(ReassignError),
+    Subcontract(SubcontractError),
+    Payee(PayeeError),
+    Receipt(ReceiptError),
+    Migrate(MigrateError),
+    Terms(TermsError),
+    Price(PriceError),
+}
+
+macro_rules! match_code {
+    ($code:expr, $wrap:path, $ty:ident { $($variant:ident),* $(,)? }) => {
+        $(
+            if $code == u32::from($ty::$variant) {
+                return Some($wrap($ty::$variant));
+            }
+        )*
+    };
+}
+
+impl PivoxError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        match_code!(code, PivoxError::Signature, SignatureError {
+            AlreadyApproved,
+            AlreadySigned,
+            ClientSignatureRequired,
+            TermsMismatch,
+        });
+        match_code!(code, PivoxError::Milestone, MilestoneError {
+            InvalidSigner,
+            AlreadySubmitted,
+            AlreadyApproved,
+            AlreadyConfirmed,
+            NotSubmitted,
+            NotApproved,
+            OutOfOrder,
+            InvalidDependency,
+            InvalidMilestoneAccount,
+            MissingPrerequisites,
+            TooManyMilestones,
+            ContractTerminated,
+            NotConfirmed,
+            NotFunded,
+            Cancelled,
+            MilestoneAccountInUse,
+        });
+        match_code!(code, PivoxError::Contract, ContractError { Unauthorized, MilestoneError, AlreadyReleased });
+        match_code!(code, PivoxError::VaultDeposit, VaultDepositError {
+            Overflow,
+            InsufficientFunds,
+            AlreadyFunded,
+            MilestoneAmountMismatch,
+        });
+        match_code!(code, PivoxError::VaultWithdraw, VaultWithdrawError { Unauthorized, NotAParty, NothingToWithdraw });
+        match_code!(code, PivoxError::Offer, OfferError {
+            PartyMismatch,
+            MintMismatch,
+            OfferExpired,
+            MissingSignatureInstruction,
+            InvalidSignatureInstruction,
+            SignerMismatch,
+            TermsMismatch,
+        });
+        match_code!(code, PivoxError::Ledger, LedgerError { Overflow, InsufficientBalance, Unauthorized, Underflow });
+        match_code!(code, PivoxError::Template, TemplateError {
+            InvalidName,
+            InvalidMilestoneCount,
+            DescriptionTooLong,
+            InvalidPercentages,
+            BudgetMismatch,
+            ZeroAmount,
+            MissingTemplate,
+        });
+        match_code!(code, PivoxError::Bond, BondError {
+            MissingBondAccounts,
+            ZeroAmount,
+            Unauthorized,
+            ContractTerminated,
+            NotTerminated,
+            DeadlineNotPassed,
+            NothingOutstanding,
+            NotAbandoned,
+            ExceedsSlashable,
+            Overflow,
+        });
+        match_code!(code, PivoxError::Bonus, BonusError { Unauthorized, ZeroAmount, MemoTooLong, InsufficientSurplus });
+        match_code!(code, PivoxError::Reassign, ReassignError {
+            InvalidReassignment,
+            IncomingMismatch,
+            ContractTerminated,
+            MissingBondAccounts,
+            SubcontractLinked,
+            NoBond,
+            MissingMilestones,
+        });
+        match_code!(code, PivoxError::Subcontract, SubcontractError {
+            Unauthorized,
+            InvalidChild,
+            AlreadySubcontracted,
+            MilestoneReleased,
+            NothingToFund,
+            ExceedsMilestone,
+            NotLinked,
+            ConsentRequired,
+            Unsettled,
+            SettleInstead,
+            LinkNotAllowed,
+            PricedMilestone,
+        });
+        match_code!(code, PivoxError::Payee, PayeeError {
+            TooManyPayees,
+            ZeroWeight,
+            DuplicatePayee,
+            InvalidTotal,
+            MissingPayeeAccounts,
+            PayeeAccountMismatch,
+        });
+        match_code!(code, PivoxError::Receipt, ReceiptError { InvalidReceiptAccount, InvoiceTooLong, Unauthorized });
+        match_code!(code, PivoxError::Migrate, MigrateError { Unauthorized, AlreadyCurrent, UnknownLayout });
+        match_code!(code, PivoxError::Terms, TermsError {
+            InvalidShares,
+            NoMilestones,
+            ZeroAmountMilestone,
+            MilestoneTotalOverflow,
+            StartInPast,
+            MilestoneAlreadyStarted,
+            InvalidPricing,
+            PricingNotCovered,
+        });
+        match_code!(code, PivoxError::Price, PriceError {
+            MissingPriceFeed,
+            PriceFeedMismatch,
+            InvalidPriceAccount,
+            PriceNotTrading,
+            NonPositivePrice,
+            StalePrice,
+            PriceTooUncertain,
+            SlippageExceeded,
+            ConversionOverflow,
+        });
+        None
+    }
+
+    pub fn code(&self) -> u32 {
+        match *self {
+            PivoxError::Signature(e) => e.into(),
+            PivoxError::Milestone(e) => e.into(),
+            PivoxError::Contract(e) => e.into(),
+            PivoxError::VaultDeposit(e) => e.into(),
+            PivoxError::VaultWithdraw(e) => e.into(),
+            PivoxError::Offer(e) => e.into(),
+            PivoxError::Ledger(e) => e.into(),
+            PivoxError::Template(e) => e.into(),
+            PivoxError::Bond(e) => e.into(),
+            PivoxError::Bonus(e) => e.into(),
+            PivoxError::Reassign(e) => e.into(),
+            PivoxError::Subcontract(e) => e.into(),
+            PivoxError::Payee(e) => e.into(),
+            PivoxError::Receipt(e) => e.into(),
+            PivoxError::Migrate(e) => e.into(),
+            PivoxError::Terms(e) => e.into(),
+            PivoxError::Price(e) => e.into(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            PivoxError::Signature(e) => e.name(),
+            PivoxError::Milestone(e) => e.name(),
+            PivoxError::Contract(e) => e.name(),
+            PivoxError::VaultDeposit(e) => e.name(),
+            PivoxError::VaultWithdraw(e) => e.name(),
+            PivoxError::Offer(e) => e.name(),
+            PivoxError::Ledger(e) => e.name(),
+            PivoxError::Template(e) => e.name(),
+            PivoxError::Bond(e) => e.name(),
+            PivoxError::Bonus(e) => e.name(),
+            PivoxError::Reassign(e) => e.name(),
+            PivoxError::Subcontract(e) => e.name(),
+            PivoxError::Payee(e) => e.name(),
+            PivoxError::Receipt(e) => e.name(),
+            PivoxError::Migrate(e) => e.name(),
+            PivoxError::Terms(e) => e.name(),
+            PivoxError::Price(e) => e.name(),
+        }
+    }
+}
+
+impl fmt::Display for PivoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            PivoxError::Signature(e) => e.to_string(),
+            PivoxError::Milestone(e) => e.to_string(),
+            PivoxError::Contract(e) => e.to_string(),
+            PivoxError::VaultDeposit(e) => e.to_string(),
+            PivoxError::VaultWithdraw(e) => e.to_string(),
+            PivoxError::Offer(e) => e.to_string(),
+            PivoxError::Ledger(e) => e.to_string(),
+            PivoxError::Template(e) => e.to_string(),
+            PivoxError::Bond(e) => e.to_string(),
+            PivoxError::Bonus(e) => e.to_string(),
+            PivoxError::Reassign(e) => e.to_string(),
+            PivoxError::Subcontract(e) => e.to_string(),
+            PivoxError::Payee(e) => e.to_string(),
+            PivoxError::Receipt(e) => e.to_string(),
+            PivoxError::Migrate(e) => e.to_string(),
+            PivoxError::Terms(e) => e.to_string(),
+            PivoxError::Price(e) => e.to_string(),
+        };
+        write!(f, "{} ({}): {}", self.name(), self.code(), message)
+    }
+}
+
+/// Decodes the code carried by `InstructionError::Custom` into a PIVOX program error.
+///
+/// Each error enum has its own range of 100 codes. This is a wire change: programs deployed before this
+/// crate numbered every enum from 6000, so the codes of their `approve`, milestone, release, deposit,
+/// withdrawal and offer errors overlapped. Codes from such a program are not decoded correctly here.
+pub fn decode_error(code: u32) -> Option<PivoxError> {
+    PivoxError::from_code(code)
+}
@@ -0,0 +1,18 @@
+//! Decoding of the events the program emits, from a transaction's log messages.
+
+use anchor_lang::Event;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Every `E` emitted in `logs`, in order. Lines that are not `E` events are skipped.
+pub fn decode_events<E: Event>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| {
+            let body = data.strip_prefix(E::DISCRIMINATOR)?;
+            E::try_from_slice(body).ok()
+        })
+        .collect()
+}
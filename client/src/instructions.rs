@@ -0,0 +1,665 @@
+//! Typed builders for every `pivox_contract` instruction.
+//!
+//! Account lists come from the program's own generated `accounts` structs, so a builder stops compiling
+//! as soon as an instruction's accounts change.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        sysvar::instructions as sysvar_instructions,
+    },
+    system_program, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token, token};
+use pivox_contract::{
+    accounts, instruction,
+    state::{BonusSource, ContractTerms, Milestone, MilestoneOrdering, Payee, ReleasePolicy, SlashReason, TemplateMilestone},
+    OfferTerms, ID,
+};
+
+use crate::error::ClientError;
+use crate::pda::{
+    consumed_offer_address, milestone_approval_address, milestone_template_address, receipt_address,
+    ContractAddresses,
+};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Appends the accounts of `count` new milestones, numbered on from `first`, for the program to create.
+fn append_new_milestones(ix: &mut Instruction, addresses: &ContractAddresses, first: u8, count: usize) {
+    ix.accounts.extend((0..count).map(|i| AccountMeta::new(addresses.milestone(first + i as u8), false)));
+}
+
+/// The client proposes a contract to `freelancer`, who accepts it with [`approve`] or
+/// [`approve_from_template`] and the terms `terms_hash` was made from ([`ApproveArgs::terms_hash`],
+/// [`TemplateApproveArgs::terms_hash`]).
+pub fn propose(client: &Pubkey, freelancer: &Pubkey, terms_hash: [u8; 32]) -> Instruction {
+    build(
+        accounts::Propose {
+            client: *client,
+            freelancer: *freelancer,
+            milestone_approval: milestone_approval_address(client, freelancer).0,
+            system_program: system_program::ID,
+        },
+        instruction::Propose { terms_hash },
+    )
+}
+
+/// Contract terms the freelancer signs with `approve`.
+#[derive(Clone)]
+pub struct ApproveArgs {
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    pub initial_payment: u64,
+    pub project_start: i128,
+    pub project_duration: u64,
+    pub dispute_resolution: String,
+    pub ordering: MilestoneOrdering,
+    pub release_policy: ReleasePolicy,
+    /// Default release split; empty pays the freelancer. Milestones can carry their own.
+    pub payees: Vec<Payee>,
+    /// Performance bond the freelancer posts from their ATA; 0 for none.
+    pub bond_amount: u64,
+    /// Funds the client moves into the vault in the same transaction, which they co-sign; 0 for none.
+    pub deposit: u64,
+    pub milestones: Vec<Milestone>,
+}
+
+impl ApproveArgs {
+    fn terms(&self) -> ContractTerms {
+        ContractTerms {
+            client_share: self.client_share,
+            freelancer_share: self.freelancer_share,
+            initial_payment: self.initial_payment,
+            project_start: self.project_start,
+            project_duration: self.project_duration,
+            dispute_resolution: self.dispute_resolution.clone(),
+            ordering: self.ordering,
+            release_policy: self.release_policy,
+            payees: self.payees.clone(),
+            bond_amount: self.bond_amount,
+            deposit: self.deposit,
+        }
+    }
+
+    /// What the client passes to [`propose`] for these terms.
+    pub fn terms_hash(&self) -> Result<[u8; 32], ClientError> {
+        self.terms().hash(&self.milestones).map_err(ClientError::Serialize)
+    }
+}
+
+/// Marks the client as a signer of an `approve` that makes a first deposit.
+fn client_signs_deposit(ix: &mut Instruction, addresses: &ContractAddresses, deposit: u64) {
+    if deposit > 0 {
+        ix.accounts.iter_mut().filter(|meta| meta.pubkey == addresses.client).for_each(|meta| meta.is_signer = true);
+    }
+}
+
+fn milestone_signature(
+    addresses: &ContractAddresses,
+    template: Option<Pubkey>,
+    bond_amount: u64,
+) -> accounts::MilestoneSignature {
+    let bonded = bond_amount > 0;
+    accounts::MilestoneSignature {
+        freelancer: addresses.freelancer,
+        client: addresses.client,
+        usdc_mint: addresses.mint,
+        milestone_approval: addresses.milestone_approval,
+        vault_account: addresses.vault_account,
+        contract: addresses.contract,
+        activity_log: addresses.activity_log,
+        freelancer_ata: addresses.freelancer_ata,
+        client_ata: addresses.client_ata,
+        vault_ata: addresses.vault_ata,
+        template,
+        bond: bonded.then_some(addresses.bond),
+        bond_ata: bonded.then_some(addresses.bond_ata),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    }
+}
+
+pub fn approve(addresses: &ContractAddresses, args: ApproveArgs) -> Instruction {
+    let count = args.milestones.len();
+    let mut ix = build(
+        milestone_signature(addresses, None, args.bond_amount),
+        instruction::Approve { terms: args.terms(), milestones: args.milestones },
+    );
+    client_signs_deposit(&mut ix, addresses, args.deposit);
+    append_new_milestones(&mut ix, addresses, 0, count);
+    ix
+}
+
+/// Contract terms for `approve_from_template`; the milestones come from `template` scaled to `total_budget`.
+#[derive(Clone)]
+pub struct TemplateApproveArgs {
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    pub initial_payment: u64,
+    pub project_start: i128,
+    pub project_duration: u64,
+    pub dispute_resolution: String,
+    pub ordering: MilestoneOrdering,
+    pub release_policy: ReleasePolicy,
+    pub payees: Vec<Payee>,
+    pub bond_amount: u64,
+    /// See [`ApproveArgs::deposit`].
+    pub deposit: u64,
+    pub template: Pubkey,
+    pub total_budget: u64,
+    /// Number of milestones in `template`, whose accounts are created with the contract.
+    pub milestone_count: u8,
+}
+
+impl TemplateApproveArgs {
+    fn terms(&self) -> ContractTerms {
+        ContractTerms {
+            client_share: self.client_share,
+            freelancer_share: self.freelancer_share,
+            initial_payment: self.initial_payment,
+            project_start: self.project_start,
+            project_duration: self.project_duration,
+            dispute_resolution: self.dispute_resolution.clone(),
+            ordering: self.ordering,
+            release_policy: self.release_policy,
+            payees: self.payees.clone(),
+            bond_amount: self.bond_amount,
+            deposit: self.deposit,
+        }
+    }
+
+    /// What the client passes to [`propose`] for these terms; `milestones` are the template's, from
+    /// [`pivox_contract::state::MilestoneTemplate::instantiate`] with `total_budget`.
+    pub fn terms_hash(&self, milestones: &[Milestone]) -> Result<[u8; 32], ClientError> {
+        self.terms().hash(milestones).map_err(ClientError::Serialize)
+    }
+}
+
+pub fn approve_from_template(addresses: &ContractAddresses, args: TemplateApproveArgs) -> Instruction {
+    let count = args.milestone_count as usize;
+    let mut ix = build(
+        milestone_signature(addresses, Some(args.template), args.bond_amount),
+        instruction::ApproveFromTemplate { terms: args.terms(), total_budget: args.total_budget },
+    );
+    client_signs_deposit(&mut ix, addresses, args.deposit);
+    append_new_milestones(&mut ix, addresses, 0, count);
+    ix
+}
+
+pub fn create_milestone_template(creator: &Pubkey, name: &str, milestones: Vec<TemplateMilestone>) -> Instruction {
+    build(
+        accounts::CreateMilestoneTemplate {
+            creator: *creator,
+            template: milestone_template_address(creator, name).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateMilestoneTemplate { name: name.to_string(), milestones },
+    )
+}
+
+pub fn close_milestone_template(creator: &Pubkey, name: &str) -> Instruction {
+    build(
+        accounts::CloseMilestoneTemplate {
+            creator: *creator,
+            template: milestone_template_address(creator, name).0,
+        },
+        instruction::CloseMilestoneTemplate {},
+    )
+}
+
+/// The `accept_offer` instruction. It must directly follow the ed25519 instruction built by
+/// [`crate::offer::ed25519_instruction`] for the same terms.
+pub fn accept_offer(terms: OfferTerms) -> Instruction {
+    let addresses = ContractAddresses::new(&terms.client, &terms.freelancer, &terms.mint);
+    let count = terms.milestones.len();
+    let mut ix = build(
+        accounts::AcceptOffer {
+            freelancer: addresses.freelancer,
+            client: addresses.client,
+            usdc_mint: addresses.mint,
+            consumed_offer: consumed_offer_address(&terms.client, terms.nonce).0,
+            milestone_approval: addresses.milestone_approval,
+            vault_account: addresses.vault_account,
+            contract: addresses.contract,
+            activity_log: addresses.activity_log,
+            vault_ata: addresses.vault_ata,
+            instructions_sysvar: sysvar_instructions::ID,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::AcceptOffer { terms },
+    );
+    append_new_milestones(&mut ix, &addresses, 0, count);
+    ix
+}
+
+/// Both parties add `milestones` to a running contract that already has `milestone_count` of them.
+pub fn add_milestones(addresses: &ContractAddresses, milestone_count: u8, milestones: Vec<Milestone>) -> Instruction {
+    let count = milestones.len();
+    let mut ix = build(
+        accounts::AddMilestones {
+            client: addresses.client,
+            freelancer: addresses.freelancer,
+            contract: addresses.contract,
+            activity_log: addresses.activity_log,
+            system_program: system_program::ID,
+        },
+        instruction::AddMilestones { milestones },
+    );
+    append_new_milestones(&mut ix, addresses, milestone_count, count);
+    ix
+}
+
+/// Both parties drop milestone `milestone_index`; any escrow refunded to the client is receipted at
+/// `receipt_number`.
+pub fn cancel_milestone(addresses: &ContractAddresses, milestone_index: u64, receipt_number: u64) -> Instruction {
+    build(
+        accounts::CancelMilestone {
+            client: addresses.client,
+            freelancer: addresses.freelancer,
+            contract: addresses.contract,
+            milestone: addresses.milestone(milestone_index as u8),
+            vault_account: addresses.vault_account,
+            usdc_mint: addresses.mint,
+            vault_ata: addresses.vault_ata,
+            client_ata: addresses.client_ata,
+            activity_log: addresses.activity_log,
+            receipt: receipt_address(&addresses.contract_id, receipt_number).0,
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        instruction::CancelMilestone { milestone_index },
+    )
+}
+
+/// Upgrades the contract's accounts to the current layout; either party signs. `legacy_milestones` is the
+/// number of milestones a layout 0 contract holds inline (`ContractV0::milestones`), whose accounts the
+/// program creates; pass 0 for anything newer.
+pub fn migrate(addresses: &ContractAddresses, signer: &Pubkey, legacy_milestones: u8) -> Instruction {
+    let mut ix = build(
+        accounts::Migrate {
+            signer: *signer,
+            client: addresses.client,
+            freelancer: addresses.freelancer,
+            contract: addresses.contract,
+            vault_account: addresses.vault_account,
+            milestone_approval: addresses.milestone_approval,
+            activity_log: addresses.activity_log,
+            system_program: system_program::ID,
+        },
+        instruction::Migrate {},
+    );
+    append_new_milestones(&mut ix, addresses, 0, legacy_milestones as usize);
+    ix
+}
+
+fn contract_func(
+    addresses: &ContractAddresses,
+    signer: &Pubkey,
+    milestone: Option<Pubkey>,
+    receipt_number: u64,
+) -> accounts::ContractFunc {
+    accounts::ContractFunc {
+        signer: *signer,
+        contract: addresses.contract,
+        activity_log: addresses.activity_log,
+        milestone,
+        vault_account: addresses.vault_account,
+        vault_ata: addresses.vault_ata,
+        freelancer_ata: addresses.freelancer_ata,
+        client_ata: addresses.client_ata,
+        price_feed: None,
+        usdc_mint: addresses.mint,
+        client_profile: addresses.client_profile,
+        freelancer_profile: addresses.freelancer_profile,
+        receipt: receipt_address(&addresses.contract_id, receipt_number).0,
+        refund_receipt: None,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    }
+}
+
+/// Approves termination. `receipt_number` is the contract's current `receipt_count`, used for the receipt
+/// of the final payout when this approval terminates the contract.
+pub fn complete_or_cancel_contract(
+    addresses: &ContractAddresses,
+    signer: &Pubkey,
+    receipt_number: u64,
+    invoice: &str,
+) -> Instruction {
+    build(
+        contract_func(addresses, signer, None, receipt_number),
+        instruction::CompleteOrCancelContract { invoice: invoice.to_string() },
+    )
+}
+
+/// Releases a milestone. `payees` is its split, from [`pivox_contract::state::Contract::payees_for`];
+/// each payee's ATA is appended in order, and an empty list pays the freelancer. A milestone with
+/// `pricing` needs its `price_feed`. The payment's receipt is created at `receipt_number`, the contract's
+/// current `receipt_count`, and for a priced milestone the receipt for any refund at the number after it.
+pub fn release_milestone_payment(
+    addresses: &ContractAddresses,
+    signer: &Pubkey,
+    milestone_index: u64,
+    payees: &[Payee],
+    price_feed: Option<Pubkey>,
+    receipt_number: u64,
+    invoice: &str,
+) -> Instruction {
+    let mut ix = build(
+        accounts::ContractFunc {
+            price_feed,
+            refund_receipt: price_feed.map(|_| receipt_address(&addresses.contract_id, receipt_number + 1).0),
+            ..contract_func(addresses, signer, Some(addresses.milestone(milestone_index as u8)), receipt_number)
+        },
+        instruction::ReleaseMilestonePayment { milestone_index, invoice: invoice.to_string() },
+    );
+    ix.accounts.extend(payees.iter().map(|payee| {
+        AccountMeta::new(associated_token::get_associated_token_address(&payee.wallet, &addresses.mint), false)
+    }));
+    ix
+}
+
+fn vault_deposit(addresses: &ContractAddresses, depositor: &Pubkey, milestone: Option<Pubkey>) -> accounts::VaultDeposit {
+    accounts::VaultDeposit {
+        depositor: *depositor,
+        depositor_ata: associated_token::get_associated_token_address(depositor, &addresses.mint),
+        client: addresses.client,
+        freelancer: addresses.freelancer,
+        usdc_mint: addresses.mint,
+        vault_account: addresses.vault_account,
+        vault_ata: addresses.vault_ata,
+        contract: addresses.contract,
+        milestone,
+        activity_log: addresses.activity_log,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    }
+}
+
+pub fn deposit_funds(addresses: &ContractAddresses, depositor: &Pubkey, amount: u64) -> Instruction {
+    build(
+        vault_deposit(addresses, depositor, None),
+        instruction::DepositFunds { amount, milestone_index: None },
+    )
+}
+
+/// A deposit tagged to milestone `milestone_index`; `amount` must be exactly that milestone's escrow.
+pub fn fund_milestone(addresses: &ContractAddresses, depositor: &Pubkey, milestone_index: u64, amount: u64) -> Instruction {
+    build(
+        vault_deposit(addresses, depositor, Some(addresses.milestone(milestone_index as u8))),
+        instruction::DepositFunds { amount, milestone_index: Some(milestone_index) },
+    )
+}
+
+/// `signer`'s share of what termination left in the vault, paid to their own ATA, with a receipt at
+/// `receipt_number`.
+pub fn withdraw_funds(addresses: &ContractAddresses, signer: &Pubkey, receipt_number: u64) -> Instruction {
+    build(
+        accounts::VaultWithdraw {
+            signer: *signer,
+            usdc_mint: addresses.mint,
+            vault_account: addresses.vault_account,
+            contract: addresses.contract,
+            vault_ata: addresses.vault_ata,
+            destination: associated_token::get_associated_token_address(signer, &addresses.mint),
+            client_profile: addresses.client_profile,
+            freelancer_profile: addresses.freelancer_profile,
+            activity_log: addresses.activity_log,
+            receipt: receipt_address(&addresses.contract_id, receipt_number).0,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::WithdrawFunds {},
+    )
+}
+
+/// Squares the vault's ledger with its ATA; any untracked surplus is refunded to the client, with a receipt
+/// at `receipt_number`.
+pub fn reconcile(addresses: &ContractAddresses, signer: &Pubkey, receipt_number: u64) -> Instruction {
+    build(
+        accounts::Reconcile {
+            signer: *signer,
+            contract: addresses.contract,
+            vault_account: addresses.vault_account,
+            vault_ata: addresses.vault_ata,
+            client_ata: addresses.client_ata,
+            activity_log: addresses.activity_log,
+            usdc_mint: addresses.mint,
+            receipt: receipt_address(&addresses.contract_id, receipt_number).0,
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        instruction::Reconcile {},
+    )
+}
+
+fn bond_settlement(addresses: &ContractAddresses, signer: &Pubkey) -> accounts::BondSettlement {
+    accounts::BondSettlement {
+        signer: *signer,
+        freelancer: addresses.freelancer,
+        contract: addresses.contract,
+        vault_account: addresses.vault_account,
+        bond: addresses.bond,
+        bond_ata: addresses.bond_ata,
+        client_ata: addresses.client_ata,
+        freelancer_ata: addresses.freelancer_ata,
+        usdc_mint: addresses.mint,
+        freelancer_profile: addresses.freelancer_profile,
+        activity_log: addresses.activity_log,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    }
+}
+
+/// Moves `amount` of the freelancer's bond to the client for `reason`. A `DisputeLost` slash is the
+/// client's, with the freelancer co-signing.
+pub fn slash_bond(addresses: &ContractAddresses, signer: &Pubkey, amount: u64, reason: SlashReason) -> Instruction {
+    let mut ix = build(bond_settlement(addresses, signer), instruction::SlashBond { amount, reason });
+    ix.accounts[1].is_signer = reason == SlashReason::DisputeLost;
+    ix
+}
+
+/// Returns the rest of the bond to the freelancer after termination and closes the bond.
+pub fn return_bond(addresses: &ContractAddresses, signer: &Pubkey) -> Instruction {
+    build(bond_settlement(addresses, signer), instruction::ReturnBond {})
+}
+
+/// Moves the contract to a new client or freelancer; the current client, the current freelancer and the
+/// incoming wallet must all sign. `bonded` says whether the freelancer posted a bond, which moves too. All
+/// `milestone_count` milestone accounts are passed, for their payees to be re-keyed.
+pub fn reassign_party(
+    addresses: &ContractAddresses,
+    new_client: &Pubkey,
+    new_freelancer: &Pubkey,
+    bonded: bool,
+    milestone_count: u16,
+) -> Instruction {
+    let new = ContractAddresses::new(new_client, new_freelancer, &addresses.mint);
+    let incoming = if *new_client != addresses.client { *new_client } else { *new_freelancer };
+    let mut ix = build(
+        accounts::ReassignParty {
+            client: addresses.client,
+            freelancer: addresses.freelancer,
+            incoming,
+            contract: addresses.contract,
+            vault_account: addresses.vault_account,
+            milestone_approval: addresses.milestone_approval,
+            activity_log: addresses.activity_log,
+            vault_ata: addresses.vault_ata,
+            new_contract: new.contract,
+            new_vault_account: new.vault_account,
+            new_milestone_approval: new.milestone_approval,
+            new_activity_log: new.activity_log,
+            new_vault_ata: new.vault_ata,
+            bond: addresses.bond,
+            bond_ata: bonded.then_some(addresses.bond_ata),
+            new_bond: bonded.then_some(new.bond),
+            new_bond_ata: bonded.then_some(new.bond_ata),
+            usdc_mint: addresses.mint,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::ReassignParty { new_client: *new_client, new_freelancer: *new_freelancer },
+    );
+    ix.accounts.extend((0..milestone_count).map(|i| AccountMeta::new(addresses.milestone(i as u8), false)));
+    ix
+}
+
+/// Client pays the freelancer a bonus outside the milestone schedule.
+pub fn pay_bonus(addresses: &ContractAddresses, amount: u64, source: BonusSource, memo: &str) -> Instruction {
+    build(
+        accounts::PayBonus {
+            client: addresses.client,
+            contract: addresses.contract,
+            vault_account: addresses.vault_account,
+            vault_ata: addresses.vault_ata,
+            client_ata: addresses.client_ata,
+            freelancer_ata: addresses.freelancer_ata,
+            activity_log: addresses.activity_log,
+            usdc_mint: addresses.mint,
+            token_program: token::ID,
+        },
+        instruction::PayBonus { amount, source, memo: memo.to_string() },
+    )
+}
+
+/// The parent milestone's freelancer funds the `child` contract, where they are the client, out of that
+/// milestone's escrow. The parent's client co-signs.
+pub fn fund_subcontract(parent: &ContractAddresses, child: &ContractAddresses, milestone_index: u8) -> Instruction {
+    build(
+        accounts::FundSubcontract {
+            freelancer: parent.freelancer,
+            parent_client: parent.client,
+            parent_contract: parent.contract,
+            parent_milestone: parent.milestone(milestone_index),
+            parent_vault_account: parent.vault_account,
+            parent_vault_ata: parent.vault_ata,
+            child_contract: child.contract,
+            child_vault_account: child.vault_account,
+            child_vault_ata: child.vault_ata,
+            parent_activity_log: parent.activity_log,
+            child_activity_log: child.activity_log,
+            usdc_mint: child.mint,
+            token_program: token::ID,
+        },
+        instruction::FundSubcontract { milestone_index },
+    )
+}
+
+/// Closes the `child` subcontract of `parent`'s milestone `milestone_index` and returns what it still holds
+/// to its client, with a receipt at the child's `receipt_number`. The subcontractor must also sign, with
+/// `subcontractor_signs`, while any child milestone is unreleased.
+pub fn settle_subcontract(
+    parent: &ContractAddresses,
+    milestone_index: u8,
+    child: &ContractAddresses,
+    subcontractor_signs: bool,
+    receipt_number: u64,
+) -> Instruction {
+    let mut ix = build(
+        accounts::SettleSubcontract {
+            client: child.client,
+            freelancer: child.freelancer,
+            parent_contract: parent.contract,
+            parent_milestone: parent.milestone(milestone_index),
+            child_contract: child.contract,
+            child_vault_account: child.vault_account,
+            child_vault_ata: child.vault_ata,
+            client_ata: child.client_ata,
+            parent_activity_log: parent.activity_log,
+            child_activity_log: child.activity_log,
+            usdc_mint: child.mint,
+            receipt: receipt_address(&child.contract_id, receipt_number).0,
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        instruction::SettleSubcontract {},
+    );
+    ix.accounts[1].is_signer = subcontractor_signs;
+    ix
+}
+
+/// Closes a payment receipt, returning its rent to the wallet that paid it.
+pub fn close_payment_receipt(rent_payer: &Pubkey, receipt: &Pubkey) -> Instruction {
+    build(
+        accounts::ClosePaymentReceipt { rent_payer: *rent_payer, receipt: *receipt },
+        instruction::ClosePaymentReceipt {},
+    )
+}
+
+/// `prerequisites` are the milestones that must already be released, from
+/// [`pivox_contract::state::Contract::prerequisites`]; their accounts are appended in order.
+fn milestone_lifecycle(
+    addresses: &ContractAddresses,
+    signer: &Pubkey,
+    milestone_index: u64,
+    prerequisites: &[u8],
+    data: impl InstructionData,
+) -> Instruction {
+    let mut ix = build(
+        accounts::MilestoneLifecycle {
+            signer: *signer,
+            contract: addresses.contract,
+            milestone: addresses.milestone(milestone_index as u8),
+            vault_account: addresses.vault_account,
+            activity_log: addresses.activity_log,
+        },
+        data,
+    );
+    ix.accounts.extend(prerequisites.iter().map(|&i| AccountMeta::new_readonly(addresses.milestone(i), false)));
+    ix
+}
+
+pub fn freelancer_submit_milestone(
+    addresses: &ContractAddresses,
+    milestone_index: u64,
+    prerequisites: &[u8],
+) -> Instruction {
+    milestone_lifecycle(
+        addresses,
+        &addresses.freelancer,
+        milestone_index,
+        prerequisites,
+        instruction::FreelancerSubmitMilestone { milestone_index },
+    )
+}
+
+pub fn client_approve_milestone(
+    addresses: &ContractAddresses,
+    milestone_index: u64,
+    prerequisites: &[u8],
+) -> Instruction {
+    milestone_lifecycle(
+        addresses,
+        &addresses.client,
+        milestone_index,
+        prerequisites,
+        instruction::ClientApproveMilestone { milestone_index },
+    )
+}
+
+pub fn freelancer_confirm_milestone(addresses: &ContractAddresses, milestone_index: u64) -> Instruction {
+    milestone_lifecycle(
+        addresses,
+        &addresses.freelancer,
+        milestone_index,
+        &[],
+        instruction::FreelancerConfirmMilestone { milestone_index },
+    )
+}
@@ -0,0 +1,27 @@
+//! Rust client SDK for the PIVOX contract program.
+//!
+//! The crate shares the workspace version with `pivox-contract` and builds against it directly, so the
+//! seeds, instruction layouts and account types here always match the program they ship with.
+
+pub mod accounts;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod offer;
+pub mod pda;
+
+pub use pivox_contract::{
+    state::{
+        ActivityAction, ActivityEntry, ActivityLog, Bond, BonusSource, ConsumedOffer, Contract, ContractTerms,
+        ContractV0, Milestone, MilestoneAccount, MilestoneApproval, MilestoneApprovalV0, MilestoneOrdering,
+        MilestoneTemplate, MilestoneV0, ParentLink, Payee, PaymentReceipt, Payout, PayoutKind, Profile,
+        PythPrice, QuotePricing, ReceiptKind, ReleasePolicy, SlashReason, SubcontractLink, TemplateAmount,
+        TemplateMilestone, VaultAccount, VaultAccountV0, Versioned,
+    },
+    BonusPaid, OfferTerms, ID as PROGRAM_ID, LAYOUT_VERSION,
+};
+
+pub use accounts::*;
+pub use error::*;
+pub use events::*;
+pub use pda::*;
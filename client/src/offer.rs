@@ -0,0 +1,50 @@
+//! Helpers for gasless offers: the client signs [`offer_message`] off-chain and the freelancer submits
+//! [`ed25519_instruction`] followed by [`crate::instructions::accept_offer`].
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{ed25519_program, instruction::Instruction},
+};
+use pivox_contract::OfferTerms;
+
+use crate::error::ClientError;
+
+const SIGNATURE_LEN: usize = 64;
+const PUBKEY_LEN: usize = 32;
+const DATA_START: usize = 2 + 14;
+
+/// The exact bytes the client must sign for `terms`.
+pub fn offer_message(terms: &OfferTerms) -> Result<Vec<u8>, ClientError> {
+    terms.message().map_err(ClientError::Serialize)
+}
+
+/// Builds an ed25519 precompile instruction carrying an existing signature, so the signing key never
+/// has to be present where the transaction is assembled.
+pub fn ed25519_instruction(signer: &Pubkey, signature: &[u8; SIGNATURE_LEN], message: &[u8]) -> Instruction {
+    let pubkey_offset = DATA_START;
+    let signature_offset = pubkey_offset + PUBKEY_LEN;
+    let message_offset = signature_offset + SIGNATURE_LEN;
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for value in [
+        signature_offset as u16,
+        u16::MAX,
+        pubkey_offset as u16,
+        u16::MAX,
+        message_offset as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: vec![],
+        data,
+    }
+}
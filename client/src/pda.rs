@@ -0,0 +1,114 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use pivox_contract::{
+    ACTIVITY_SEED, BOND_SEED, CONTRACT_SEED, ID, MILESTONE_APPROVAL_SEED, MILESTONE_SEED, OFFER_SEED, PROFILE_SEED, RECEIPT_SEED,
+    TEMPLATE_SEED, VAULT_ACCOUNT_SEED,
+};
+
+pub fn milestone_approval_address(client: &Pubkey, freelancer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MILESTONE_APPROVAL_SEED, client.as_ref(), freelancer.as_ref()], &ID)
+}
+
+pub fn vault_account_address(client: &Pubkey, freelancer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_ACCOUNT_SEED, client.as_ref(), freelancer.as_ref()], &ID)
+}
+
+pub fn contract_address(client: &Pubkey, freelancer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONTRACT_SEED, client.as_ref(), freelancer.as_ref()], &ID)
+}
+
+pub fn consumed_offer_address(client: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OFFER_SEED, client.as_ref(), &nonce.to_le_bytes()], &ID)
+}
+
+pub fn milestone_template_address(creator: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TEMPLATE_SEED, creator.as_ref(), name.as_bytes()], &ID)
+}
+
+pub fn profile_address(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROFILE_SEED, owner.as_ref()], &ID)
+}
+
+pub fn bond_address(contract: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BOND_SEED, contract.as_ref()], &ID)
+}
+
+/// Receipt `number` of the contract whose `Contract::id` is `contract_id`; the next one to be issued is
+/// numbered with the contract's `receipt_count`.
+pub fn receipt_address(contract_id: &Pubkey, number: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_SEED, contract_id.as_ref(), &number.to_le_bytes()], &ID)
+}
+
+pub fn activity_log_address(contract: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ACTIVITY_SEED, contract.as_ref()], &ID)
+}
+
+/// Milestone `index` of the contract whose `Contract::id` is `contract_id`.
+pub fn milestone_address(contract_id: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MILESTONE_SEED, contract_id.as_ref(), &[index]], &ID)
+}
+
+/// Every address belonging to the contract between `client` and `freelancer` for a given mint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractAddresses {
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub mint: Pubkey,
+    pub milestone_approval: Pubkey,
+    pub milestone_approval_bump: u8,
+    pub vault_account: Pubkey,
+    pub vault_bump: u8,
+    pub contract: Pubkey,
+    pub contract_bump: u8,
+    /// The contract's `id`, which its milestone and receipt accounts derive from. It is the contract address unless the
+    /// contract has been reassigned; see [`ContractAddresses::with_contract_id`].
+    pub contract_id: Pubkey,
+    pub vault_ata: Pubkey,
+    pub client_ata: Pubkey,
+    pub freelancer_ata: Pubkey,
+    pub client_profile: Pubkey,
+    pub freelancer_profile: Pubkey,
+    pub bond: Pubkey,
+    pub bond_ata: Pubkey,
+    pub activity_log: Pubkey,
+}
+
+impl ContractAddresses {
+    pub fn new(client: &Pubkey, freelancer: &Pubkey, mint: &Pubkey) -> Self {
+        let (milestone_approval, milestone_approval_bump) = milestone_approval_address(client, freelancer);
+        let (vault_account, vault_bump) = vault_account_address(client, freelancer);
+        let (contract, contract_bump) = contract_address(client, freelancer);
+        let bond = bond_address(&contract).0;
+
+        Self {
+            client: *client,
+            freelancer: *freelancer,
+            mint: *mint,
+            milestone_approval,
+            milestone_approval_bump,
+            vault_account,
+            vault_bump,
+            contract,
+            contract_bump,
+            contract_id: contract,
+            vault_ata: get_associated_token_address(&vault_account, mint),
+            client_ata: get_associated_token_address(client, mint),
+            freelancer_ata: get_associated_token_address(freelancer, mint),
+            client_profile: profile_address(client).0,
+            freelancer_profile: profile_address(freelancer).0,
+            bond,
+            bond_ata: get_associated_token_address(&bond, mint),
+            activity_log: activity_log_address(&contract).0,
+        }
+    }
+
+    /// Uses the `id` of a reassigned contract, read from the contract account, for its milestone and receipt
+    /// addresses.
+    pub fn with_contract_id(self, contract_id: Pubkey) -> Self {
+        Self { contract_id, ..self }
+    }
+
+    pub fn milestone(&self, index: u8) -> Pubkey {
+        milestone_address(&self.contract_id, index).0
+    }
+}
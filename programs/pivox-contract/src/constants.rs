@@ -2,3 +2,48 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+#[constant]
+pub const MILESTONE_APPROVAL_SEED: &[u8] = b"milestone_approval";
+
+#[constant]
+pub const VAULT_ACCOUNT_SEED: &[u8] = b"vault_account";
+
+#[constant]
+pub const CONTRACT_SEED: &[u8] = b"contract";
+
+#[constant]
+pub const OFFER_SEED: &[u8] = b"offer";
+
+#[constant]
+pub const TEMPLATE_SEED: &[u8] = b"template";
+
+#[constant]
+pub const PROFILE_SEED: &[u8] = b"profile";
+
+#[constant]
+pub const BOND_SEED: &[u8] = b"bond";
+
+#[constant]
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+#[constant]
+pub const ACTIVITY_SEED: &[u8] = b"activity";
+
+#[constant]
+pub const MILESTONE_SEED: &[u8] = b"milestone";
+
+// Layout version written into every `Contract`, `VaultAccount`, `MilestoneApproval` and `MilestoneAccount`.
+// Accounts from before the version byte existed are layout 0; `migrate` upgrades older layouts to this one.
+// Bump it, and add a snapshot of the old layout to `state::layout`, whenever one of these accounts changes
+// shape.
+#[constant]
+pub const LAYOUT_VERSION: u8 = 1;
+
+// How long after the deadline a freelancer with nothing submitted counts as having abandoned the contract.
+#[constant]
+pub const ABANDONMENT_GRACE_PERIOD: i64 = 14 * 24 * 60 * 60;
+
+// Prefix of every signed offer message, so a signature over PIVOX terms can't be mistaken for anything else.
+#[constant]
+pub const OFFER_DOMAIN: &[u8] = b"PIVOX_OFFER_V1";
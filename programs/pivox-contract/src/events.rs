@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+use crate::state::BonusSource;
+
+#[event]
+pub struct BonusPaid {
+    pub contract: Pubkey,
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub amount: u64,
+    pub source: BonusSource,
+    pub memo: String,
+    pub paid_at: i64,
+}
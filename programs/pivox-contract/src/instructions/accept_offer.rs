@@ -0,0 +1,312 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    hash::hash,
+    instruction::Instruction,
+    sysvar::instructions::{self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use crate::state::{
+    validate_payees, validate_terms, ActivityAction, ActivityLog, ConsumedOffer, Contract, Milestone, MilestoneApproval,
+    MilestoneOrdering, Payee, ReleasePolicy, VaultAccount,
+};
+use crate::instructions::create_milestones;
+use crate::constants::{
+    ACTIVITY_SEED, CONTRACT_SEED, LAYOUT_VERSION, MILESTONE_APPROVAL_SEED, OFFER_DOMAIN, OFFER_SEED, VAULT_ACCOUNT_SEED,
+};
+
+// Layout of the ed25519 precompile instruction data.
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OfferTerms {
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub mint: Pubkey,
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    pub initial_payment: u64,
+    pub project_start: i128,
+    pub project_duration: u64,
+    pub dispute_resolution: String,
+    pub ordering: MilestoneOrdering,
+    pub release_policy: ReleasePolicy,
+    pub payees: Vec<Payee>,
+    pub milestones: Vec<Milestone>,
+    pub expires_at: i64,
+    pub nonce: u64,
+}
+
+impl OfferTerms {
+    // Bytes the client signs off-chain: domain, program id, then the borsh encoded terms.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(OFFER_DOMAIN.len() + 32 + 256);
+        message.extend_from_slice(OFFER_DOMAIN);
+        message.extend_from_slice(crate::ID.as_ref());
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(terms: OfferTerms)]
+pub struct AcceptOffer<'info> {
+    #[account(mut)]
+    pub freelancer: Signer<'info>,
+
+    /// CHECK: authenticated by the ed25519 signature over the offer terms
+    pub client: AccountInfo<'info>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + ConsumedOffer::INIT_SPACE,
+        seeds = [OFFER_SEED, client.key().as_ref(), terms.nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub consumed_offer: Account<'info, ConsumedOffer>,
+
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + MilestoneApproval::INIT_SPACE,
+        seeds = [MILESTONE_APPROVAL_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub milestone_approval: Account<'info, MilestoneApproval>,
+
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + VaultAccount::INIT_SPACE,
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + Contract::INIT_SPACE,
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub contract: Account<'info, Contract>,
+
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + ActivityLog::INIT_SPACE,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: address constrained to the instructions sysvar
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> AcceptOffer<'info> {
+    pub fn accept_offer(
+        &mut self,
+        terms: OfferTerms,
+        milestone_accounts: &[AccountInfo<'info>],
+        bumps: &AcceptOfferBumps,
+    ) -> Result<()> {
+        let client_key = self.client.key();
+        let freelancer_key = self.freelancer.key();
+
+        require_keys_eq!(terms.client, client_key, OfferError::PartyMismatch);
+        require_keys_eq!(terms.freelancer, freelancer_key, OfferError::PartyMismatch);
+        require_keys_eq!(terms.mint, self.usdc_mint.key(), OfferError::MintMismatch);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= terms.expires_at, OfferError::OfferExpired);
+
+        // The ed25519 precompile must run immediately before this instruction.
+        let current_index = load_current_index_checked(&self.instructions_sysvar)?;
+        require!(current_index > 0, OfferError::MissingSignatureInstruction);
+        let signature_ix = load_instruction_at_checked(
+            (current_index - 1) as usize,
+            &self.instructions_sysvar,
+        )?;
+        verify_ed25519_instruction(&signature_ix, &client_key, &terms.message()?)?;
+        validate_payees(&terms.payees)?;
+        validate_terms(terms.client_share, terms.freelancer_share, terms.project_start, &terms.milestones)?;
+
+        self.consumed_offer.set_inner(ConsumedOffer {
+            client: client_key,
+            freelancer: freelancer_key,
+            nonce: terms.nonce,
+            accepted_at: now,
+            bump: bumps.consumed_offer,
+        });
+
+        self.milestone_approval.set_inner(MilestoneApproval {
+            version: LAYOUT_VERSION,
+            client: client_key,
+            freelancer: freelancer_key,
+            initial_payment: terms.initial_payment,
+            threshold: 2,
+            approved_by: vec![client_key, freelancer_key],
+            approvals: 2,
+            is_signed: true,
+            multisig_bump: bumps.milestone_approval,
+            terms_hash: hash(&terms.message()?).to_bytes(),
+        });
+
+        self.vault_account.set_inner(VaultAccount {
+            version: LAYOUT_VERSION,
+            client: client_key,
+            freelancer: freelancer_key,
+            balance: 0,
+            client_share: terms.client_share,
+            freelancer_share: terms.freelancer_share,
+            multisig_account: self.milestone_approval.key(),
+            vault_bump: bumps.vault_account,
+            vault_status: "active".to_string(),
+            total_deposited: 0,
+            total_released: 0,
+            total_refunded: 0,
+            total_written_off: 0,
+            client_withdrawn: 0,
+            freelancer_withdrawn: 0,
+        });
+
+        self.contract.set_inner(Contract {
+            version: LAYOUT_VERSION,
+            client: client_key,
+            freelancer: freelancer_key,
+            vault_account: self.vault_account.key(),
+            multisig_account: self.milestone_approval.key(),
+            initial_payment: terms.initial_payment,
+            project_start: terms.project_start,
+            project_duration: terms.project_duration,
+            client_share: terms.client_share,
+            freelancer_share: terms.freelancer_share,
+            dispute_resolution: terms.dispute_resolution,
+            status: "active".to_string(),
+            contract_bump: bumps.contract,
+            client_approved: false,
+            freelancer_approved: false,
+            ordering: terms.ordering,
+            release_policy: terms.release_policy,
+            parent: None,
+            payees: terms.payees,
+            id: self.contract.key(),
+            milestone_count: 0,
+            milestones_total: 0,
+            submitted_count: 0,
+            released_count: 0,
+            unreleased: 0,
+            subcontracted: 0,
+            open_subcontracts: 0,
+            payouts: vec![],
+            receipt_count: 0,
+            unsubmitted_funding: 0,
+            cancelled_count: 0,
+            unsubmitted: 0,
+        });
+        create_milestones(
+            &mut self.contract,
+            terms.milestones,
+            milestone_accounts,
+            &self.freelancer.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+
+        self.activity_log.set_inner(ActivityLog {
+            contract: self.contract.key(),
+            total: 0,
+            entries: vec![],
+            bump: bumps.activity_log,
+        });
+        let budget = self.contract.outstanding()?;
+        self.activity_log.record(freelancer_key, ActivityAction::Created, None, budget)
+    }
+}
+
+// Checks that `ix` is an ed25519 precompile call verifying exactly one signature by `signer` over `message`,
+// with the key, signature and message all embedded in that same instruction.
+fn verify_ed25519_instruction(ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    require_keys_eq!(ix.program_id, ed25519_program::ID, OfferError::MissingSignatureInstruction);
+    require!(ix.accounts.is_empty(), OfferError::InvalidSignatureInstruction);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        OfferError::InvalidSignatureInstruction
+    );
+
+    let offsets = &data[ED25519_HEADER_LEN..ED25519_HEADER_LEN + ED25519_OFFSETS_LEN];
+    let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+    let signature_offset = read_u16(0) as usize;
+    let signature_ix_index = read_u16(2);
+    let pubkey_offset = read_u16(4) as usize;
+    let pubkey_ix_index = read_u16(6);
+    let message_offset = read_u16(8) as usize;
+    let message_size = read_u16(10) as usize;
+    let message_ix_index = read_u16(12);
+
+    // u16::MAX means "this instruction"; anything else could point the precompile at other data.
+    require!(
+        signature_ix_index == u16::MAX && pubkey_ix_index == u16::MAX && message_ix_index == u16::MAX,
+        OfferError::InvalidSignatureInstruction
+    );
+    require!(
+        signature_offset + ED25519_SIGNATURE_LEN <= data.len(),
+        OfferError::InvalidSignatureInstruction
+    );
+
+    let signed_pubkey = data
+        .get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN)
+        .ok_or(OfferError::InvalidSignatureInstruction)?;
+    require!(signed_pubkey == signer.as_ref(), OfferError::SignerMismatch);
+
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(OfferError::InvalidSignatureInstruction)?;
+    require!(signed_message == message, OfferError::TermsMismatch);
+
+    Ok(())
+}
+
+#[error_code(offset = 6500)]
+pub enum OfferError {
+    #[msg("Offer parties do not match the accounts")]
+    PartyMismatch,
+    #[msg("Offer mint does not match the provided mint")]
+    MintMismatch,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Expected an ed25519 signature instruction before accept_offer")]
+    MissingSignatureInstruction,
+    #[msg("Malformed ed25519 signature instruction")]
+    InvalidSignatureInstruction,
+    #[msg("Offer was not signed by the client")]
+    SignerMismatch,
+    #[msg("Signed message does not match the offer terms")]
+    TermsMismatch,
+}
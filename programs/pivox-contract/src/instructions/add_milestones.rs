@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, LAYOUT_VERSION, MILESTONE_SEED};
+use crate::instructions::{create_pda, require_no_subcontracts, validate_milestone_ordering, MilestoneError};
+use crate::state::{
+    validate_new_milestones, validate_payees, ActivityAction, ActivityLog, Contract, Milestone, MilestoneAccount,
+    TermsError, MAX_MILESTONES,
+};
+
+/// Creates an account for each of `milestones`, numbered on from the contract's `milestone_count`.
+/// `accounts` holds their addresses in the same order; the payer funds the rent.
+pub fn create_milestones<'info>(
+    contract: &mut Contract,
+    milestones: Vec<Milestone>,
+    accounts: &[AccountInfo<'info>],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let first = contract.milestone_count as usize;
+    require!(first + milestones.len() <= MAX_MILESTONES, MilestoneError::TooManyMilestones);
+    require!(accounts.len() == milestones.len(), MilestoneError::InvalidMilestoneAccount);
+    validate_milestone_ordering(contract.ordering, first, &milestones)?;
+    require_no_subcontracts(&milestones)?;
+    milestones.iter().try_for_each(|m| validate_payees(&m.payees))?;
+
+    let id = contract.id;
+    for (milestone, account) in milestones.into_iter().zip(accounts) {
+        let index = contract.milestone_count as u8;
+        let (address, bump) = Pubkey::find_program_address(&[MILESTONE_SEED, id.as_ref(), &[index]], &crate::ID);
+        require_keys_eq!(account.key(), address, MilestoneError::InvalidMilestoneAccount);
+        // Still held by a contract these parties reassigned away, which kept this address as its id.
+        require!(account.data_is_empty(), MilestoneError::MilestoneAccountInUse);
+
+        let seeds: &[&[u8]] = &[MILESTONE_SEED, id.as_ref(), &[index], &[bump]];
+        create_pda(account, payer, system_program, 8 + MilestoneAccount::INIT_SPACE, &[seeds])?;
+
+        contract.record_milestone(milestone.amount)?;
+        let data = MilestoneAccount { version: LAYOUT_VERSION, contract_id: id, index, bump, milestone };
+        data.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    }
+    Ok(())
+}
+
+/// Both parties agree to extend a running contract with more milestones; the client pays their rent and
+/// funds them with `deposit_funds` as usual.
+#[derive(Accounts)]
+pub struct AddMilestones<'info> {
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = contract.contract_bump
+    )]
+    pub contract: Account<'info, Contract>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddMilestones<'info> {
+    pub fn add_milestones(&mut self, milestones: Vec<Milestone>, accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(self.contract.status != "terminated", MilestoneError::ContractTerminated);
+        require!(!milestones.is_empty(), TermsError::NoMilestones);
+        validate_new_milestones(self.contract.milestones_total, &milestones)?;
+
+        let first = self.contract.milestone_count as u8;
+        let amount = milestones.iter().map(|m| m.amount).sum();
+        create_milestones(
+            &mut self.contract,
+            milestones,
+            accounts,
+            &self.client.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+        self.activity_log.record(self.client.key(), ActivityAction::MilestonesAdded, Some(first), amount)
+    }
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::constants::{
+    ABANDONMENT_GRACE_PERIOD, ACTIVITY_SEED, BOND_SEED, CONTRACT_SEED, PROFILE_SEED, VAULT_ACCOUNT_SEED,
+};
+use crate::state::{ActivityAction, ActivityLog, Bond, BondError, Contract, Profile, SlashReason, VaultAccount};
+
+#[derive(Accounts)]
+pub struct BondSettlement<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: the contract's freelancer. Co-signs a DisputeLost slash and gets the bond account's rent back
+    /// when it is closed.
+    #[account(mut, address = contract.freelancer @ BondError::Unauthorized)]
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONTRACT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = contract.contract_bump
+    )]
+    pub contract: Account<'info, Contract>,
+
+    #[account(
+        seeds = [VAULT_ACCOUNT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = vault_account.vault_bump
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        has_one = contract,
+        seeds = [BOND_SEED, contract.key().as_ref()],
+        bump = bond.bump
+    )]
+    pub bond: Account<'info, Bond>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = bond
+    )]
+    pub bond_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contract.client
+    )]
+    pub client_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contract.freelancer
+    )]
+    pub freelancer_ata: Account<'info, TokenAccount>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + Profile::INIT_SPACE,
+        seeds = [PROFILE_SEED, contract.freelancer.as_ref()],
+        bump
+    )]
+    pub freelancer_profile: Account<'info, Profile>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> BondSettlement<'info> {
+    /// Moves `amount` of the bond to the client. The client can slash after a missed deadline or
+    /// abandonment; a lost dispute needs the freelancer to co-sign the client's slash.
+    pub fn slash_bond(&mut self, amount: u64, reason: SlashReason, bumps: &BondSettlementBumps) -> Result<()> {
+        self.freelancer_profile.claim(self.contract.freelancer, bumps.freelancer_profile);
+        require!(self.contract.status != "terminated", BondError::ContractTerminated);
+
+        let signer_key = self.signer.key();
+        let contract = &self.contract;
+        let now = Clock::get()?.unix_timestamp as i128;
+        let deadline = contract.project_start.saturating_add(contract.project_duration as i128);
+        // Only milestones the client funded could have been worked on; the rest count against no one.
+        let undelivered = contract.funded_unsubmitted(self.vault_account.balance)? as u128;
+
+        match reason {
+            SlashReason::MissedDeadline => {
+                require!(signer_key == contract.client, BondError::Unauthorized);
+                require!(now > deadline, BondError::DeadlineNotPassed);
+                require!(undelivered > 0, BondError::NothingOutstanding);
+
+                // Only the share of the bond backing work that was never delivered is at stake; submitted
+                // milestones waiting on the client don't count against the freelancer.
+                let total = contract.milestones_total as u128;
+                let slashable = (self.bond.posted as u128 * undelivered / total) as u64;
+                let after = self.bond.slashed.checked_add(amount).ok_or(BondError::Overflow)?;
+                require!(after <= slashable, BondError::ExceedsSlashable);
+            }
+            SlashReason::Abandoned => {
+                require!(signer_key == contract.client, BondError::Unauthorized);
+                require!(undelivered > 0, BondError::NothingOutstanding);
+                require!(
+                    now > deadline.saturating_add(ABANDONMENT_GRACE_PERIOD as i128),
+                    BondError::NotAbandoned
+                );
+                // Every submitted milestone has been released, i.e. nothing is waiting on the client.
+                require!(contract.submitted_count == contract.released_count, BondError::NotAbandoned);
+            }
+            SlashReason::DisputeLost => {
+                require!(
+                    signer_key == contract.client && self.freelancer.is_signer,
+                    BondError::Unauthorized
+                );
+                self.freelancer_profile.record_dispute_lost();
+            }
+        }
+
+        self.bond.record_slash(amount)?;
+        self.pay_out(&self.client_ata, amount)?;
+        self.activity_log.record(signer_key, ActivityAction::BondSlashed, None, amount)?;
+
+        msg!("Slashed {} of the bond ({:?})", amount, reason);
+        Ok(())
+    }
+
+    /// Returns what is left of the bond to the freelancer once the contract is terminated, and closes the
+    /// bond's ATA and account.
+    pub fn return_bond(&mut self, bumps: &BondSettlementBumps) -> Result<()> {
+        self.freelancer_profile.claim(self.contract.freelancer, bumps.freelancer_profile);
+
+        let signer_key = self.signer.key();
+        require!(
+            signer_key == self.contract.client || signer_key == self.contract.freelancer,
+            BondError::Unauthorized
+        );
+        require!(self.contract.status == "terminated", BondError::NotTerminated);
+
+        let remaining = self.bond.remaining();
+        if remaining > 0 {
+            self.bond.record_return(remaining)?;
+            self.pay_out(&self.freelancer_ata, remaining)?;
+            self.activity_log.record(signer_key, ActivityAction::BondReturned, None, remaining)?;
+        }
+
+        let contract_key = self.contract.key();
+        let seeds = &[BOND_SEED, contract_key.as_ref(), &[self.bond.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.bond_ata.to_account_info(),
+                destination: self.signer.to_account_info(),
+                authority: self.bond.to_account_info(),
+            },
+            signer_seeds,
+        );
+        close_account(cpi_ctx)?;
+        self.bond.close(self.freelancer.to_account_info())
+    }
+
+    fn pay_out(&self, to: &Account<'info, TokenAccount>, amount: u64) -> Result<()> {
+        let contract_key = self.contract.key();
+        let seeds = &[BOND_SEED, contract_key.as_ref(), &[self.bond.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.bond_ata.to_account_info(),
+                to: to.to_account_info(),
+                authority: self.bond.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer(cpi_ctx, amount)
+    }
+}
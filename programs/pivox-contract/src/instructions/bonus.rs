@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, VAULT_ACCOUNT_SEED};
+use crate::events::BonusPaid;
+use crate::state::{ActivityAction, ActivityLog, BonusSource, Contract, PayoutKind, VaultAccount, MAX_MEMO_LEN};
+
+#[error_code(offset = 6900)]
+pub enum BonusError {
+    #[msg("Only the client can pay a bonus")]
+    Unauthorized,
+    #[msg("Bonus amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Bonus memo is longer than 64 bytes")]
+    MemoTooLong,
+    #[msg("Escrow holds less than the bonus beyond what unreleased milestones need")]
+    InsufficientSurplus,
+}
+
+#[derive(Accounts)]
+pub struct PayBonus<'info> {
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = contract.contract_bump
+    )]
+    pub contract: Account<'info, Contract>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = vault_account.vault_bump
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    /// Only debited for `BonusSource::EscrowSurplus`.
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault_account
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contract.client
+    )]
+    pub client_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contract.freelancer
+    )]
+    pub freelancer_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> PayBonus<'info> {
+    /// Pays the freelancer `amount` on top of the milestone schedule, either straight from the client or
+    /// out of escrow that unreleased milestones don't need.
+    pub fn pay_bonus(&mut self, amount: u64, source: BonusSource, memo: String) -> Result<()> {
+        require!(self.client.key() == self.contract.client, BonusError::Unauthorized);
+        require!(amount > 0, BonusError::ZeroAmount);
+        require!(memo.len() <= MAX_MEMO_LEN, BonusError::MemoTooLong);
+
+        match source {
+            BonusSource::Direct => {
+                let cpi_ctx = CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: self.client_ata.to_account_info(),
+                        to: self.freelancer_ata.to_account_info(),
+                        authority: self.client.to_account_info(),
+                    },
+                );
+                transfer(cpi_ctx, amount)?;
+            }
+            BonusSource::EscrowSurplus => {
+                // Tokens sent straight to the vault count towards the surplus once they are booked.
+                self.vault_account.sync_to(self.vault_ata.amount)?;
+                let surplus = self.vault_account.balance.saturating_sub(self.contract.outstanding()?);
+                require!(amount <= surplus, BonusError::InsufficientSurplus);
+
+                let seeds = &[
+                    VAULT_ACCOUNT_SEED,
+                    self.contract.client.as_ref(),
+                    self.contract.freelancer.as_ref(),
+                    &[self.vault_account.vault_bump],
+                ];
+                let signer_seeds = &[&seeds[..]];
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: self.vault_ata.to_account_info(),
+                        to: self.freelancer_ata.to_account_info(),
+                        authority: self.vault_account.to_account_info(),
+                    },
+                    signer_seeds,
+                );
+                transfer(cpi_ctx, amount)?;
+                self.vault_account.record_release(amount)?;
+            }
+        }
+
+        self.contract.record_payout(PayoutKind::Bonus, amount, memo.clone())?;
+        self.activity_log.record(self.client.key(), ActivityAction::BonusPaid, None, amount)?;
+        emit!(BonusPaid {
+            contract: self.contract.key(),
+            client: self.contract.client,
+            freelancer: self.contract.freelancer,
+            amount,
+            source,
+            memo,
+            paid_at: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, MILESTONE_SEED, VAULT_ACCOUNT_SEED};
+use crate::instructions::{issue_receipt, ContractError, MilestoneError, SubcontractError};
+use crate::state::{
+    ActivityAction, ActivityLog, Contract, MilestoneAccount, ReceiptDetails, ReceiptKind, VaultAccount,
+};
+
+/// Both parties agree to drop one unreleased milestone. Its escrow goes back to the client and the rest
+/// of the contract carries on.
+#[derive(Accounts)]
+#[instruction(milestone_index: u64)]
+pub struct CancelMilestone<'info> {
+    /// Gets the refund and pays the receipt's rent.
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    pub freelancer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = contract.contract_bump
+    )]
+    pub contract: Account<'info, Contract>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, contract.id.as_ref(), &[milestone_index as u8]],
+        bump = milestone.bump,
+        constraint = milestone.index as u64 == milestone_index @ MilestoneError::InvalidMilestoneAccount
+    )]
+    pub milestone: Account<'info, MilestoneAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = vault_account.vault_bump
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault_account
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = client
+    )]
+    pub client_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    /// CHECK: the contract's next receipt address, checked and created by `issue_receipt`; left untouched
+    /// when there is nothing to refund.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelMilestone<'info> {
+    pub fn cancel_milestone(&mut self, milestone_index: u64) -> Result<()> {
+        require!(self.contract.status != "terminated", MilestoneError::ContractTerminated);
+        let milestone = &self.milestone;
+        require!(!milestone.is_released, ContractError::AlreadyReleased);
+        require!(!milestone.cancelled, MilestoneError::Cancelled);
+        require!(milestone.subcontract.is_none(), SubcontractError::AlreadySubcontracted);
+
+        // A milestone funded on its own gets its whole escrow back. Otherwise its share of untagged deposits
+        // is whatever the vault holds beyond what the other milestones still need.
+        let escrow = milestone.escrow()?;
+        let refund = if milestone.funded {
+            escrow
+        } else {
+            let needed = self.contract.outstanding()?.saturating_sub(escrow);
+            escrow.min(self.vault_account.balance.saturating_sub(needed))
+        };
+
+        self.contract.record_milestone_cancellation(&self.milestone)?;
+        self.milestone.cancelled = true;
+        let index = milestone_index as u8;
+
+        if refund > 0 {
+            let client_key = self.contract.client;
+            let freelancer_key = self.contract.freelancer;
+            let seeds = &[
+                VAULT_ACCOUNT_SEED,
+                client_key.as_ref(),
+                freelancer_key.as_ref(),
+                &[self.vault_account.vault_bump],
+            ];
+            let signer_seeds = &[&seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault_ata.to_account_info(),
+                    to: self.client_ata.to_account_info(),
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer(cpi_ctx, refund)?;
+            self.vault_account.record_refund(refund)?;
+
+            let details = ReceiptDetails {
+                kind: ReceiptKind::Refund,
+                milestone_index: Some(index),
+                payer: self.vault_account.key(),
+                payee: client_key,
+                mint: self.usdc_mint.key(),
+                amount: refund,
+                invoice: String::new(),
+            };
+            issue_receipt(
+                &self.receipt.to_account_info(),
+                &self.client.to_account_info(),
+                &self.system_program.to_account_info(),
+                &mut self.contract,
+                details,
+            )?;
+        }
+
+        self.activity_log.record(self.client.key(), ActivityAction::MilestoneCancelled, Some(index), refund)
+    }
+}
@@ -1,11 +1,16 @@
-use crate::state::{Contract, VaultAccount};
+use crate::state::{
+    split_amount, validate_invoice, ActivityAction, ActivityLog, Contract, LedgerError, MilestoneAccount, PayeeError,
+    PayoutKind, PriceError, Profile, ReceiptDetails, ReceiptError, ReceiptKind, VaultAccount,
+};
+use crate::instructions::{issue_receipt, MilestoneError, SubcontractError};
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{get_associated_token_address, AssociatedToken},
     token::{self, close_account, transfer, CloseAccount, Token, TokenAccount, Transfer},
 };
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, PROFILE_SEED, VAULT_ACCOUNT_SEED};
 
-#[error_code]
+#[error_code(offset = 6200)]
 pub enum ContractError {
     Unauthorized,
     MilestoneError,
@@ -19,14 +24,25 @@ pub struct ContractFunc<'info> {
 
     #[account(
         mut,
-        seeds = [b"contract", contract.client.as_ref(), contract.freelancer.as_ref()],
+        seeds = [CONTRACT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
         bump = contract.contract_bump
     )]
     pub contract: Account<'info, Contract>,
 
     #[account(
         mut,
-        seeds = [b"vault_account", contract.client.as_ref(), contract.freelancer.as_ref()],
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    /// The milestone being released; not used by termination.
+    #[account(mut)]
+    pub milestone: Option<Account<'info, MilestoneAccount>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
         bump = vault_account.vault_bump
     )]
     pub vault_account: Account<'info, VaultAccount>,
@@ -45,17 +61,67 @@ pub struct ContractFunc<'info> {
     )]
     pub freelancer_ata: Account<'info, TokenAccount>,
 
+    /// Gets back escrow that isn't paid out: what a priced milestone's payout doesn't use, and on termination
+    /// the escrow of milestones that were never submitted.
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contract.client
+    )]
+    pub client_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: the price account a priced milestone was agreed with; checked and decoded by `QuotePricing::payout`.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
     pub usdc_mint: Account<'info, token::Mint>,
 
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + Profile::INIT_SPACE,
+        seeds = [PROFILE_SEED, contract.client.as_ref()],
+        bump
+    )]
+    pub client_profile: Account<'info, Profile>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + Profile::INIT_SPACE,
+        seeds = [PROFILE_SEED, contract.freelancer.as_ref()],
+        bump
+    )]
+    pub freelancer_profile: Account<'info, Profile>,
+
+    /// CHECK: the contract's next receipt address, checked and created by `issue_receipt` when this pays out
+    /// or refunds.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    /// CHECK: the receipt address after `receipt`, for the refund of what a priced milestone's payout doesn't
+    /// use; checked and created by `issue_receipt` when there is one.
+    #[account(mut)]
+    pub refund_receipt: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> ContractFunc<'info> {
-    pub fn complete_or_cancel_contract(&mut self) -> Result<()> {
+    fn claim_profiles(&mut self, bumps: &ContractFuncBumps) {
+        self.client_profile.claim(self.contract.client, bumps.client_profile);
+        self.freelancer_profile.claim(self.contract.freelancer, bumps.freelancer_profile);
+    }
+
+    pub fn complete_or_cancel_contract(&mut self, invoice: String, bumps: &ContractFuncBumps) -> Result<()> {
+        validate_invoice(&invoice)?;
+        self.claim_profiles(bumps);
+
         let contract = &mut self.contract;
         let signer_key = self.signer.key();
+        // A subcontract's leftovers belong to the parent freelancer, not the subcontractor.
+        require!(contract.parent.is_none(), SubcontractError::SettleInstead);
 
         if signer_key == contract.client {
             contract.client_approved = true;
@@ -64,96 +130,244 @@ impl<'info> ContractFunc<'info> {
         } else {
             return Err(error!(ContractError::Unauthorized));
         }
+        self.activity_log.record(signer_key, ActivityAction::TerminationApproved, None, 0)?;
 
         if contract.client_approved && contract.freelancer_approved {
             contract.status = "terminated".to_string();
 
+            let completed = contract.is_complete()?;
+            self.client_profile.record_termination(completed);
+            self.freelancer_profile.record_termination(completed);
+
+            // Escrow of milestones that were never submitted goes back to the client. The rest stays in the
+            // vault for each party to `withdraw` their share of. Book untracked tokens first so the ledger balances.
+            self.vault_account.sync_to(self.vault_ata.amount)?;
+            let refund = contract.termination_refund(self.vault_account.balance)?;
+            contract.unsubmitted = 0;
+            contract.unsubmitted_funding = 0;
+
+            let client_key = contract.client;
+            let freelancer_key = contract.freelancer;
             let seeds = &[
-                b"vault_account",
-                contract.client.as_ref(),
-                contract.freelancer.as_ref(),
+                VAULT_ACCOUNT_SEED,
+                client_key.as_ref(),
+                freelancer_key.as_ref(),
                 &[self.vault_account.vault_bump],
             ];
             let signer_seeds = &[&seeds[..]];
 
-            if self.vault_ata.amount > 0 {
+            if refund > 0 {
                 let cpi_ctx = CpiContext::new_with_signer(
                     self.token_program.to_account_info(),
                     Transfer {
                         from: self.vault_ata.to_account_info(),
-                        to: self.freelancer_ata.to_account_info(),
+                        to: self.client_ata.to_account_info(),
                         authority: self.vault_account.to_account_info(),
                     },
                     signer_seeds,
                 );
-                transfer(cpi_ctx, self.vault_ata.amount)?;
+                transfer(cpi_ctx, refund)?;
+                self.vault_account.record_refund(refund)?;
+                let receipt = self.receipt.to_account_info();
+                self.issue_refund_receipt(&receipt, None, refund, invoice)?;
             }
 
-            let cpi_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                CloseAccount {
-                    account: self.vault_ata.to_account_info(),
-                    destination: self.signer.to_account_info(),
-                    authority: self.vault_account.to_account_info(),
-                },
-                signer_seeds,
-            );
-            close_account(cpi_ctx)?;
+            // Nothing is left to withdraw, so the ATA's rent goes back now.
+            if self.vault_ata.amount == refund {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: self.vault_ata.to_account_info(),
+                        destination: self.signer.to_account_info(),
+                        authority: self.vault_account.to_account_info(),
+                    },
+                    signer_seeds,
+                );
+                close_account(cpi_ctx)?;
+            }
 
-            self.vault_account.balance = 0;
             self.vault_account.vault_status = "Terminated".to_string();
+            self.activity_log.record(signer_key, ActivityAction::Terminated, None, refund)?;
         }
 
         Ok(())
     }
 
-    pub fn release_milestone_payment(&mut self, milestone_index: u64) -> Result<()> {
-        let milestone_index = milestone_index as usize;
-    
+    /// Pays a released milestone to the freelancer, or splits it between the milestone's (or contract's)
+    /// payees. With payees, `payee_atas` holds each payee's associated token account in list order.
+    pub fn release_milestone_payment(
+        &mut self,
+        milestone_index: u64,
+        invoice: String,
+        payee_atas: &[AccountInfo<'info>],
+        bumps: &ContractFuncBumps,
+    ) -> Result<()> {
+        validate_invoice(&invoice)?;
+        self.claim_profiles(bumps);
+
         let contract = &mut self.contract;
-    
+        let milestone = self.milestone.as_mut().ok_or(MilestoneError::InvalidMilestoneAccount)?;
         require!(
-            milestone_index < contract.milestones.len(),
-            ContractError::MilestoneError
+            milestone.contract_id == contract.id && milestone.index as u64 == milestone_index,
+            MilestoneError::InvalidMilestoneAccount
         );
-    
-        let client_key = contract.client.clone();
-        let freelancer_key = contract.freelancer.clone();
-        let milestone = &mut contract.milestones[milestone_index];
-    
+        let milestone_index = milestone.index;
+
+        let client_key = contract.client;
+        let freelancer_key = contract.freelancer;
+        let deadline = contract.project_start.saturating_add(contract.project_duration as i128);
+
+        require!(contract.status != "terminated", MilestoneError::ContractTerminated);
         require!(milestone.freelancer_submitted, ContractError::MilestoneError);
         require!(milestone.client_approved, ContractError::MilestoneError);
         require!(!milestone.is_released, ContractError::AlreadyReleased);
+        require!(!milestone.cancelled, MilestoneError::Cancelled);
+        let policy = contract.release_policy;
+        require!(policy.may_release(contract, self.signer.key()), ContractError::Unauthorized);
+        require!(
+            !policy.requires_confirmation() || milestone.freelancer_confirmed,
+            MilestoneError::NotConfirmed
+        );
+
+        // The subcontracted part left the vault when the subcontract was funded.
+        if let Some(link) = milestone.subcontract {
+            require!(link.settled, SubcontractError::Unsettled);
+        }
+        let escrow = milestone.escrow()?;
+        // A priced milestone pays what its quote is worth in tokens now; the rest of its escrow is refunded.
+        let amount = match milestone.pricing {
+            Some(pricing) => {
+                let feed = self.price_feed.as_ref().ok_or(PriceError::MissingPriceFeed)?;
+                pricing.payout(feed, self.usdc_mint.decimals, Clock::get()?.unix_timestamp)?
+            }
+            None => escrow,
+        };
+        let refund = escrow.checked_sub(amount).ok_or(LedgerError::Underflow)?;
     
         let seeds = &[
-            b"vault_account",
+            VAULT_ACCOUNT_SEED,
             client_key.as_ref(),
             freelancer_key.as_ref(),
             &[self.vault_account.vault_bump],
         ];
         let signer_seeds = &[&seeds[..]];
-    
-        let cpi_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            Transfer {
-                from: self.vault_ata.to_account_info(),
-                to: self.freelancer_ata.to_account_info(),
-                authority: self.vault_account.to_account_info(),
-            },
-            signer_seeds,
-        );
-    
-        transfer(cpi_ctx, milestone.amount)?;
-    
+
+        let payees = contract.payees_for(milestone).to_vec();
+        let recipients = if payees.is_empty() {
+            vec![(self.freelancer_ata.to_account_info(), amount)]
+        } else {
+            require!(payee_atas.len() == payees.len(), PayeeError::MissingPayeeAccounts);
+            let mint = self.usdc_mint.key();
+            payees
+                .iter()
+                .zip(payee_atas)
+                .zip(split_amount(amount, &payees))
+                .map(|((payee, ata), share)| {
+                    require_keys_eq!(
+                        ata.key(),
+                        get_associated_token_address(&payee.wallet, &mint),
+                        PayeeError::PayeeAccountMismatch
+                    );
+                    Ok((ata.clone(), share))
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        for (to, share) in recipients.into_iter().filter(|(_, share)| *share > 0) {
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault_ata.to_account_info(),
+                    to,
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer(cpi_ctx, share)?;
+        }
+
+        if refund > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault_ata.to_account_info(),
+                    to: self.client_ata.to_account_info(),
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer(cpi_ctx, refund)?;
+            self.vault_account.record_refund(refund)?;
+        }
+
         milestone.is_released = true;
-        milestone.freelancer_confirmed = true;
-    
-        self.vault_account.balance = self
-            .vault_account
-            .balance
-            .saturating_sub(milestone.amount);
+        contract.record_milestone_release(milestone)?;
     
+        self.vault_account.record_release(amount)?;
+        contract.record_payout(PayoutKind::Milestone { index: milestone_index }, amount, String::new())?;
+
+        let on_time = (Clock::get()?.unix_timestamp as i128) <= deadline;
+        self.client_profile.record_paid(amount);
+        self.freelancer_profile.record_earned(amount);
+        self.freelancer_profile.record_delivery(on_time);
+        self.activity_log.record(
+            self.signer.key(),
+            ActivityAction::MilestoneReleased,
+            Some(milestone_index),
+            amount,
+        )?;
+
+        self.issue_receipt(ReceiptKind::MilestoneRelease, Some(milestone_index), amount, invoice.clone())?;
+        if refund > 0 {
+            let receipt = self.refund_receipt.as_ref().ok_or(ReceiptError::InvalidReceiptAccount)?.to_account_info();
+            self.issue_refund_receipt(&receipt, Some(milestone_index), refund, invoice)?;
+        }
         Ok(())
     }
-    
+
+    /// Receipt for a payment from the client's escrow to the freelancer (or the freelancer's payees).
+    fn issue_receipt(&mut self, kind: ReceiptKind, milestone_index: Option<u8>, amount: u64, invoice: String) -> Result<()> {
+        let details = ReceiptDetails {
+            kind,
+            milestone_index,
+            payer: self.contract.client,
+            payee: self.contract.freelancer,
+            mint: self.usdc_mint.key(),
+            amount,
+            invoice,
+        };
+        issue_receipt(
+            &self.receipt.to_account_info(),
+            &self.signer.to_account_info(),
+            &self.system_program.to_account_info(),
+            &mut self.contract,
+            details,
+        )
+    }
+
+    /// Receipt at `receipt` for escrow the vault returned to the client.
+    fn issue_refund_receipt(
+        &mut self,
+        receipt: &AccountInfo<'info>,
+        milestone_index: Option<u8>,
+        amount: u64,
+        invoice: String,
+    ) -> Result<()> {
+        let details = ReceiptDetails {
+            kind: ReceiptKind::Refund,
+            milestone_index,
+            payer: self.vault_account.key(),
+            payee: self.contract.client,
+            mint: self.usdc_mint.key(),
+            amount,
+            invoice,
+        };
+        issue_receipt(
+            receipt,
+            &self.signer.to_account_info(),
+            &self.system_program.to_account_info(),
+            &mut self.contract,
+            details,
+        )
+    }
 }
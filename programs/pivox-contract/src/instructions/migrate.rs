@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, LAYOUT_VERSION, MILESTONE_APPROVAL_SEED, VAULT_ACCOUNT_SEED};
+use crate::instructions::{create_milestones, create_pda};
+use crate::state::{
+    decode_v0, layout_version, ActivityAction, ActivityLog, Contract, ContractV0, MilestoneApproval, VaultAccount,
+};
+
+#[error_code(offset = 7400)]
+pub enum MigrateError {
+    #[msg("Only the client or the freelancer can migrate the contract")]
+    Unauthorized,
+    #[msg("The contract's accounts are already in the current layout")]
+    AlreadyCurrent,
+    #[msg("Account was written with a layout this program does not know")]
+    UnknownLayout,
+}
+
+/// Upgrades a contract's accounts written by an older program to the current layout, in place. The
+/// accounts are taken unchecked because they can't be decoded as the current types until this has run.
+/// A contract from before milestone accounts also needs its milestones' addresses as remaining accounts,
+/// in index order, and gets the activity log it never had.
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    /// The client or the freelancer; pays for any extra space.
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: only used for seeds
+    pub client: AccountInfo<'info>,
+
+    /// CHECK: only used for seeds
+    pub freelancer: AccountInfo<'info>,
+
+    /// CHECK: decoded by hand according to its layout version
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub contract: AccountInfo<'info>,
+
+    /// CHECK: decoded by hand according to its layout version
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub vault_account: AccountInfo<'info>,
+
+    /// CHECK: decoded by hand according to its layout version
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [MILESTONE_APPROVAL_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub milestone_approval: AccountInfo<'info>,
+
+    /// CHECK: created here when the contract predates the activity log
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump
+    )]
+    pub activity_log: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Migrate<'info> {
+    pub fn migrate(&mut self, milestone_accounts: &[AccountInfo<'info>], bumps: &MigrateBumps) -> Result<()> {
+        let signer = self.signer.key();
+        require!(
+            signer == self.client.key() || signer == self.freelancer.key(),
+            MigrateError::Unauthorized
+        );
+
+        let contract = self.migrate_contract(milestone_accounts, bumps.activity_log)?;
+        let vault = self.migrate_vault()?;
+        let approval = self.migrate_approval()?;
+        require!(contract || vault || approval, MigrateError::AlreadyCurrent);
+
+        let mut log = ActivityLog::try_deserialize(&mut &self.activity_log.try_borrow_data()?[..])?;
+        log.record(signer, ActivityAction::Migrated, None, 0)?;
+        log.try_serialize(&mut &mut self.activity_log.try_borrow_mut_data()?[..])?;
+
+        msg!("Migrated contract {} to layout {}", self.contract.key(), LAYOUT_VERSION);
+        Ok(())
+    }
+
+    fn migrate_contract(&self, milestone_accounts: &[AccountInfo<'info>], log_bump: u8) -> Result<bool> {
+        let data = self.contract.try_borrow_data()?;
+        match layout_version::<Contract>(&data)? {
+            LAYOUT_VERSION => Ok(false),
+            0 => {
+                let old = decode_v0::<Contract>(&data)?;
+                drop(data);
+                self.upgrade_contract_v0(old, milestone_accounts, log_bump)?;
+                Ok(true)
+            }
+            _ => err!(MigrateError::UnknownLayout),
+        }
+    }
+
+    /// Moves a layout 0 contract's milestones into their own accounts and opens its activity log.
+    fn upgrade_contract_v0(
+        &self,
+        old: ContractV0,
+        milestone_accounts: &[AccountInfo<'info>],
+        log_bump: u8,
+    ) -> Result<()> {
+        let (mut contract, milestones) = old.upgrade(self.contract.key());
+        let payer = self.signer.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        let submitted: Vec<_> = milestones.iter().filter(|m| m.freelancer_submitted).cloned().collect();
+        let released: Vec<_> = milestones.iter().filter(|m| m.is_released).cloned().collect();
+        create_milestones(&mut contract, milestones, milestone_accounts, &payer, &system_program)?;
+        submitted.iter().try_for_each(|m| contract.record_submission(m))?;
+        released.iter().try_for_each(|m| contract.record_milestone_release(m))?;
+        rewrite(&self.contract, &payer, &system_program, &contract, 8 + Contract::INIT_SPACE)?;
+
+        let contract_key = self.contract.key();
+        let seeds: &[&[u8]] = &[ACTIVITY_SEED, contract_key.as_ref(), &[log_bump]];
+        let space = 8 + ActivityLog::INIT_SPACE;
+        create_pda(&self.activity_log, &payer, &system_program, space, &[seeds])?;
+        let log = ActivityLog { contract: contract_key, total: 0, entries: vec![], bump: log_bump };
+        log.try_serialize(&mut &mut self.activity_log.try_borrow_mut_data()?[..])
+    }
+
+    fn migrate_vault(&self) -> Result<bool> {
+        let data = self.vault_account.try_borrow_data()?;
+        let vault = match layout_version::<VaultAccount>(&data)? {
+            LAYOUT_VERSION => return Ok(false),
+            0 => decode_v0::<VaultAccount>(&data)?.upgrade(),
+            _ => return err!(MigrateError::UnknownLayout),
+        };
+        drop(data);
+        let (payer, system_program) = (self.signer.to_account_info(), self.system_program.to_account_info());
+        rewrite(&self.vault_account, &payer, &system_program, &vault, 8 + VaultAccount::INIT_SPACE)?;
+        Ok(true)
+    }
+
+    fn migrate_approval(&self) -> Result<bool> {
+        let data = self.milestone_approval.try_borrow_data()?;
+        let approval = match layout_version::<MilestoneApproval>(&data)? {
+            LAYOUT_VERSION => return Ok(false),
+            0 => decode_v0::<MilestoneApproval>(&data)?.upgrade(),
+            _ => return err!(MigrateError::UnknownLayout),
+        };
+        drop(data);
+        let (payer, system_program) = (self.signer.to_account_info(), self.system_program.to_account_info());
+        rewrite(&self.milestone_approval, &payer, &system_program, &approval, 8 + MilestoneApproval::INIT_SPACE)?;
+        Ok(true)
+    }
+}
+
+/// Resizes `account` to `space`, topping its rent up from `payer`, and overwrites it with `value`.
+fn rewrite<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    value: &T,
+    space: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+    if current < rent {
+        let cpi_ctx = CpiContext::new(system_program.clone(), Transfer { from: payer.clone(), to: account.clone() });
+        transfer(cpi_ctx, rent - current)?;
+    }
+    account.realloc(space, false)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    data.fill(0);
+    value.try_serialize(&mut &mut data[..])
+}
@@ -1,20 +1,44 @@
 use anchor_lang::prelude::*;
-use crate::state::Contract;
+use crate::state::{ActivityAction, ActivityLog, Contract, Milestone, MilestoneAccount, MilestoneOrdering, VaultAccount};
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, MILESTONE_SEED, VAULT_ACCOUNT_SEED};
 
 #[derive(Accounts)]
+#[instruction(milestone_index: u64)]
 pub struct MilestoneLifecycle<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"contract", contract.client.as_ref(), contract.freelancer.as_ref()],
+        seeds = [CONTRACT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
         bump = contract.contract_bump
     )]
     pub contract: Account<'info, Contract>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, contract.id.as_ref(), &[milestone_index as u8]],
+        bump = milestone.bump,
+        constraint = milestone.index as u64 == milestone_index @ MilestoneError::InvalidMilestoneAccount
+    )]
+    pub milestone: Account<'info, MilestoneAccount>,
+
+    /// Read to tell whether the milestone's escrow is in place before it is submitted.
+    #[account(
+        seeds = [VAULT_ACCOUNT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = vault_account.vault_bump
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
 }
 
-#[error_code]
+#[error_code(offset = 6100)]
 pub enum MilestoneError {
     InvalidSigner,
     AlreadySubmitted,
@@ -22,72 +46,107 @@ pub enum MilestoneError {
     AlreadyConfirmed,
     NotSubmitted,
     NotApproved,
+    OutOfOrder,
+    InvalidDependency,
+    InvalidMilestoneAccount,
+    MissingPrerequisites,
+    TooManyMilestones,
+    ContractTerminated,
+    NotConfirmed,
+    NotFunded,
+    Cancelled,
+    MilestoneAccountInUse,
 }
 
-impl<'info> MilestoneLifecycle<'info> {
-    pub fn freelancer_submit(&mut self, milestone_index: u64) -> Result<()> {
-        let milestone_index = milestone_index as usize;
+/// Checks new milestones, numbered from `first`, against the contract's ordering policy. Dependencies may
+/// only point at earlier milestones, which also rules out cycles.
+pub fn validate_milestone_ordering(ordering: MilestoneOrdering, first: usize, milestones: &[Milestone]) -> Result<()> {
+    for (index, milestone) in (first..).zip(milestones) {
+        if ordering != MilestoneOrdering::Dependencies {
+            require!(milestone.depends_on.is_empty(), MilestoneError::InvalidDependency);
+            continue;
+        }
+        for (n, &dep) in milestone.depends_on.iter().enumerate() {
+            require!((dep as usize) < index, MilestoneError::InvalidDependency);
+            require!(!milestone.depends_on[..n].contains(&dep), MilestoneError::InvalidDependency);
+        }
+    }
+    Ok(())
+}
 
-        let freelancer_key = self.contract.freelancer;
+/// Checks that every milestone `milestone_index` waits for has been released. Their accounts are passed as
+/// remaining accounts, in the order `Contract::prerequisites` lists them.
+fn require_prerequisites<'a>(
+    contract: &Contract,
+    milestone_index: u8,
+    milestone: &Milestone,
+    prerequisites: &'a [AccountInfo<'a>],
+) -> Result<()> {
+    let needed = contract.prerequisites(milestone_index, milestone);
+    require!(prerequisites.len() >= needed.len(), MilestoneError::MissingPrerequisites);
+    for (&index, info) in needed.iter().zip(prerequisites) {
+        let prerequisite = Account::<MilestoneAccount>::try_from(info)?;
+        require!(
+            prerequisite.contract_id == contract.id && prerequisite.index == index,
+            MilestoneError::InvalidMilestoneAccount
+        );
+        // A cancelled milestone no longer holds up the ones after it.
+        require!(prerequisite.is_released || prerequisite.cancelled, MilestoneError::OutOfOrder);
+    }
+    Ok(())
+}
 
-        let milestone = self
-            .contract
-            .milestones
-            .get_mut(milestone_index)
-            .ok_or(MilestoneError::NotSubmitted)?;
+impl<'info> MilestoneLifecycle<'info> {
+    fn log(&mut self, action: ActivityAction, milestone_index: u64) -> Result<()> {
+        self.activity_log.record(self.signer.key(), action, Some(milestone_index as u8), 0)
+    }
 
+    pub fn freelancer_submit(&mut self, milestone_index: u64, prerequisites: &'info [AccountInfo<'info>]) -> Result<()> {
+        require_prerequisites(&self.contract, self.milestone.index, &self.milestone, prerequisites)?;
         require!(
-            self.signer.key() == freelancer_key,
+            self.signer.key() == self.contract.freelancer,
             MilestoneError::InvalidSigner
         );
-        require!(!milestone.freelancer_submitted, MilestoneError::AlreadySubmitted);
+        require!(self.contract.status != "terminated", MilestoneError::ContractTerminated);
+        require!(!self.milestone.cancelled, MilestoneError::Cancelled);
+        require!(!self.milestone.freelancer_submitted, MilestoneError::AlreadySubmitted);
+        require!(
+            self.contract.is_funded(&self.milestone, self.vault_account.balance)?,
+            MilestoneError::NotFunded
+        );
 
-        milestone.freelancer_submitted = true;
-        Ok(())
+        self.milestone.freelancer_submitted = true;
+        self.contract.record_submission(&self.milestone)?;
+        self.log(ActivityAction::MilestoneSubmitted, milestone_index)
     }
 
-    pub fn client_approve(&mut self, milestone_index: u64) -> Result<()> {
-        let milestone_index = milestone_index as usize;
-
-        let client_key = self.contract.client;
-
-        let milestone = self
-            .contract
-            .milestones
-            .get_mut(milestone_index)
-            .ok_or(MilestoneError::NotSubmitted)?;
-
+    pub fn client_approve(&mut self, milestone_index: u64, prerequisites: &'info [AccountInfo<'info>]) -> Result<()> {
+        require_prerequisites(&self.contract, self.milestone.index, &self.milestone, prerequisites)?;
         require!(
-            self.signer.key() == client_key,
+            self.signer.key() == self.contract.client,
             MilestoneError::InvalidSigner
         );
-        require!(milestone.freelancer_submitted, MilestoneError::NotSubmitted);
-        require!(!milestone.client_approved, MilestoneError::AlreadyApproved);
+        require!(self.contract.status != "terminated", MilestoneError::ContractTerminated);
+        require!(!self.milestone.cancelled, MilestoneError::Cancelled);
+        require!(self.milestone.freelancer_submitted, MilestoneError::NotSubmitted);
+        require!(!self.milestone.client_approved, MilestoneError::AlreadyApproved);
 
-        milestone.client_approved = true;
-        Ok(())
+        self.milestone.client_approved = true;
+        self.log(ActivityAction::MilestoneApproved, milestone_index)
     }
 
     pub fn freelancer_confirm(&mut self, milestone_index: u64) -> Result<()> {
-        let milestone_index = milestone_index as usize;
-
-        let freelancer_key = self.contract.freelancer;
-
-        let milestone = self
-            .contract
-            .milestones
-            .get_mut(milestone_index)
-            .ok_or(MilestoneError::NotSubmitted)?;
-
         require!(
-            self.signer.key() == freelancer_key,
+            self.signer.key() == self.contract.freelancer,
             MilestoneError::InvalidSigner
         );
-        require!(milestone.freelancer_submitted, MilestoneError::NotSubmitted);
-        require!(milestone.client_approved, MilestoneError::NotApproved);
-        require!(!milestone.freelancer_confirmed, MilestoneError::AlreadyConfirmed);
-
-        milestone.freelancer_confirmed = true;
-        Ok(())
+        require!(self.contract.status != "terminated", MilestoneError::ContractTerminated);
+        require!(!self.milestone.cancelled, MilestoneError::Cancelled);
+        require!(self.milestone.freelancer_submitted, MilestoneError::NotSubmitted);
+        require!(self.milestone.client_approved, MilestoneError::NotApproved);
+        require!(!self.milestone.freelancer_confirmed, MilestoneError::AlreadyConfirmed);
+
+        self.milestone.freelancer_confirmed = true;
+        self.log(ActivityAction::MilestoneConfirmed, milestone_index)
     }
 }
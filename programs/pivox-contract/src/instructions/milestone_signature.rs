@@ -1,6 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
-use crate::state::{Contract, VaultAccount, MilestoneApproval, Milestone};
+use crate::state::{
+    validate_payees, validate_terms, ActivityAction, ActivityLog, Bond, BondError, Contract, ContractTerms, VaultAccount,
+    MilestoneApproval, Milestone, MilestoneTemplate, TemplateError,
+};
+use crate::instructions::{create_milestones, VaultDepositError};
+use crate::constants::{
+    ACTIVITY_SEED, BOND_SEED, CONTRACT_SEED, LAYOUT_VERSION, MILESTONE_APPROVAL_SEED, VAULT_ACCOUNT_SEED,
+};
 
 #[derive(Accounts)]
 pub struct MilestoneSignature<'info> {
@@ -14,7 +21,7 @@ pub struct MilestoneSignature<'info> {
 
     #[account(
         mut,
-        seeds = [b"milestone_approval", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [MILESTONE_APPROVAL_SEED, client.key().as_ref(), freelancer.key().as_ref()],
         bump
        
     )]
@@ -24,7 +31,7 @@ pub struct MilestoneSignature<'info> {
         init,
         payer = freelancer,
         space = 8 + VaultAccount::INIT_SPACE,
-        seeds = [b"vault_account", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
         bump
     )]
     pub vault_account: Account<'info, VaultAccount>,
@@ -33,11 +40,20 @@ pub struct MilestoneSignature<'info> {
         init,
         payer = freelancer,
         space = 8 + Contract::INIT_SPACE,
-        seeds = [b"contract", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
         bump
     )]
     pub contract: Account<'info, Contract>,
 
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + ActivityLog::INIT_SPACE,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
     #[account(
         mut,
         associated_token::mint = usdc_mint,
@@ -52,6 +68,36 @@ pub struct MilestoneSignature<'info> {
     )]
     pub client_ata: Account<'info, TokenAccount>,
 
+    /// Created here, so the contract can be funded as soon as it exists.
+    #[account(
+        init_if_needed,
+        payer = freelancer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_ata: Box<Account<'info, TokenAccount>>,
+
+    /// Only read by `approve_from_template`.
+    pub template: Option<Account<'info, MilestoneTemplate>>,
+
+    /// Only passed when the freelancer posts a performance bond.
+    #[account(
+        init,
+        payer = freelancer,
+        space = 8 + Bond::INIT_SPACE,
+        seeds = [BOND_SEED, contract.key().as_ref()],
+        bump
+    )]
+    pub bond: Option<Account<'info, Bond>>,
+
+    #[account(
+        init,
+        payer = freelancer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = bond,
+    )]
+    pub bond_ata: Option<Account<'info, TokenAccount>>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -60,17 +106,13 @@ pub struct MilestoneSignature<'info> {
 impl<'info> MilestoneSignature<'info> {
     pub fn approve(
         &mut self,
-        client_share: u8,
-        freelancer_share: u8,
-        initial_payment: u64,
-        project_start: i128,
-        project_duration: u64,
-        dispute_resolution: String,
-        vault_bump: u8,
-        contract_bump: u8,
-        vault_status: String,
+        terms: ContractTerms,
         milestones: Vec<Milestone>,
+        milestone_accounts: &[AccountInfo<'info>],
+        bumps: &MilestoneSignatureBumps,
     ) -> Result<()> {
+        validate_terms(terms.client_share, terms.freelancer_share, terms.project_start, &milestones)?;
+
         let milestone_approval = &mut self.milestone_approval;
         let freelancer_key = self.freelancer.key();
 
@@ -82,55 +124,78 @@ impl<'info> MilestoneSignature<'info> {
             !milestone_approval.is_signed,
             ErrorCode::AlreadySigned
         );
+        require!(terms.hash(&milestones)? == milestone_approval.terms_hash, ErrorCode::TermsMismatch);
 
         milestone_approval.approved_by.push(freelancer_key);
         milestone_approval.approvals += 1;
 
         if milestone_approval.approvals == milestone_approval.threshold {
-            self.finalize(
-                client_share,
-                freelancer_share,
-                initial_payment,
-                project_start,
-                project_duration,
-                dispute_resolution,
-                vault_bump,
-                contract_bump,
-                vault_status,
-                milestones,
-            )?;
+            self.finalize(terms, milestones, milestone_accounts, bumps)?;
         }
 
         Ok(())
     }
 
+    /// `approve` with the milestones instantiated from `template` for `total_budget`.
+    pub fn approve_from_template(
+        &mut self,
+        terms: ContractTerms,
+        total_budget: u64,
+        milestone_accounts: &[AccountInfo<'info>],
+        bumps: &MilestoneSignatureBumps,
+    ) -> Result<()> {
+        let milestones = self
+            .template
+            .as_ref()
+            .ok_or(TemplateError::MissingTemplate)?
+            .instantiate(total_budget)?;
+
+        self.approve(terms, milestones, milestone_accounts, bumps)
+    }
+
     pub fn finalize(
         &mut self,
-        client_share: u8,
-        freelancer_share: u8,
-        initial_payment: u64,
-        project_start: i128,
-        project_duration: u64,
-        dispute_resolution: String,
-        vault_bump: u8,
-        contract_bump: u8,
-        vault_status: String,
+        terms: ContractTerms,
         milestones: Vec<Milestone>,
+        milestone_accounts: &[AccountInfo<'info>],
+        bumps: &MilestoneSignatureBumps,
     ) -> Result<()> {
+        let ContractTerms {
+            client_share,
+            freelancer_share,
+            initial_payment,
+            project_start,
+            project_duration,
+            dispute_resolution,
+            ordering,
+            release_policy,
+            payees,
+            bond_amount,
+            deposit,
+        } = terms;
+        validate_payees(&payees)?;
         self.milestone_approval.is_signed = true;
 
         self.vault_account.set_inner(VaultAccount {
+            version: LAYOUT_VERSION,
             client: self.client.key(),
             freelancer: self.freelancer.key(),
             balance: 0,
             client_share,
             freelancer_share,
             multisig_account: self.milestone_approval.key(),
-            vault_bump,
-            vault_status,
+            vault_bump: bumps.vault_account,
+            vault_status: "active".to_string(),
+            total_deposited: 0,
+            total_released: 0,
+            total_refunded: 0,
+            total_written_off: 0,
+            client_withdrawn: 0,
+            freelancer_withdrawn: 0,
         });
 
         self.contract.set_inner(Contract {
+            version: LAYOUT_VERSION,
             client: self.client.key(),
             freelancer: self.freelancer.key(),
             vault_account: self.vault_account.key(),
@@ -142,21 +207,108 @@ impl<'info> MilestoneSignature<'info> {
             freelancer_share,
             dispute_resolution,
             status: "active".to_string(),
-            contract_bump,
+            contract_bump: bumps.contract,
             client_approved: false,
             freelancer_approved: false,
+            ordering,
+            release_policy,
+            parent: None,
+            payees,
+            id: self.contract.key(),
+            milestone_count: 0,
+            milestones_total: 0,
+            submitted_count: 0,
+            released_count: 0,
+            unreleased: 0,
+            subcontracted: 0,
+            open_subcontracts: 0,
+            payouts: vec![],
+            receipt_count: 0,
+            unsubmitted_funding: 0,
+            cancelled_count: 0,
+            unsubmitted: 0,
+        });
+        create_milestones(
+            &mut self.contract,
             milestones,
+            milestone_accounts,
+            &self.freelancer.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+
+        self.activity_log.set_inner(ActivityLog {
+            contract: self.contract.key(),
+            total: 0,
+            entries: vec![],
+            bump: bumps.activity_log,
         });
+        let budget = self.contract.outstanding()?;
+        self.activity_log.record(self.freelancer.key(), ActivityAction::Created, None, budget)?;
+
+        self.post_bond(bond_amount, bumps.bond)?;
+        self.first_deposit(deposit)
+    }
 
+    /// Funds the new vault in the same transaction, which the client co-signs.
+    fn first_deposit(&mut self, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        require!(self.client.is_signer, ErrorCode::ClientSignatureRequired);
+        require!(amount <= self.contract.outstanding()?, VaultDepositError::Overflow);
 
-        Ok(())
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.client_ata.to_account_info(),
+                to: self.vault_ata.to_account_info(),
+                authority: self.client.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, amount)?;
+
+        self.vault_account.record_deposit(amount)?;
+        self.activity_log.record(self.client.key(), ActivityAction::Deposited, None, amount)
+    }
+
+    fn post_bond(&mut self, amount: u64, bump: Option<u8>) -> Result<()> {
+        let (Some(bond), Some(bond_ata), Some(bump)) = (self.bond.as_mut(), self.bond_ata.as_ref(), bump) else {
+            require!(amount == 0, BondError::MissingBondAccounts);
+            return Ok(());
+        };
+        require!(amount > 0, BondError::ZeroAmount);
+
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.freelancer_ata.to_account_info(),
+                to: bond_ata.to_account_info(),
+                authority: self.freelancer.to_account_info(),
+            },
+        );
+        transfer(cpi_ctx, amount)?;
+
+        bond.set_inner(Bond {
+            contract: self.contract.key(),
+            client: self.client.key(),
+            freelancer: self.freelancer.key(),
+            posted: amount,
+            slashed: 0,
+            returned: 0,
+            bump,
+        });
+        self.activity_log.record(self.freelancer.key(), ActivityAction::BondPosted, None, amount)
     }
 }
 
-#[error_code]
+#[error_code(offset = 7700)]
 pub enum ErrorCode {
     #[msg("Already Approved")]
     AlreadyApproved,
     #[msg("Already Signed")]
     AlreadySigned,
+    #[msg("The client must co-sign a first deposit")]
+    ClientSignatureRequired,
+    #[msg("Terms differ from the ones the client proposed")]
+    TermsMismatch,
 }
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::TEMPLATE_SEED;
+use crate::state::{MilestoneTemplate, TemplateMilestone};
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateMilestoneTemplate<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + MilestoneTemplate::INIT_SPACE,
+        seeds = [TEMPLATE_SEED, creator.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub template: Account<'info, MilestoneTemplate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateMilestoneTemplate<'info> {
+    pub fn create_template(
+        &mut self,
+        name: String,
+        milestones: Vec<TemplateMilestone>,
+        bump: u8,
+    ) -> Result<()> {
+        MilestoneTemplate::validate(&name, &milestones)?;
+
+        self.template.set_inner(MilestoneTemplate {
+            creator: self.creator.key(),
+            name,
+            milestones,
+            bump,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseMilestoneTemplate<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        close = creator,
+        has_one = creator,
+        seeds = [TEMPLATE_SEED, creator.key().as_ref(), template.name.as_bytes()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, MilestoneTemplate>,
+}
@@ -1,20 +1,40 @@
-pub mod initialize;
-pub mod intialize_milestone_approval;
+pub mod propose;
 pub mod milestone_signature;
 pub mod contract_func;
 pub mod vault_action;
 pub mod vault_deposit;
 pub mod vault_withdraw;
 pub mod milestone_lifecycle;
+pub mod accept_offer;
+pub mod reconcile;
+pub mod milestone_template;
+pub mod bond_settlement;
+pub mod bonus;
+pub mod reassign;
+pub mod subcontract;
+pub mod payment_receipt;
+pub mod add_milestones;
+pub mod migrate;
+pub mod cancel_milestone;
 
 
 
-pub use initialize::*;
-pub use intialize_milestone_approval::*;
+pub use propose::*;
 pub use milestone_signature::*;
 pub use contract_func::*;
 pub use vault_action::*;
 pub use vault_deposit::*;
 pub use vault_withdraw::*;
 pub use milestone_lifecycle::*;
+pub use accept_offer::*;
+pub use reconcile::*;
+pub use milestone_template::*;
+pub use bond_settlement::*;
+pub use bonus::*;
+pub use reassign::*;
+pub use subcontract::*;
+pub use payment_receipt::*;
+pub use add_milestones::*;
+pub use migrate::*;
+pub use cancel_milestone::*;
 
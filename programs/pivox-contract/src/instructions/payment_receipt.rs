@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer};
+
+use crate::constants::RECEIPT_SEED;
+use crate::state::{Contract, PaymentReceipt, ReceiptDetails, ReceiptError};
+
+/// Creates the contract's next `PaymentReceipt` at `receipt` and bumps the contract's receipt counter.
+/// Receipts derive from the contract's `id`, so they stay reachable after a reassignment moves the
+/// contract to a new address. The account is created here rather than with `init` so instructions that
+/// only sometimes pay, like the first approval of a termination, don't leave empty receipts behind.
+pub fn issue_receipt<'info>(
+    receipt: &AccountInfo<'info>,
+    rent_payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    contract: &mut Contract,
+    details: ReceiptDetails,
+) -> Result<()> {
+    let number = contract.receipt_count;
+    let contract_id = contract.id;
+    let sequence = number.to_le_bytes();
+    let (address, bump) =
+        Pubkey::find_program_address(&[RECEIPT_SEED, contract_id.as_ref(), &sequence], &crate::ID);
+    require_keys_eq!(receipt.key(), address, ReceiptError::InvalidReceiptAccount);
+
+    let seeds: &[&[u8]] = &[RECEIPT_SEED, contract_id.as_ref(), &sequence, &[bump]];
+    let space = 8 + PaymentReceipt::INIT_SPACE;
+    create_pda(receipt, rent_payer, system_program, space, &[seeds])?;
+
+    let data = PaymentReceipt {
+        contract: contract_id,
+        number,
+        kind: details.kind,
+        milestone_index: details.milestone_index,
+        payer: details.payer,
+        payee: details.payee,
+        mint: details.mint,
+        amount: details.amount,
+        paid_at: Clock::get()?.unix_timestamp,
+        invoice: details.invoice,
+        rent_payer: rent_payer.key(),
+        bump,
+    };
+    data.try_serialize(&mut &mut receipt.try_borrow_mut_data()?[..])?;
+
+    contract.receipt_count = number + 1;
+    Ok(())
+}
+
+/// What `init` does: create the account, or top up, allocate and assign one that already holds lamports,
+/// so nobody can block an instruction by pre-funding a predictable address.
+pub fn create_pda<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+    if current == 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            system_program.clone(),
+            CreateAccount { from: payer.clone(), to: account.clone() },
+            signer_seeds,
+        );
+        return create_account(cpi_ctx, rent, space as u64, &crate::ID);
+    }
+
+    if current < rent {
+        let cpi_ctx = CpiContext::new(system_program.clone(), Transfer { from: payer.clone(), to: account.clone() });
+        transfer(cpi_ctx, rent - current)?;
+    }
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.clone(),
+        Allocate { account_to_allocate: account.clone() },
+        signer_seeds,
+    );
+    allocate(cpi_ctx, space as u64)?;
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.clone(),
+        Assign { account_to_assign: account.clone() },
+        signer_seeds,
+    );
+    assign(cpi_ctx, &crate::ID)
+}
+
+#[derive(Accounts)]
+pub struct ClosePaymentReceipt<'info> {
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = rent_payer @ ReceiptError::Unauthorized
+    )]
+    pub receipt: Account<'info, PaymentReceipt>,
+}
+
+impl<'info> ClosePaymentReceipt<'info> {
+    pub fn close_payment_receipt(&self) -> Result<()> {
+        msg!("Closed receipt {} of contract {}", self.receipt.number, self.receipt.contract);
+        Ok(())
+    }
+}
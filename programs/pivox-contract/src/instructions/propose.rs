@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{LAYOUT_VERSION, MILESTONE_APPROVAL_SEED};
+use crate::state::MilestoneApproval;
+
+/// Both parties must sign before a contract exists.
+pub const PROPOSAL_THRESHOLD: u16 = 2;
+
+/// First step of creating a contract: the client opens a proposal to a freelancer, counting as the
+/// client's approval of the terms hashed into `terms_hash`. The freelancer accepts it with `approve` or
+/// `approve_from_template` and exactly those terms, which creates the contract, its vault and the vault's
+/// token account.
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    /// CHECK: the wallet being proposed to; it signs when accepting
+    pub freelancer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = client,
+        space = 8 + MilestoneApproval::INIT_SPACE,
+        seeds = [MILESTONE_APPROVAL_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub milestone_approval: Account<'info, MilestoneApproval>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Propose<'info> {
+    pub fn propose(&mut self, terms_hash: [u8; 32], bump: u8) -> Result<()> {
+        let client = self.client.key();
+        self.milestone_approval.set_inner(MilestoneApproval {
+            version: LAYOUT_VERSION,
+            client,
+            freelancer: self.freelancer.key(),
+            initial_payment: 0,
+            threshold: PROPOSAL_THRESHOLD,
+            approved_by: vec![client],
+            approvals: 1,
+            is_signed: false,
+            multisig_bump: bump,
+            terms_hash,
+        });
+        Ok(())
+    }
+}
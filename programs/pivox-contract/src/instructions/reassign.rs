@@ -0,0 +1,372 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::constants::{ACTIVITY_SEED, BOND_SEED, CONTRACT_SEED, MILESTONE_APPROVAL_SEED, VAULT_ACCOUNT_SEED};
+use crate::instructions::MilestoneError;
+use crate::state::{
+    rekey_payee, ActivityAction, ActivityLog, Bond, Contract, MilestoneAccount, MilestoneApproval, VaultAccount,
+};
+
+#[error_code(offset = 7000)]
+pub enum ReassignError {
+    #[msg("Exactly one party must change, to a wallet that is not already on the contract")]
+    InvalidReassignment,
+    #[msg("Incoming signer is not the new party")]
+    IncomingMismatch,
+    #[msg("Contract is terminated")]
+    ContractTerminated,
+    #[msg("The contract has a bond; pass the bond ATA and the new bond accounts")]
+    MissingBondAccounts,
+    #[msg("Contract is a subcontract or has an unsettled subcontract")]
+    SubcontractLinked,
+    #[msg("The contract has no bond; leave out the new bond accounts")]
+    NoBond,
+    #[msg("Pass every milestone account, in index order")]
+    MissingMilestones,
+}
+
+/// Every PDA is seeded with both parties, so a reassignment creates the contract, vault, approval and
+/// activity log under the new seeds, moves the escrowed tokens across and closes the old accounts. The
+/// old accounts' rent goes to the outgoing party. Milestones and receipts stay where they are, under the
+/// contract's `id`, which is the old contract address; since those accounts are never moved, the outgoing
+/// pair can't open a new contract with each other afterwards. Every milestone account is passed as a
+/// remaining account, in index order, so payees naming the outgoing party can be handed to the incoming one.
+#[derive(Accounts)]
+#[instruction(new_client: Pubkey, new_freelancer: Pubkey)]
+pub struct ReassignParty<'info> {
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    #[account(mut)]
+    pub freelancer: Signer<'info>,
+
+    /// The wallet taking over; pays for the new accounts.
+    #[account(mut)]
+    pub incoming: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = contract.contract_bump
+    )]
+    pub contract: Box<Account<'info, Contract>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = vault_account.vault_bump
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_APPROVAL_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump
+    )]
+    pub milestone_approval: Box<Account<'info, MilestoneApproval>>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Box<Account<'info, ActivityLog>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault_account
+    )]
+    pub vault_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        space = 8 + Contract::INIT_SPACE,
+        seeds = [CONTRACT_SEED, new_client.as_ref(), new_freelancer.as_ref()],
+        bump
+    )]
+    pub new_contract: Box<Account<'info, Contract>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        space = 8 + VaultAccount::INIT_SPACE,
+        seeds = [VAULT_ACCOUNT_SEED, new_client.as_ref(), new_freelancer.as_ref()],
+        bump
+    )]
+    pub new_vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        space = 8 + MilestoneApproval::INIT_SPACE,
+        seeds = [MILESTONE_APPROVAL_SEED, new_client.as_ref(), new_freelancer.as_ref()],
+        bump
+    )]
+    pub new_milestone_approval: Box<Account<'info, MilestoneApproval>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        space = 8 + ActivityLog::INIT_SPACE,
+        seeds = [ACTIVITY_SEED, new_contract.key().as_ref()],
+        bump
+    )]
+    pub new_activity_log: Box<Account<'info, ActivityLog>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = new_vault_account
+    )]
+    pub new_vault_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: the contract's bond PDA, which only holds data if the freelancer posted a bond.
+    #[account(
+        mut,
+        seeds = [BOND_SEED, contract.key().as_ref()],
+        bump
+    )]
+    pub bond: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = bond
+    )]
+    pub bond_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        space = 8 + Bond::INIT_SPACE,
+        seeds = [BOND_SEED, new_contract.key().as_ref()],
+        bump
+    )]
+    pub new_bond: Option<Box<Account<'info, Bond>>>,
+
+    #[account(
+        init,
+        payer = incoming,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = new_bond
+    )]
+    pub new_bond_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ReassignParty<'info> {
+    pub fn reassign_party(
+        &mut self,
+        new_client: Pubkey,
+        new_freelancer: Pubkey,
+        milestone_accounts: &'info [AccountInfo<'info>],
+        bumps: &ReassignPartyBumps,
+    ) -> Result<()> {
+        let (client, freelancer) = (self.client.key(), self.freelancer.key());
+        let incoming = match (new_client != client, new_freelancer != freelancer) {
+            (true, false) => new_client,
+            (false, true) => new_freelancer,
+            _ => return err!(ReassignError::InvalidReassignment),
+        };
+        require!(
+            incoming != client && incoming != freelancer && new_client != new_freelancer,
+            ReassignError::InvalidReassignment
+        );
+        require!(self.incoming.key() == incoming, ReassignError::IncomingMismatch);
+        require!(self.contract.status != "terminated", ReassignError::ContractTerminated);
+        require!(
+            self.contract.parent.is_none() && self.contract.open_subcontracts == 0,
+            ReassignError::SubcontractLinked
+        );
+
+        let outgoing = if incoming == new_client { client } else { freelancer };
+        let outgoing_info =
+            if outgoing == client { self.client.to_account_info() } else { self.freelancer.to_account_info() };
+        let mut approved_by = self.milestone_approval.approved_by.clone();
+        for key in approved_by.iter_mut().filter(|key| **key == outgoing) {
+            *key = incoming;
+        }
+        self.new_milestone_approval.set_inner(MilestoneApproval {
+            client: new_client,
+            freelancer: new_freelancer,
+            approved_by,
+            multisig_bump: bumps.new_milestone_approval,
+            ..(**self.milestone_approval).clone()
+        });
+        self.new_vault_account.set_inner(VaultAccount {
+            client: new_client,
+            freelancer: new_freelancer,
+            multisig_account: self.new_milestone_approval.key(),
+            vault_bump: bumps.new_vault_account,
+            ..(**self.vault_account).clone()
+        });
+        self.new_contract.set_inner(Contract {
+            client: new_client,
+            freelancer: new_freelancer,
+            vault_account: self.new_vault_account.key(),
+            multisig_account: self.new_milestone_approval.key(),
+            contract_bump: bumps.new_contract,
+            // A pending termination approval was given to the old pair, not the new one.
+            client_approved: false,
+            freelancer_approved: false,
+            ..(**self.contract).clone()
+        });
+        rekey_payee(&mut self.new_contract.payees, outgoing, incoming);
+        self.rekey_milestone_payees(milestone_accounts, outgoing, incoming)?;
+        self.new_activity_log.set_inner(ActivityLog {
+            contract: self.new_contract.key(),
+            bump: bumps.new_activity_log,
+            ..(**self.activity_log).clone()
+        });
+        self.new_activity_log.record(incoming, ActivityAction::Reassigned, None, 0)?;
+
+        // Move everything the old vault holds, tracked or not, so the ledger carries over unchanged.
+        let vault_seeds = &[
+            VAULT_ACCOUNT_SEED,
+            client.as_ref(),
+            freelancer.as_ref(),
+            &[self.vault_account.vault_bump],
+        ];
+        let vault_signer = &[&vault_seeds[..]];
+        move_and_close(
+            &self.token_program,
+            &self.vault_ata,
+            &self.new_vault_ata,
+            self.vault_account.to_account_info(),
+            outgoing_info.clone(),
+            vault_signer,
+        )?;
+
+        self.migrate_bond(new_client, new_freelancer, &outgoing_info, bumps)?;
+
+        self.contract.close(outgoing_info.clone())?;
+        self.vault_account.close(outgoing_info.clone())?;
+        self.milestone_approval.close(outgoing_info.clone())?;
+        self.activity_log.close(outgoing_info)?;
+
+        msg!("Reassigned contract from {} to {}", outgoing, incoming);
+        Ok(())
+    }
+
+    fn rekey_milestone_payees(
+        &self,
+        milestone_accounts: &'info [AccountInfo<'info>],
+        outgoing: Pubkey,
+        incoming: Pubkey,
+    ) -> Result<()> {
+        require!(
+            milestone_accounts.len() == self.contract.milestone_count as usize,
+            ReassignError::MissingMilestones
+        );
+        for (index, info) in milestone_accounts.iter().enumerate() {
+            let mut milestone = Account::<MilestoneAccount>::try_from(info)?;
+            require!(
+                milestone.contract_id == self.contract.id && milestone.index as usize == index,
+                MilestoneError::InvalidMilestoneAccount
+            );
+            if rekey_payee(&mut milestone.payees, outgoing, incoming) {
+                milestone.exit(&crate::ID)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn migrate_bond(
+        &mut self,
+        new_client: Pubkey,
+        new_freelancer: Pubkey,
+        rent_to: &AccountInfo<'info>,
+        bumps: &ReassignPartyBumps,
+    ) -> Result<()> {
+        if self.bond.data_is_empty() {
+            require!(self.new_bond.is_none() && self.new_bond_ata.is_none(), ReassignError::NoBond);
+            return Ok(());
+        }
+        require_keys_eq!(*self.bond.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        let old_bond = Bond::try_deserialize(&mut &self.bond.try_borrow_data()?[..])?;
+        let (Some(bond_ata), Some(new_bond), Some(new_bond_ata), Some(new_bond_bump)) = (
+            self.bond_ata.as_ref(),
+            self.new_bond.as_mut(),
+            self.new_bond_ata.as_ref(),
+            bumps.new_bond,
+        ) else {
+            return err!(ReassignError::MissingBondAccounts);
+        };
+
+        new_bond.set_inner(Bond {
+            contract: self.new_contract.key(),
+            client: new_client,
+            freelancer: new_freelancer,
+            bump: new_bond_bump,
+            ..old_bond.clone()
+        });
+
+        let contract_key = self.contract.key();
+        let bond_seeds = &[BOND_SEED, contract_key.as_ref(), &[old_bond.bump]];
+        let bond_signer = &[&bond_seeds[..]];
+        move_and_close(
+            &self.token_program,
+            bond_ata,
+            new_bond_ata,
+            self.bond.to_account_info(),
+            rent_to.clone(),
+            bond_signer,
+        )?;
+        close_program_account(self.bond.to_account_info(), rent_to.clone())
+    }
+}
+
+/// Transfers all of `from` to `to` and closes `from`, with `authority` signing as a PDA.
+fn move_and_close<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    rent_to: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if from.amount > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        );
+        transfer(cpi_ctx, from.amount)?;
+    }
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: from.to_account_info(),
+            destination: rent_to,
+            authority,
+        },
+        signer_seeds,
+    );
+    close_account(cpi_ctx)
+}
+
+/// What `close = ...` does for a typed account, for the bond PDA we only hold as an `UncheckedAccount`.
+fn close_program_account<'info>(account: AccountInfo<'info>, rent_to: AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    let total = rent_to.lamports().checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+    **rent_to.try_borrow_mut_lamports()? = total;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&anchor_lang::system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
+}
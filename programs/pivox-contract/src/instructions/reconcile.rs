@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, VAULT_ACCOUNT_SEED};
+use crate::instructions::issue_receipt;
+use crate::state::{ActivityAction, ActivityLog, Contract, LedgerError, ReceiptDetails, ReceiptKind, VaultAccount};
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    /// Pays the rent of the receipt for a refund.
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = contract.contract_bump
+    )]
+    pub contract: Account<'info, Contract>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
+        bump = vault_account.vault_bump
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = vault_account
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contract.client
+    )]
+    pub client_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    /// CHECK: the contract's next receipt address, only created when there is a surplus to refund.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Reconcile<'info> {
+    /// Compares the ledger with the vault ATA. Tokens sent straight to the ATA are booked as a deposit
+    /// and refunded to the client; tokens missing from the ATA are written off the balance.
+    pub fn reconcile(&mut self) -> Result<()> {
+        let signer_key = self.signer.key();
+        require!(
+            signer_key == self.contract.client || signer_key == self.contract.freelancer,
+            LedgerError::Unauthorized
+        );
+
+        let (surplus, deficit) = self.vault_account.sync_to(self.vault_ata.amount)?;
+
+        if surplus > 0 {
+            let seeds = &[
+                VAULT_ACCOUNT_SEED,
+                self.contract.client.as_ref(),
+                self.contract.freelancer.as_ref(),
+                &[self.vault_account.vault_bump],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault_ata.to_account_info(),
+                    to: self.client_ata.to_account_info(),
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer(cpi_ctx, surplus)?;
+            self.vault_account.record_refund(surplus)?;
+            self.activity_log.record(signer_key, ActivityAction::Reconciled, None, surplus)?;
+
+            let details = ReceiptDetails {
+                kind: ReceiptKind::Refund,
+                milestone_index: None,
+                payer: self.vault_account.key(),
+                payee: self.contract.client,
+                mint: self.usdc_mint.key(),
+                amount: surplus,
+                invoice: String::new(),
+            };
+            issue_receipt(
+                &self.receipt.to_account_info(),
+                &self.signer.to_account_info(),
+                &self.system_program.to_account_info(),
+                &mut self.contract,
+                details,
+            )?;
+        }
+
+        msg!("Reconciled vault: surplus {} refunded, deficit {} written off", surplus, deficit);
+        Ok(())
+    }
+}
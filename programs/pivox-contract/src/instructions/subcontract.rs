@@ -0,0 +1,354 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, MILESTONE_SEED, VAULT_ACCOUNT_SEED};
+use crate::instructions::{issue_receipt, MilestoneError};
+use crate::state::{
+    ActivityAction, ActivityLog, Contract, LedgerError, Milestone, MilestoneAccount, ParentLink, ReceiptDetails,
+    ReceiptKind, SubcontractLink, VaultAccount,
+};
+
+#[error_code(offset = 7100)]
+pub enum SubcontractError {
+    #[msg("Only the parent milestone's freelancer, as the subcontract's client, can do this; funding also needs the parent client")]
+    Unauthorized,
+    #[msg("Contract cannot be a subcontract of this milestone")]
+    InvalidChild,
+    #[msg("Milestone is already subcontracted")]
+    AlreadySubcontracted,
+    #[msg("Milestone has already been released")]
+    MilestoneReleased,
+    #[msg("Subcontract is already funded")]
+    NothingToFund,
+    #[msg("Subcontract needs more than the parent milestone holds")]
+    ExceedsMilestone,
+    #[msg("Subcontract is not linked to this parent milestone")]
+    NotLinked,
+    #[msg("Unfinished subcontracts can only be settled with the subcontractor's signature")]
+    ConsentRequired,
+    #[msg("Milestone's subcontract has not been settled")]
+    Unsettled,
+    #[msg("Subcontracts are closed with settle_subcontract")]
+    SettleInstead,
+    #[msg("Milestones cannot be created with a subcontract link")]
+    LinkNotAllowed,
+    #[msg("Milestones priced in a quote currency can't be subcontracted")]
+    PricedMilestone,
+}
+
+/// New contracts start without subcontract links; only `fund_subcontract` creates them.
+pub fn require_no_subcontracts(milestones: &[Milestone]) -> Result<()> {
+    require!(milestones.iter().all(|m| m.subcontract.is_none()), SubcontractError::LinkNotAllowed);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(milestone_index: u8)]
+pub struct FundSubcontract<'info> {
+    /// Freelancer on the parent contract and client on the child.
+    pub freelancer: Signer<'info>,
+
+    /// Client on the parent contract; agrees to part of the milestone's escrow leaving their vault.
+    pub parent_client: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, parent_contract.client.as_ref(), parent_contract.freelancer.as_ref()],
+        bump = parent_contract.contract_bump
+    )]
+    pub parent_contract: Box<Account<'info, Contract>>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, parent_contract.id.as_ref(), &[milestone_index]],
+        bump = parent_milestone.bump
+    )]
+    pub parent_milestone: Box<Account<'info, MilestoneAccount>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, parent_contract.client.as_ref(), parent_contract.freelancer.as_ref()],
+        bump = parent_vault_account.vault_bump
+    )]
+    pub parent_vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = parent_vault_account
+    )]
+    pub parent_vault_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, child_contract.client.as_ref(), child_contract.freelancer.as_ref()],
+        bump = child_contract.contract_bump
+    )]
+    pub child_contract: Box<Account<'info, Contract>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, child_contract.client.as_ref(), child_contract.freelancer.as_ref()],
+        bump = child_vault_account.vault_bump
+    )]
+    pub child_vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = child_vault_account
+    )]
+    pub child_vault_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, parent_contract.key().as_ref()],
+        bump = parent_activity_log.bump
+    )]
+    pub parent_activity_log: Box<Account<'info, ActivityLog>>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, child_contract.key().as_ref()],
+        bump = child_activity_log.bump
+    )]
+    pub child_activity_log: Box<Account<'info, ActivityLog>>,
+
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundSubcontract<'info> {
+    /// Links the child contract to the parent milestone and funds all of the child's milestones from the
+    /// parent milestone's escrow. Both parties of the parent contract sign.
+    pub fn fund_subcontract(&mut self, milestone_index: u8) -> Result<()> {
+        let freelancer = self.freelancer.key();
+        require!(
+            freelancer == self.parent_contract.freelancer
+                && freelancer == self.child_contract.client
+                && self.parent_client.key() == self.parent_contract.client,
+            SubcontractError::Unauthorized
+        );
+        require!(
+            self.child_contract.key() != self.parent_contract.key()
+                && self.child_contract.parent.is_none()
+                && self.child_contract.status != "terminated",
+            SubcontractError::InvalidChild
+        );
+
+        let milestone = &self.parent_milestone;
+        require!(!milestone.is_released, SubcontractError::MilestoneReleased);
+        require!(!milestone.cancelled, MilestoneError::Cancelled);
+        require!(milestone.subcontract.is_none(), SubcontractError::AlreadySubcontracted);
+        // The parent's payout is only known at release, so it can't be carved up in advance.
+        require!(milestone.pricing.is_none(), SubcontractError::PricedMilestone);
+        require!(
+            self.parent_contract.is_funded(milestone, self.parent_vault_account.balance)?,
+            MilestoneError::NotFunded
+        );
+
+        let amount = self.child_contract.outstanding()?.saturating_sub(self.child_vault_account.balance);
+        require!(amount > 0, SubcontractError::NothingToFund);
+        require!(amount <= milestone.amount, SubcontractError::ExceedsMilestone);
+
+        let parent = &self.parent_contract;
+        let seeds = &[
+            VAULT_ACCOUNT_SEED,
+            parent.client.as_ref(),
+            parent.freelancer.as_ref(),
+            &[self.parent_vault_account.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.parent_vault_ata.to_account_info(),
+                to: self.child_vault_ata.to_account_info(),
+                authority: self.parent_vault_account.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer(cpi_ctx, amount)?;
+
+        // Paid out of the parent on the freelancer's behalf, and deposited into the child.
+        self.parent_vault_account.record_release(amount)?;
+        self.child_vault_account.record_deposit(amount)?;
+        let index = Some(milestone_index);
+        self.parent_activity_log.record(freelancer, ActivityAction::SubcontractFunded, index, amount)?;
+        self.child_activity_log.record(freelancer, ActivityAction::Deposited, None, amount)?;
+
+        let child = self.child_contract.key();
+        self.parent_milestone.subcontract = Some(SubcontractLink { child, amount, settled: false });
+        let parent = &mut self.parent_contract;
+        parent.subcontracted = parent.subcontracted.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        parent.open_subcontracts = parent.open_subcontracts.checked_add(1).ok_or(LedgerError::Overflow)?;
+        // That part of the milestone's escrow has left the vault, so termination can't refund it.
+        if !self.parent_milestone.freelancer_submitted {
+            parent.book_unsubmitted_escrow_out(&self.parent_milestone, amount)?;
+        }
+        self.child_contract.parent = Some(ParentLink { contract: self.parent_contract.key(), milestone_index });
+
+        msg!("Funded subcontract {} with {} from milestone {}", child, amount, milestone_index);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SettleSubcontract<'info> {
+    /// Client of the subcontract, i.e. the parent milestone's freelancer; receives what is unspent.
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    /// CHECK: the subcontractor; only has to sign while child milestones are unreleased.
+    pub freelancer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, parent_contract.client.as_ref(), parent_contract.freelancer.as_ref()],
+        bump = parent_contract.contract_bump
+    )]
+    pub parent_contract: Box<Account<'info, Contract>>,
+
+    /// The parent milestone the subcontract is linked to.
+    #[account(mut)]
+    pub parent_milestone: Box<Account<'info, MilestoneAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = child_contract.contract_bump
+    )]
+    pub child_contract: Box<Account<'info, Contract>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
+        bump = child_vault_account.vault_bump
+    )]
+    pub child_vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = child_vault_account
+    )]
+    pub child_vault_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = client
+    )]
+    pub client_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, parent_contract.key().as_ref()],
+        bump = parent_activity_log.bump
+    )]
+    pub parent_activity_log: Box<Account<'info, ActivityLog>>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, child_contract.key().as_ref()],
+        bump = child_activity_log.bump
+    )]
+    pub child_activity_log: Box<Account<'info, ActivityLog>>,
+
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: the child contract's next receipt address, only created when something is refunded.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SettleSubcontract<'info> {
+    /// Terminates the child, returning whatever its vault still holds to the parent freelancer, and
+    /// unblocks the parent milestone. Once every child milestone is released the parent freelancer can
+    /// settle alone; before that the subcontractor has to agree.
+    pub fn settle_subcontract(&mut self) -> Result<()> {
+        let link = self.child_contract.parent.ok_or(SubcontractError::NotLinked)?;
+        require_keys_eq!(link.contract, self.parent_contract.key(), SubcontractError::NotLinked);
+        let milestone = &self.parent_milestone;
+        require!(
+            milestone.contract_id == self.parent_contract.id && milestone.index == link.milestone_index,
+            SubcontractError::NotLinked
+        );
+        let subcontract = milestone.subcontract.ok_or(SubcontractError::NotLinked)?;
+        require_keys_eq!(subcontract.child, self.child_contract.key(), SubcontractError::NotLinked);
+        require!(!subcontract.settled, SubcontractError::NotLinked);
+
+        let finished = self.child_contract.is_complete()?;
+        require!(finished || self.freelancer.is_signer, SubcontractError::ConsentRequired);
+
+        self.child_vault_account.sync_to(self.child_vault_ata.amount)?;
+        let unspent = self.child_vault_ata.amount;
+
+        let (client, freelancer) = (self.client.key(), self.freelancer.key());
+        let seeds = &[
+            VAULT_ACCOUNT_SEED,
+            client.as_ref(),
+            freelancer.as_ref(),
+            &[self.child_vault_account.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        if unspent > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.child_vault_ata.to_account_info(),
+                    to: self.client_ata.to_account_info(),
+                    authority: self.child_vault_account.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer(cpi_ctx, unspent)?;
+            self.child_vault_account.record_refund(unspent)?;
+
+            let details = ReceiptDetails {
+                kind: ReceiptKind::Refund,
+                milestone_index: None,
+                payer: self.child_vault_account.key(),
+                payee: client,
+                mint: self.usdc_mint.key(),
+                amount: unspent,
+                invoice: String::new(),
+            };
+            issue_receipt(
+                &self.receipt.to_account_info(),
+                &self.client.to_account_info(),
+                &self.system_program.to_account_info(),
+                &mut self.child_contract,
+                details,
+            )?;
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.child_vault_ata.to_account_info(),
+                destination: self.client.to_account_info(),
+                authority: self.child_vault_account.to_account_info(),
+            },
+            signer_seeds,
+        );
+        close_account(cpi_ctx)?;
+
+        self.child_contract.status = "terminated".to_string();
+        self.child_vault_account.vault_status = "Terminated".to_string();
+        if let Some(link) = self.parent_milestone.subcontract.as_mut() {
+            link.settled = true;
+        }
+        let open = self.parent_contract.open_subcontracts.checked_sub(1).ok_or(LedgerError::Underflow)?;
+        self.parent_contract.open_subcontracts = open;
+        let index = Some(link.milestone_index);
+        self.parent_activity_log.record(client, ActivityAction::SubcontractSettled, index, unspent)?;
+        self.child_activity_log.record(client, ActivityAction::Terminated, None, unspent)?;
+
+        msg!("Settled subcontract {}; {} unspent returned", self.child_contract.key(), unspent);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 use crate::state::VaultAccount;
+use crate::constants::VAULT_ACCOUNT_SEED;
 
 #[derive(Accounts)]
 pub struct VaultConfig<'info> {
@@ -14,7 +15,7 @@ pub struct VaultConfig<'info> {
 
     #[account(
         mut,
-        seeds = [b"vault_account", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
         bump,
     )]
     pub vault_account: Account<'info, VaultAccount>,
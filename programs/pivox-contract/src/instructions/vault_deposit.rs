@@ -3,7 +3,9 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{transfer, Mint, Token, TokenAccount, Transfer},
 };
-use crate::state::{VaultAccount, Contract};
+use crate::instructions::MilestoneError;
+use crate::state::{ActivityAction, ActivityLog, LedgerError, MilestoneAccount, VaultAccount, Contract};
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, VAULT_ACCOUNT_SEED};
 
 #[derive(Accounts)]
 pub struct VaultDeposit<'info> {
@@ -29,7 +31,7 @@ pub struct VaultDeposit<'info> {
 
     #[account(
         mut,
-        seeds = [b"vault_account", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [VAULT_ACCOUNT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
         bump = vault_account.vault_bump,
     )]
     pub vault_account: Account<'info, VaultAccount>,
@@ -43,11 +45,22 @@ pub struct VaultDeposit<'info> {
 
     #[account(
         mut,
-        seeds = [b"contract", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [CONTRACT_SEED, client.key().as_ref(), freelancer.key().as_ref()],
         bump = contract.contract_bump
     )]
     pub contract: Account<'info, Contract>,
 
+    /// The milestone a tagged deposit funds; not used by untagged deposits.
+    #[account(mut)]
+    pub milestone: Option<Account<'info, MilestoneAccount>>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -91,14 +104,13 @@ impl<'info> VaultDeposit<'info> {
     }*/
 
 
-    pub fn deposit_funds(&mut self, amount: u64) -> Result<()> {
-        // Sum total required milestone funding
-        let total_required: u64 = self
-            .contract
-            .milestones
-            .iter()
-            .map(|m| m.amount)
-            .sum();
+    /// Escrows `amount` for the contract's unpaid milestones. Tagged with `milestone_index`, the deposit
+    /// is exactly that milestone's escrow and funds it on its own, so it can be worked on before the rest.
+    pub fn deposit_funds(&mut self, amount: u64, milestone_index: Option<u64>) -> Result<()> {
+        require!(self.contract.status != "terminated", MilestoneError::ContractTerminated);
+
+        // Only milestones that are still unpaid need funding
+        let total_required = self.contract.outstanding()?;
     
         // Check that current vault balance is less than required
         require!(
@@ -115,6 +127,19 @@ impl<'info> VaultDeposit<'info> {
                 <= total_required,
             VaultDepositError::Overflow
         );
+
+        if let Some(index) = milestone_index {
+            let milestone = self.milestone.as_ref().ok_or(MilestoneError::InvalidMilestoneAccount)?;
+            require!(
+                milestone.contract_id == self.contract.id && milestone.index as u64 == index,
+                MilestoneError::InvalidMilestoneAccount
+            );
+            require!(
+                !(milestone.funded || milestone.is_released || milestone.cancelled),
+                VaultDepositError::AlreadyFunded
+            );
+            require!(amount == milestone.escrow()?, VaultDepositError::MilestoneAmountMismatch);
+        }
     
         // Transfer funds from depositor to vault ATA
         let cpi_accounts = Transfer {
@@ -125,28 +150,28 @@ impl<'info> VaultDeposit<'info> {
         let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
         transfer(cpi_ctx, amount)?;
     
-        // Update vault balance after successful transfer
-        self.vault_account.balance = self
-            .vault_account
-            .balance
-            .checked_add(amount)
-            .ok_or_else(|| error!(VaultDepositError::Overflow))?;
-    
-        Ok(())
+        // Update the ledger after successful transfer
+        self.vault_account.record_deposit(amount)?;
+        if let (Some(_), Some(milestone)) = (milestone_index, self.milestone.as_mut()) {
+            milestone.funded = true;
+            if !milestone.freelancer_submitted {
+                let funding = self.contract.unsubmitted_funding.checked_add(amount).ok_or(LedgerError::Overflow)?;
+                self.contract.unsubmitted_funding = funding;
+            }
+        }
+        let tagged = milestone_index.map(|index| index as u8);
+        self.activity_log.record(self.depositor.key(), ActivityAction::Deposited, tagged, amount)
     }
-    
-
-
-
-
-
-
 }
 
-#[error_code]
+#[error_code(offset = 6300)]
 pub enum VaultDepositError {
     #[msg("Balance overflow")]
     Overflow,
     #[msg("Insufficient funds for milestones")]
     InsufficientFunds,
+    #[msg("Milestone is already funded, released or cancelled")]
+    AlreadyFunded,
+    #[msg("A milestone deposit must be exactly the milestone's escrow")]
+    MilestoneAmountMismatch,
 }
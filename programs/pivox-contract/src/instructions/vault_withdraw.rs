@@ -1,33 +1,35 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer, Mint, Token, TokenAccount, Transfer},
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
 };
 
-use crate::state::{Contract, VaultAccount};
+use crate::instructions::issue_receipt;
+use crate::state::{
+    ActivityAction, ActivityLog, Contract, LedgerError, PayoutKind, Profile, ReceiptDetails, ReceiptKind, VaultAccount,
+};
+use crate::constants::{ACTIVITY_SEED, CONTRACT_SEED, PROFILE_SEED, VAULT_ACCOUNT_SEED};
 
+/// Pays out what termination left in the vault once the unsubmitted milestones were refunded. Each party
+/// withdraws their own share, `VaultAccount::entitlement`, to their own token account.
 #[derive(Accounts)]
 pub struct VaultWithdraw<'info> {
-    /// CHECK
-    #[account(mut)]
-    pub client: AccountInfo<'info>,
-
-    /// CHECK
+    /// The client or the freelancer; pays the receipt's rent and gets the vault ATA's rent once it is empty.
     #[account(mut)]
-    pub freelancer: AccountInfo<'info>,
+    pub signer: Signer<'info>,
 
     pub usdc_mint: Account<'info, Mint>,
 
     #[account(
         mut,
-        seeds = [b"vault_account", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [VAULT_ACCOUNT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
         bump = vault_account.vault_bump,
     )]
     pub vault_account: Account<'info, VaultAccount>,
 
     #[account(
         mut,
-        seeds = [b"contract", client.key().as_ref(), freelancer.key().as_ref()],
+        seeds = [CONTRACT_SEED, contract.client.as_ref(), contract.freelancer.as_ref()],
         bump = contract.contract_bump
     )]
     pub contract: Account<'info, Contract>,
@@ -39,12 +41,39 @@ pub struct VaultWithdraw<'info> {
     )]
     pub vault_ata: Account<'info, TokenAccount>,
 
+    /// The signer's own token account.
     #[account(
         mut,
         associated_token::mint = usdc_mint,
-        associated_token::authority = freelancer,
+        associated_token::authority = signer,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// Both profiles were created by the termination; the freelancer's withdrawals are booked on them.
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, contract.client.as_ref()],
+        bump = client_profile.bump
+    )]
+    pub client_profile: Account<'info, Profile>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, contract.freelancer.as_ref()],
+        bump = freelancer_profile.bump
     )]
-    pub freelancer_ata: Account<'info, TokenAccount>,
+    pub freelancer_profile: Account<'info, Profile>,
+
+    #[account(
+        mut,
+        seeds = [ACTIVITY_SEED, contract.key().as_ref()],
+        bump = activity_log.bump
+    )]
+    pub activity_log: Account<'info, ActivityLog>,
+
+    /// CHECK: the contract's next receipt address, checked and created by `issue_receipt`.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -57,13 +86,19 @@ impl<'info> VaultWithdraw<'info> {
             return Err(error!(VaultWithdrawError::Unauthorized));
         }
 
-        let remaining = self.vault_ata.amount;
+        let signer_key = self.signer.key();
+        let is_client = signer_key == self.contract.client;
+        require!(is_client || signer_key == self.contract.freelancer, VaultWithdrawError::NotAParty);
 
-        let client_key = self.client.key();
-        let freelancer_key = self.freelancer.key();
+        // Book tokens sent to the vault since termination so they are shared out like the rest.
+        self.vault_account.sync_to(self.vault_ata.amount)?;
+        let amount = self.vault_account.entitlement(signer_key);
+        require!(amount > 0, VaultWithdrawError::NothingToWithdraw);
 
+        let client_key = self.contract.client;
+        let freelancer_key = self.contract.freelancer;
         let seeds = &[
-            b"vault_account",
+            VAULT_ACCOUNT_SEED,
             client_key.as_ref(),
             freelancer_key.as_ref(),
             &[self.vault_account.vault_bump],
@@ -74,20 +109,66 @@ impl<'info> VaultWithdraw<'info> {
             self.token_program.to_account_info(),
             Transfer {
                 from: self.vault_ata.to_account_info(),
-                to: self.freelancer_ata.to_account_info(),
+                to: self.destination.to_account_info(),
                 authority: self.vault_account.to_account_info(),
             },
             signer_seeds,
         );
-        transfer(cpi_ctx, remaining)?;
+        transfer(cpi_ctx, amount)?;
+
+        let vault = &mut self.vault_account;
+        let (kind, payer) = if is_client {
+            vault.record_refund(amount)?;
+            vault.client_withdrawn = vault.client_withdrawn.checked_add(amount).ok_or(LedgerError::Overflow)?;
+            (ReceiptKind::Refund, vault.key())
+        } else {
+            vault.record_release(amount)?;
+            vault.freelancer_withdrawn = vault.freelancer_withdrawn.checked_add(amount).ok_or(LedgerError::Overflow)?;
+            self.client_profile.record_paid(amount);
+            self.freelancer_profile.record_earned(amount);
+            self.contract.record_payout(PayoutKind::Termination, amount, String::new())?;
+            (ReceiptKind::Termination, client_key)
+        };
+        self.activity_log.record(signer_key, ActivityAction::Withdrawn, None, amount)?;
+
+        if self.vault_ata.amount == amount {
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: self.vault_ata.to_account_info(),
+                    destination: self.signer.to_account_info(),
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            );
+            close_account(cpi_ctx)?;
+        }
 
-        self.vault_account.balance = 0;
-        Ok(())
+        let details = ReceiptDetails {
+            kind,
+            milestone_index: None,
+            payer,
+            payee: signer_key,
+            mint: self.usdc_mint.key(),
+            amount,
+            invoice: String::new(),
+        };
+        issue_receipt(
+            &self.receipt.to_account_info(),
+            &self.signer.to_account_info(),
+            &self.system_program.to_account_info(),
+            &mut self.contract,
+            details,
+        )
     }
 }
 
-#[error_code]
+#[error_code(offset = 6400)]
 pub enum VaultWithdrawError {
     #[msg("Contract not terminated yet.")]
     Unauthorized,
+    #[msg("Only the client or the freelancer can withdraw")]
+    NotAParty,
+    #[msg("Nothing left of the signer's share to withdraw")]
+    NothingToWithdraw,
 }
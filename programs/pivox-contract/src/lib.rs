@@ -1,11 +1,13 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -16,41 +18,68 @@ pub mod pivox_contract {
 
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, client: Pubkey, freelancer: Pubkey, initial_payment: u64, approved_by: Vec<Pubkey>, approvals: u16, is_signed: bool, threshold: u16, bump: u8) -> Result<()> {
-        ctx.accounts.initialize(client, freelancer, initial_payment, approved_by, approvals, is_signed, threshold, bump)
+    pub fn propose(ctx: Context<Propose>, terms_hash: [u8; 32]) -> Result<()> {
+        ctx.accounts.propose(terms_hash, ctx.bumps.milestone_approval)
     }
 
-    
-    pub fn initialize_milestone_approval(
-        ctx: Context<InitializeMilestoneApproval>,
-        threshold: u16,
+    pub fn approve<'info>(
+        ctx: Context<'_, '_, '_, 'info, MilestoneSignature<'info>>,
+        terms: ContractTerms,
+        milestones: Vec<Milestone>,
+    ) -> Result<()> {
+        ctx.accounts.approve(terms, milestones, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn approve_from_template<'info>(
+        ctx: Context<'_, '_, '_, 'info, MilestoneSignature<'info>>,
+        terms: ContractTerms,
+        total_budget: u64,
     ) -> Result<()> {
-        ctx.accounts.initialize(threshold)
-    }
-
-    pub fn approve(
-        ctx: Context<MilestoneSignature>,
-        client_share: u8,
-        freelancer_share: u8,
-        initial_payment: u64,
-        project_start: i128,
-        project_duration: u64,
-        dispute_resolution: String,
-        vault_bump: u8,
-        contract_bump: u8,
-        vault_status: String,
+        ctx.accounts.approve_from_template(terms, total_budget, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn create_milestone_template(
+        ctx: Context<CreateMilestoneTemplate>,
+        name: String,
+        milestones: Vec<TemplateMilestone>,
+    ) -> Result<()> {
+        ctx.accounts.create_template(name, milestones, ctx.bumps.template)
+    }
+
+    pub fn close_milestone_template(_ctx: Context<CloseMilestoneTemplate>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn accept_offer<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOffer<'info>>, terms: OfferTerms) -> Result<()> {
+        ctx.accounts.accept_offer(terms, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn add_milestones<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddMilestones<'info>>,
         milestones: Vec<Milestone>,
     ) -> Result<()> {
-        ctx.accounts.approve(client_share, freelancer_share, initial_payment, project_start, project_duration, dispute_resolution, vault_bump, contract_bump, vault_status, milestones)
+        ctx.accounts.add_milestones(milestones, ctx.remaining_accounts)
     }
 
+    pub fn cancel_milestone(ctx: Context<CancelMilestone>, milestone_index: u64) -> Result<()> {
+        ctx.accounts.cancel_milestone(milestone_index)
+    }
 
-    pub fn complete_or_cancel_contract(ctx: Context<ContractFunc>) -> Result<()> {
-        ctx.accounts.complete_or_cancel_contract()
+    pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, Migrate<'info>>) -> Result<()> {
+        ctx.accounts.migrate(ctx.remaining_accounts, &ctx.bumps)
     }
 
-    pub fn release_milestone_payment(ctx: Context<ContractFunc>, milestone_index: u64) -> Result<()> {
-        ctx.accounts.release_milestone_payment(milestone_index)
+
+    pub fn complete_or_cancel_contract(ctx: Context<ContractFunc>, invoice: String) -> Result<()> {
+        ctx.accounts.complete_or_cancel_contract(invoice, &ctx.bumps)
+    }
+
+    pub fn release_milestone_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, ContractFunc<'info>>,
+        milestone_index: u64,
+        invoice: String,
+    ) -> Result<()> {
+        ctx.accounts.release_milestone_payment(milestone_index, invoice, ctx.remaining_accounts, &ctx.bumps)
     }
 
     
@@ -65,21 +94,63 @@ pub mod pivox_contract {
 
    
 
-    pub fn deposit_funds(ctx: Context<VaultDeposit>, amount: u64) -> Result<()> {
-        ctx.accounts.deposit_funds(amount)
+    pub fn deposit_funds(ctx: Context<VaultDeposit>, amount: u64, milestone_index: Option<u64>) -> Result<()> {
+        ctx.accounts.deposit_funds(amount, milestone_index)
     }
 
     pub fn withdraw_funds(ctx: Context<VaultWithdraw>) -> Result<()> {
         ctx.accounts.withdraw_funds()
     }
 
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        ctx.accounts.reconcile()
+    }
+
+    pub fn slash_bond(ctx: Context<BondSettlement>, amount: u64, reason: SlashReason) -> Result<()> {
+        ctx.accounts.slash_bond(amount, reason, &ctx.bumps)
+    }
+
+    pub fn return_bond(ctx: Context<BondSettlement>) -> Result<()> {
+        ctx.accounts.return_bond(&ctx.bumps)
+    }
+
+    pub fn reassign_party<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReassignParty<'info>>,
+        new_client: Pubkey,
+        new_freelancer: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.reassign_party(new_client, new_freelancer, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn fund_subcontract(ctx: Context<FundSubcontract>, milestone_index: u8) -> Result<()> {
+        ctx.accounts.fund_subcontract(milestone_index)
+    }
 
-    pub fn freelancer_submit_milestone(ctx: Context<MilestoneLifecycle>, milestone_index: u64) -> Result<()> {
-        ctx.accounts.freelancer_submit(milestone_index)
+    pub fn settle_subcontract(ctx: Context<SettleSubcontract>) -> Result<()> {
+        ctx.accounts.settle_subcontract()
     }
 
-    pub fn client_approve_milestone(ctx: Context<MilestoneLifecycle>, milestone_index: u64) -> Result<()> {
-        ctx.accounts.client_approve(milestone_index)
+    pub fn close_payment_receipt(ctx: Context<ClosePaymentReceipt>) -> Result<()> {
+        ctx.accounts.close_payment_receipt()
+    }
+
+    pub fn pay_bonus(ctx: Context<PayBonus>, amount: u64, source: BonusSource, memo: String) -> Result<()> {
+        ctx.accounts.pay_bonus(amount, source, memo)
+    }
+
+
+    pub fn freelancer_submit_milestone<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MilestoneLifecycle<'info>>,
+        milestone_index: u64,
+    ) -> Result<()> {
+        ctx.accounts.freelancer_submit(milestone_index, ctx.remaining_accounts)
+    }
+
+    pub fn client_approve_milestone<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MilestoneLifecycle<'info>>,
+        milestone_index: u64,
+    ) -> Result<()> {
+        ctx.accounts.client_approve(milestone_index, ctx.remaining_accounts)
     }
 
     pub fn freelancer_confirm_milestone(ctx: Context<MilestoneLifecycle>, milestone_index: u64) -> Result<()> {
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_ACTIVITY_ENTRIES: usize = 64;
+
+/// Audit trail of who did what to a contract, kept on chain so it can be proven without an indexer. It is
+/// a ring buffer of the latest 64 actions: once full, each new entry overwrites the oldest one.
+#[account]
+#[derive(InitSpace)]
+pub struct ActivityLog {
+    pub contract: Pubkey,
+    /// Entries ever appended, including those since overwritten.
+    pub total: u64,
+    /// In write order until the log first fills up; after that the oldest entry sits at `total % 64`.
+    /// [`ActivityLog::chronological`] reads them oldest first.
+    #[max_len(64)]
+    pub entries: Vec<ActivityEntry>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct ActivityEntry {
+    pub actor: Pubkey,
+    pub action: ActivityAction,
+    pub milestone_index: Option<u8>,
+    /// Tokens moved by the action, or the contract's budget for `Created`; 0 when nothing moved.
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/// What happened; stored as a one-byte action code in declaration order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ActivityAction {
+    Created,
+    Deposited,
+    MilestoneSubmitted,
+    MilestoneApproved,
+    MilestoneConfirmed,
+    MilestoneReleased,
+    /// One party's approval of the termination; the second approval is followed by `Terminated`.
+    TerminationApproved,
+    Terminated,
+    /// The actor took over as client or freelancer and the log moved with the contract.
+    Reassigned,
+    /// Milestones appended to a running contract, starting at `milestone_index`, worth `amount` in total.
+    MilestonesAdded,
+    /// The contract's accounts were upgraded to the current layout by `migrate`.
+    Migrated,
+    /// Both parties dropped the milestone; `amount` is the escrow refunded to the client.
+    MilestoneCancelled,
+    /// The actor took `amount` of what termination left in the vault as their share.
+    Withdrawn,
+    /// The client paid the freelancer a bonus of `amount`, from their wallet or from escrow surplus.
+    BonusPaid,
+    /// `amount` sent straight to the vault was refunded to the client.
+    Reconciled,
+    /// `amount` of milestone `milestone_index`'s escrow went to fund a subcontract.
+    SubcontractFunded,
+    /// The subcontract of milestone `milestone_index` was closed; `amount` it didn't spend went to the
+    /// parent freelancer.
+    SubcontractSettled,
+    /// The freelancer posted a bond of `amount` when the contract was created.
+    BondPosted,
+    /// `amount` of the bond went to the client.
+    BondSlashed,
+    /// What was left of the bond, `amount`, went back to the freelancer.
+    BondReturned,
+}
+
+impl ActivityLog {
+    pub fn record(
+        &mut self,
+        actor: Pubkey,
+        action: ActivityAction,
+        milestone_index: Option<u8>,
+        amount: u64,
+    ) -> Result<()> {
+        let entry = ActivityEntry { actor, action, milestone_index, amount, slot: Clock::get()?.slot };
+        if self.entries.len() < MAX_ACTIVITY_ENTRIES {
+            self.entries.push(entry);
+        } else {
+            self.entries[(self.total % MAX_ACTIVITY_ENTRIES as u64) as usize] = entry;
+        }
+        self.total += 1;
+        Ok(())
+    }
+
+    /// The retained entries, oldest first.
+    pub fn chronological(&self) -> impl Iterator<Item = &ActivityEntry> {
+        let oldest = if self.entries.len() < MAX_ACTIVITY_ENTRIES {
+            0
+        } else {
+            (self.total % MAX_ACTIVITY_ENTRIES as u64) as usize
+        };
+        self.entries[oldest..].iter().chain(&self.entries[..oldest])
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+/// Performance bond the freelancer posts when signing a contract. The tokens sit in the bond's own ATA,
+/// apart from the escrow vault, until they are slashed to the client or returned to the freelancer.
+#[account]
+#[derive(InitSpace)]
+pub struct Bond {
+    pub contract: Pubkey,
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub posted: u64,
+    pub slashed: u64,
+    pub returned: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlashReason {
+    /// The deadline passed with milestones never submitted; up to their share of the bond.
+    MissedDeadline,
+    /// Nothing submitted and outstanding for the abandonment grace period after the deadline; all of it.
+    Abandoned,
+    /// The client and the freelancer both sign that the freelancer lost a dispute; any amount they agree on.
+    DisputeLost,
+}
+
+#[error_code(offset = 6800)]
+pub enum BondError {
+    #[msg("Bond account and bond ATA must both be passed to post a bond")]
+    MissingBondAccounts,
+    #[msg("Bond amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Signer is not allowed to settle the bond for this reason")]
+    Unauthorized,
+    #[msg("Bond can no longer be slashed once the contract is terminated")]
+    ContractTerminated,
+    #[msg("Bond is only returned after the contract is terminated")]
+    NotTerminated,
+    #[msg("Project deadline has not passed")]
+    DeadlineNotPassed,
+    #[msg("No milestone is outstanding")]
+    NothingOutstanding,
+    #[msg("Freelancer still has submitted work outstanding, or the grace period has not passed")]
+    NotAbandoned,
+    #[msg("Slash exceeds what this reason allows")]
+    ExceedsSlashable,
+    #[msg("Bond counter overflow")]
+    Overflow,
+}
+
+impl Bond {
+    pub fn remaining(&self) -> u64 {
+        self.posted.saturating_sub(self.slashed).saturating_sub(self.returned)
+    }
+
+    pub fn record_slash(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, BondError::ZeroAmount);
+        require!(amount <= self.remaining(), BondError::ExceedsSlashable);
+        self.slashed = self.slashed.checked_add(amount).ok_or(BondError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn record_return(&mut self, amount: u64) -> Result<()> {
+        self.returned = self.returned.checked_add(amount).ok_or(BondError::Overflow)?;
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{LedgerError, Payee, QuotePricing};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Milestone {
@@ -10,11 +12,120 @@ pub struct Milestone {
     pub client_approved: bool,
     pub freelancer_confirmed: bool,
     pub is_released: bool,
+    /// Indices that must be released before this milestone starts; only used with `Dependencies`.
+    #[max_len(10)]
+    pub depends_on: Vec<u8>,
+    /// Child contract this milestone's work was handed to, if any.
+    pub subcontract: Option<SubcontractLink>,
+    /// Who this milestone's release is split between; falls back to the contract's payees when empty.
+    #[max_len(8)]
+    pub payees: Vec<Payee>,
+    /// Set when the milestone is agreed in a quote currency and paid at the price on release.
+    pub pricing: Option<QuotePricing>,
+    /// Set once a deposit tagged to this milestone has escrowed it.
+    pub funded: bool,
+    /// Set when both parties dropped the milestone; it is never paid and its escrow went back to the client.
+    pub cancelled: bool,
+}
+
+impl Milestone {
+    /// What the vault holds for this milestone: its amount less anything moved into a subcontract.
+    pub fn escrow(&self) -> Result<u64> {
+        let subcontracted = self.subcontract.map_or(0, |s| s.amount);
+        Ok(self.amount.checked_sub(subcontracted).ok_or(LedgerError::Underflow)?)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct SubcontractLink {
+    pub child: Pubkey,
+    /// Moved from this milestone's escrow into the child's vault.
+    pub amount: u64,
+    /// Set once the child is settled; the milestone can't be released before.
+    pub settled: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct ParentLink {
+    pub contract: Pubkey,
+    pub milestone_index: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub enum MilestoneOrdering {
+    /// Milestones can be worked on in any order.
+    #[default]
+    Any,
+    /// Milestone N can only be submitted once N-1 has been released.
+    Sequential,
+    /// Each milestone waits for the indices in its `depends_on`.
+    Dependencies,
+}
+
+/// Who may release an approved milestone's payment, and what has to happen first.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub enum ReleasePolicy {
+    /// The client releases a milestone once they have approved it.
+    #[default]
+    ClientApproval,
+    /// Either party releases a milestone once the client has approved it and the freelancer confirmed it.
+    BothConfirmed,
+    /// Only the freelancer releases, once the client has approved.
+    FreelancerOnly,
+    /// Anyone can release a milestone the client has approved, e.g. a crank paying out on schedule.
+    Permissionless,
+}
+
+impl ReleasePolicy {
+    /// Whether `signer` may release an approved milestone of `contract`.
+    pub fn may_release(&self, contract: &Contract, signer: Pubkey) -> bool {
+        match self {
+            ReleasePolicy::ClientApproval => signer == contract.client,
+            ReleasePolicy::BothConfirmed => signer == contract.client || signer == contract.freelancer,
+            ReleasePolicy::FreelancerOnly => signer == contract.freelancer,
+            ReleasePolicy::Permissionless => true,
+        }
+    }
+
+    /// Whether the freelancer has to confirm a milestone before it is released.
+    pub fn requires_confirmation(&self) -> bool {
+        *self == ReleasePolicy::BothConfirmed
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum PayoutKind {
+    Milestone { index: u8 },
+    Bonus,
+    /// The freelancer's share of the vault, withdrawn after termination.
+    Termination,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub struct Payout {
+    pub kind: PayoutKind,
+    pub amount: u64,
+    pub paid_at: i64,
+    #[max_len(64)]
+    pub memo: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BonusSource {
+    /// Paid straight from the client's token account.
+    Direct,
+    /// Paid from vault funds beyond what unreleased milestones need.
+    EscrowSurplus,
+}
+
+pub const MAX_PAYOUT_HISTORY: usize = 20;
+pub const MAX_MEMO_LEN: usize = 64;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Contract {
+    /// `LAYOUT_VERSION` this account was written with.
+    pub version: u8,
     pub client: Pubkey,
     pub freelancer: Pubkey,
     pub vault_account: Pubkey,
@@ -31,6 +142,150 @@ pub struct Contract {
     pub contract_bump: u8,
     pub client_approved: bool,
     pub freelancer_approved: bool,
-    #[max_len(10)]
-    pub milestones: Vec<Milestone>,
+    pub ordering: MilestoneOrdering,
+    pub release_policy: ReleasePolicy,
+    /// Set on a subcontract, which is funded from and settles back into a parent milestone.
+    pub parent: Option<ParentLink>,
+    /// Default release split for milestones without their own payees; empty pays the freelancer.
+    #[max_len(8)]
+    pub payees: Vec<Payee>,
+    /// What the milestone accounts are derived from: the contract's address when it was created, kept
+    /// when a reassignment moves the contract to a new address.
+    pub id: Pubkey,
+    pub milestone_count: u16,
+    /// Sum of every milestone's amount.
+    pub milestones_total: u64,
+    pub submitted_count: u16,
+    pub released_count: u16,
+    /// Sum of the unreleased milestones' amounts.
+    pub unreleased: u64,
+    /// Escrow moved into the subcontracts of unreleased milestones.
+    pub subcontracted: u64,
+    /// Subcontracts funded and not yet settled.
+    pub open_subcontracts: u16,
+    /// Most recent payouts to the freelancer, oldest first; older entries are dropped once it is full.
+    #[max_len(20)]
+    pub payouts: Vec<Payout>,
+    /// Receipts issued so far; the next receipt is numbered with this.
+    pub receipt_count: u64,
+    /// Escrow deposited for milestones on their own that the freelancer hasn't submitted yet.
+    pub unsubmitted_funding: u64,
+    /// Milestones dropped with `cancel_milestone`; they no longer count towards the totals above.
+    pub cancelled_count: u16,
+    /// Escrow of the milestones the freelancer hasn't submitted yet, funded or not; see
+    /// [`Contract::termination_refund`].
+    pub unsubmitted: u64,
+}
+
+impl Contract {
+    /// What the vault still has to hold for unreleased milestones, less anything already moved into
+    /// subcontracts.
+    pub fn outstanding(&self) -> Result<u64> {
+        Ok(self.unreleased.checked_sub(self.subcontracted).ok_or(LedgerError::Underflow)?)
+    }
+
+    /// Whether `milestone` can be worked on: it was funded on its own, or the vault, holding
+    /// `vault_balance`, covers every outstanding milestone.
+    pub fn is_funded(&self, milestone: &Milestone, vault_balance: u64) -> Result<bool> {
+        Ok(milestone.funded || vault_balance >= self.outstanding()?)
+    }
+
+    /// What goes back to the client on termination, from a vault holding `vault_balance`. Each milestone
+    /// the freelancer never submitted is refunded as `cancel_milestone` would: one funded on its own gets
+    /// its whole escrow back, the others share what untagged deposits hold beyond the submitted milestones.
+    pub fn termination_refund(&self, vault_balance: u64) -> Result<u64> {
+        let funded = self.unsubmitted_funding.min(vault_balance);
+        let unfunded = self.unsubmitted.checked_sub(self.unsubmitted_funding).ok_or(LedgerError::Underflow)?;
+        let submitted = self.outstanding()?.checked_sub(self.unsubmitted).ok_or(LedgerError::Underflow)?;
+        let untagged = vault_balance.checked_sub(funded).ok_or(LedgerError::Underflow)?.saturating_sub(submitted);
+        Ok(funded.checked_add(unfunded.min(untagged)).ok_or(LedgerError::Overflow)?)
+    }
+
+    /// Escrow of the unsubmitted milestones the freelancer can work on, from a vault holding
+    /// `vault_balance`: all of them once the vault covers every outstanding milestone (see
+    /// [`Contract::is_funded`]), otherwise only the ones funded on their own.
+    pub fn funded_unsubmitted(&self, vault_balance: u64) -> Result<u64> {
+        if vault_balance >= self.outstanding()? {
+            Ok(self.unsubmitted)
+        } else {
+            Ok(self.unsubmitted_funding)
+        }
+    }
+
+    pub fn is_complete(&self) -> Result<bool> {
+        let settled = self.released_count.checked_add(self.cancelled_count).ok_or(LedgerError::Overflow)?;
+        Ok(settled == self.milestone_count)
+    }
+
+    /// Payees for `milestone`: its own list, else the contract's. Empty means the freelancer.
+    pub fn payees_for<'a>(&'a self, milestone: &'a Milestone) -> &'a [Payee] {
+        if milestone.payees.is_empty() {
+            &self.payees
+        } else {
+            &milestone.payees
+        }
+    }
+
+    /// Milestones that must be released before milestone `index` can be submitted or approved.
+    pub fn prerequisites(&self, index: u8, milestone: &Milestone) -> Vec<u8> {
+        match self.ordering {
+            MilestoneOrdering::Any => vec![],
+            MilestoneOrdering::Sequential => index.checked_sub(1).into_iter().collect(),
+            MilestoneOrdering::Dependencies => milestone.depends_on.clone(),
+        }
+    }
+
+    /// Books a new milestone into the contract's totals.
+    pub fn record_milestone(&mut self, amount: u64) -> Result<()> {
+        self.milestone_count = self.milestone_count.checked_add(1).ok_or(LedgerError::Overflow)?;
+        self.milestones_total = self.milestones_total.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        self.unreleased = self.unreleased.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        self.unsubmitted = self.unsubmitted.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        Ok(())
+    }
+
+    /// Books the submission of `milestone`, whose escrow stops being refundable.
+    pub fn record_submission(&mut self, milestone: &Milestone) -> Result<()> {
+        self.submitted_count = self.submitted_count.checked_add(1).ok_or(LedgerError::Overflow)?;
+        self.book_unsubmitted_escrow_out(milestone, milestone.escrow()?)
+    }
+
+    /// Takes `amount` of unsubmitted `milestone`'s escrow off the refundable totals.
+    pub fn book_unsubmitted_escrow_out(&mut self, milestone: &Milestone, amount: u64) -> Result<()> {
+        self.unsubmitted = self.unsubmitted.checked_sub(amount).ok_or(LedgerError::Underflow)?;
+        if milestone.funded {
+            self.unsubmitted_funding = self.unsubmitted_funding.checked_sub(amount).ok_or(LedgerError::Underflow)?;
+        }
+        Ok(())
+    }
+
+    /// Books the cancellation of `milestone`, which leaves the totals as if it had never been added.
+    pub fn record_milestone_cancellation(&mut self, milestone: &Milestone) -> Result<()> {
+        self.cancelled_count = self.cancelled_count.checked_add(1).ok_or(LedgerError::Overflow)?;
+        self.milestones_total = self.milestones_total.checked_sub(milestone.amount).ok_or(LedgerError::Underflow)?;
+        self.unreleased = self.unreleased.checked_sub(milestone.amount).ok_or(LedgerError::Underflow)?;
+        if milestone.freelancer_submitted {
+            self.submitted_count = self.submitted_count.checked_sub(1).ok_or(LedgerError::Underflow)?;
+        } else {
+            self.book_unsubmitted_escrow_out(milestone, milestone.escrow()?)?;
+        }
+        Ok(())
+    }
+
+    /// Books the release of `milestone`, including handing back its subcontracted share.
+    pub fn record_milestone_release(&mut self, milestone: &Milestone) -> Result<()> {
+        let subcontracted = milestone.subcontract.map_or(0, |s| s.amount);
+        self.released_count = self.released_count.checked_add(1).ok_or(LedgerError::Overflow)?;
+        self.unreleased = self.unreleased.checked_sub(milestone.amount).ok_or(LedgerError::Underflow)?;
+        self.subcontracted = self.subcontracted.checked_sub(subcontracted).ok_or(LedgerError::Underflow)?;
+        Ok(())
+    }
+
+    pub fn record_payout(&mut self, kind: PayoutKind, amount: u64, memo: String) -> Result<()> {
+        if self.payouts.len() == MAX_PAYOUT_HISTORY {
+            self.payouts.remove(0);
+        }
+        self.payouts.push(Payout { kind, amount, paid_at: Clock::get()?.unix_timestamp, memo });
+        Ok(())
+    }
 }
@@ -0,0 +1,204 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::LAYOUT_VERSION;
+use crate::state::{Contract, Milestone, MilestoneApproval, MilestoneOrdering, ReleasePolicy, VaultAccount};
+
+/// An account type that has been deployed in more than one layout.
+pub trait Versioned: Discriminator {
+    /// The original layout, from before accounts carried a version byte.
+    type V0: AnchorDeserialize;
+
+    /// Size of a layout 0 account, discriminator included. Layout 0 has no version byte, so this is how it
+    /// is told apart.
+    const V0_SPACE: usize;
+}
+
+/// Layout version of `data`, the raw data of a `T`: 0 for the unversioned original, otherwise the version
+/// byte that follows the discriminator.
+pub fn layout_version<T: Versioned>(data: &[u8]) -> Result<u8> {
+    require!(data.starts_with(T::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+    if data.len() == T::V0_SPACE {
+        return Ok(0);
+    }
+    data.get(T::DISCRIMINATOR.len()).copied().ok_or_else(|| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+/// Decodes `data` as a layout 0 `T`; check [`layout_version`] first.
+pub fn decode_v0<T: Versioned>(data: &[u8]) -> Result<T::V0> {
+    T::V0::deserialize(&mut &data[T::DISCRIMINATOR.len()..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct MilestoneV0 {
+    #[max_len(100)]
+    pub description: String,
+    pub amount: u64,
+    pub freelancer_submitted: bool,
+    pub client_approved: bool,
+    pub freelancer_confirmed: bool,
+    pub is_released: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct ContractV0 {
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub vault_account: Pubkey,
+    pub multisig_account: Pubkey,
+    pub initial_payment: u64,
+    pub project_start: i128,
+    pub project_duration: u64,
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    #[max_len(400)]
+    pub dispute_resolution: String,
+    #[max_len(10)]
+    pub status: String,
+    pub contract_bump: u8,
+    pub client_approved: bool,
+    pub freelancer_approved: bool,
+    #[max_len(10)]
+    pub milestones: Vec<MilestoneV0>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct VaultAccountV0 {
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub balance: u64,
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    pub multisig_account: Pubkey,
+    pub vault_bump: u8,
+    #[max_len(10)]
+    pub vault_status: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct MilestoneApprovalV0 {
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub initial_payment: u64,
+    pub threshold: u16,
+    #[max_len(2)]
+    pub approved_by: Vec<Pubkey>,
+    pub approvals: u16,
+    pub is_signed: bool,
+    pub multisig_bump: u8,
+}
+
+impl Versioned for Contract {
+    type V0 = ContractV0;
+    const V0_SPACE: usize = 8 + ContractV0::INIT_SPACE;
+}
+
+impl Versioned for VaultAccount {
+    type V0 = VaultAccountV0;
+    const V0_SPACE: usize = 8 + VaultAccountV0::INIT_SPACE;
+}
+
+impl Versioned for MilestoneApproval {
+    type V0 = MilestoneApprovalV0;
+    const V0_SPACE: usize = 8 + MilestoneApprovalV0::INIT_SPACE;
+}
+
+impl From<MilestoneV0> for Milestone {
+    fn from(old: MilestoneV0) -> Self {
+        Milestone {
+            description: old.description,
+            amount: old.amount,
+            freelancer_submitted: old.freelancer_submitted,
+            client_approved: old.client_approved,
+            freelancer_confirmed: old.freelancer_confirmed,
+            is_released: old.is_released,
+            depends_on: vec![],
+            subcontract: None,
+            payees: vec![],
+            pricing: None,
+            funded: false,
+            cancelled: false,
+        }
+    }
+}
+
+impl ContractV0 {
+    /// The contract in the current layout with no milestones booked yet, and the milestones it held, which
+    /// now go into their own accounts. `id` is the contract's address.
+    pub fn upgrade(self, id: Pubkey) -> (Contract, Vec<Milestone>) {
+        let contract = Contract {
+            version: LAYOUT_VERSION,
+            client: self.client,
+            freelancer: self.freelancer,
+            vault_account: self.vault_account,
+            multisig_account: self.multisig_account,
+            initial_payment: self.initial_payment,
+            project_start: self.project_start,
+            project_duration: self.project_duration,
+            client_share: self.client_share,
+            freelancer_share: self.freelancer_share,
+            dispute_resolution: self.dispute_resolution,
+            status: self.status,
+            contract_bump: self.contract_bump,
+            client_approved: self.client_approved,
+            freelancer_approved: self.freelancer_approved,
+            ordering: MilestoneOrdering::Any,
+            release_policy: ReleasePolicy::ClientApproval,
+            parent: None,
+            payees: vec![],
+            id,
+            milestone_count: 0,
+            milestones_total: 0,
+            submitted_count: 0,
+            released_count: 0,
+            unreleased: 0,
+            subcontracted: 0,
+            open_subcontracts: 0,
+            payouts: vec![],
+            receipt_count: 0,
+            unsubmitted_funding: 0,
+            cancelled_count: 0,
+            unsubmitted: 0,
+        };
+        (contract, self.milestones.into_iter().map(Milestone::from).collect())
+    }
+}
+
+impl VaultAccountV0 {
+    /// Layout 0 kept no ledger, so the whole balance is booked as deposited.
+    pub fn upgrade(self) -> VaultAccount {
+        VaultAccount {
+            version: LAYOUT_VERSION,
+            client: self.client,
+            freelancer: self.freelancer,
+            balance: self.balance,
+            client_share: self.client_share,
+            freelancer_share: self.freelancer_share,
+            multisig_account: self.multisig_account,
+            vault_bump: self.vault_bump,
+            vault_status: self.vault_status,
+            total_deposited: self.balance,
+            total_released: 0,
+            total_refunded: 0,
+            total_written_off: 0,
+            client_withdrawn: 0,
+            freelancer_withdrawn: 0,
+        }
+    }
+}
+
+impl MilestoneApprovalV0 {
+    pub fn upgrade(self) -> MilestoneApproval {
+        MilestoneApproval {
+            version: LAYOUT_VERSION,
+            client: self.client,
+            freelancer: self.freelancer,
+            initial_payment: self.initial_payment,
+            threshold: self.threshold,
+            approved_by: self.approved_by,
+            approvals: self.approvals,
+            is_signed: self.is_signed,
+            multisig_bump: self.multisig_bump,
+            terms_hash: [0; 32],
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::ops::{Deref, DerefMut};
+
+use anchor_lang::prelude::*;
+
+use crate::state::Milestone;
+
+/// Milestone indices are single bytes, so a contract holds at most 256 milestones.
+pub const MAX_MILESTONES: usize = 256;
+
+/// One milestone of a contract, in its own PDA (`["milestone", contract id, index]`) so the contract
+/// account stays small however many milestones it has. Derefs to the milestone itself.
+#[account]
+#[derive(InitSpace)]
+pub struct MilestoneAccount {
+    /// `LAYOUT_VERSION` this account was written with.
+    pub version: u8,
+    /// `Contract::id` of the owning contract.
+    pub contract_id: Pubkey,
+    pub index: u8,
+    pub bump: u8,
+    pub milestone: Milestone,
+}
+
+impl Deref for MilestoneAccount {
+    type Target = Milestone;
+
+    fn deref(&self) -> &Milestone {
+        &self.milestone
+    }
+}
+
+impl DerefMut for MilestoneAccount {
+    fn deref_mut(&mut self) -> &mut Milestone {
+        &mut self.milestone
+    }
+}
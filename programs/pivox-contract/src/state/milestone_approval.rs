@@ -3,6 +3,8 @@ use anchor_lang::prelude::*;
 #[account]
 #[derive(InitSpace)]
 pub struct MilestoneApproval {
+    /// `LAYOUT_VERSION` this account was written with.
+    pub version: u8,
     pub client: Pubkey,
     pub freelancer: Pubkey,
     pub initial_payment: u64,
@@ -12,4 +14,6 @@ pub struct MilestoneApproval {
     pub approvals: u16,
     pub is_signed: bool,
     pub multisig_bump: u8,
+    /// [`ContractTerms::hash`](crate::state::ContractTerms::hash) of what the client proposed.
+    pub terms_hash: [u8; 32],
 }
@@ -1,7 +1,29 @@
 pub mod contract;
 pub mod vault;
 pub mod milestone_approval;
+pub mod offer;
+pub mod template;
+pub mod profile;
+pub mod bond;
+pub mod payee;
+pub mod receipt;
+pub mod activity;
+pub mod milestone;
+pub mod layout;
+pub mod terms;
+pub mod price;
 
 pub use contract::*;
 pub use vault::*;
-pub use milestone_approval::*;
\ No newline at end of file
+pub use milestone_approval::*;
+pub use offer::*;
+pub use template::*;
+pub use profile::*;
+pub use bond::*;
+pub use payee::*;
+pub use receipt::*;
+pub use activity::*;
+pub use milestone::*;
+pub use layout::*;
+pub use terms::*;
+pub use price::*;
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+// Marks a client-signed offer nonce as spent so the same signature cannot create a contract twice.
+#[account]
+#[derive(InitSpace)]
+pub struct ConsumedOffer {
+    pub client: Pubkey,
+    pub freelancer: Pubkey,
+    pub nonce: u64,
+    pub accepted_at: i64,
+    pub bump: u8,
+}
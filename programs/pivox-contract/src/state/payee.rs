@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_PAYEES: usize = 8;
+pub const TOTAL_BPS: u16 = 10_000;
+
+/// A wallet that receives `bps` basis points of a milestone release.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct Payee {
+    pub wallet: Pubkey,
+    pub bps: u16,
+}
+
+#[error_code(offset = 7200)]
+pub enum PayeeError {
+    #[msg("A payee list holds at most 8 wallets")]
+    TooManyPayees,
+    #[msg("Payee weight must be greater than zero")]
+    ZeroWeight,
+    #[msg("Wallet appears twice in the payee list")]
+    DuplicatePayee,
+    #[msg("Payee weights must sum to 10,000 basis points")]
+    InvalidTotal,
+    #[msg("Pass one token account per payee, in payee order")]
+    MissingPayeeAccounts,
+    #[msg("Token account is not the payee's associated token account")]
+    PayeeAccountMismatch,
+}
+
+/// An empty list means "pay the freelancer"; anything else must name distinct wallets whose weights add
+/// up to exactly 10,000.
+pub fn validate_payees(payees: &[Payee]) -> Result<()> {
+    if payees.is_empty() {
+        return Ok(());
+    }
+    require!(payees.len() <= MAX_PAYEES, PayeeError::TooManyPayees);
+    let mut total: u32 = 0;
+    for (i, payee) in payees.iter().enumerate() {
+        require!(payee.bps > 0, PayeeError::ZeroWeight);
+        require!(
+            payees[..i].iter().all(|p| p.wallet != payee.wallet),
+            PayeeError::DuplicatePayee
+        );
+        total += payee.bps as u32;
+    }
+    require!(total == TOTAL_BPS as u32, PayeeError::InvalidTotal);
+    Ok(())
+}
+
+/// Splits `amount` by weight, rounding every share down. The rounding leftover, at most one token unit
+/// per payee, goes to the first payee so the split always adds up to `amount`.
+pub fn split_amount(amount: u64, payees: &[Payee]) -> Vec<u64> {
+    let mut shares: Vec<u64> = payees
+        .iter()
+        .map(|p| (amount as u128 * p.bps as u128 / TOTAL_BPS as u128) as u64)
+        .collect();
+    let leftover = amount - shares.iter().sum::<u64>();
+    if let Some(first) = shares.first_mut() {
+        *first += leftover;
+    }
+    shares
+}
+
+/// Hands `from`'s weight to `to`, folding it into `to`'s entry when `to` is already a payee. Returns whether
+/// `from` was on the list.
+pub fn rekey_payee(payees: &mut Vec<Payee>, from: Pubkey, to: Pubkey) -> bool {
+    let Some(from_index) = payees.iter().position(|p| p.wallet == from) else {
+        return false;
+    };
+    match payees.iter().position(|p| p.wallet == to) {
+        Some(to_index) => {
+            payees[to_index].bps += payees[from_index].bps;
+            payees.remove(from_index);
+        }
+        None => payees[from_index].wallet = to,
+    }
+    true
+}
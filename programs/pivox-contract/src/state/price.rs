@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use crate::state::TOTAL_BPS;
+
+/// Quote amounts carry six decimals, so `1_000_000` is one unit of the quote currency (e.g. one USD).
+pub const QUOTE_DECIMALS: u8 = 6;
+
+// Pyth v2 price account layout: the header fields and the aggregate price this program reads.
+pub const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+pub const PYTH_VERSION: u32 = 2;
+pub const PYTH_PRICE_ACCOUNT: u32 = 3;
+pub const PYTH_STATUS_TRADING: u32 = 1;
+pub const PYTH_PRICE_ACCOUNT_LEN: usize = 240;
+
+/// Terms of a milestone agreed in a quote currency and paid in the contract's token at the price when it
+/// is released. The milestone's `amount` is the token escrow set aside for it and has to cover the worst
+/// payout the slippage bound allows; what the payout doesn't use goes back to the client.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct QuotePricing {
+    /// Pyth-format price account quoting the contract's token in the quote currency.
+    pub feed: Pubkey,
+    /// What the milestone is worth, in quote units with `QUOTE_DECIMALS` decimals.
+    pub quote_amount: u64,
+    /// Tokens the quote came to when the terms were agreed.
+    pub reference_amount: u64,
+    /// How far above `reference_amount` the client lets the payout go, in basis points.
+    pub max_slippage_bps: u16,
+    /// Oldest price, in seconds, a release accepts.
+    pub max_staleness: u32,
+    /// Widest confidence interval a release accepts, in basis points of the price.
+    pub max_confidence_bps: u16,
+}
+
+#[error_code(offset = 7600)]
+pub enum PriceError {
+    #[msg("Release of a priced milestone needs its price feed account")]
+    MissingPriceFeed,
+    #[msg("Price feed account is not the one the milestone was priced with")]
+    PriceFeedMismatch,
+    #[msg("Account is not a Pyth price account")]
+    InvalidPriceAccount,
+    #[msg("Price feed is not trading")]
+    PriceNotTrading,
+    #[msg("Price must be positive")]
+    NonPositivePrice,
+    #[msg("Price is older than the milestone's staleness limit")]
+    StalePrice,
+    #[msg("Price confidence interval is wider than the milestone's limit")]
+    PriceTooUncertain,
+    #[msg("Payout at the current price exceeds the client's slippage bound")]
+    SlippageExceeded,
+    #[msg("Token amount does not fit in a u64")]
+    ConversionOverflow,
+}
+
+/// The aggregate price of a Pyth price account: `price * 10^expo` quote units per token.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+fn read<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    data[offset..offset + N].try_into().unwrap()
+}
+
+impl PythPrice {
+    /// Decodes a Pyth v2 price account, which has to be trading.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= PYTH_PRICE_ACCOUNT_LEN, PriceError::InvalidPriceAccount);
+        require!(
+            u32::from_le_bytes(read(data, 0)) == PYTH_MAGIC
+                && u32::from_le_bytes(read(data, 4)) == PYTH_VERSION
+                && u32::from_le_bytes(read(data, 8)) == PYTH_PRICE_ACCOUNT,
+            PriceError::InvalidPriceAccount
+        );
+        require!(u32::from_le_bytes(read(data, 224)) == PYTH_STATUS_TRADING, PriceError::PriceNotTrading);
+        Ok(PythPrice {
+            expo: i32::from_le_bytes(read(data, 20)),
+            publish_time: i64::from_le_bytes(read(data, 96)),
+            price: i64::from_le_bytes(read(data, 208)),
+            conf: u64::from_le_bytes(read(data, 216)),
+        })
+    }
+
+    /// The same price as a Pyth v2 price account would hold it, e.g. for a local mock feed.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = vec![0; PYTH_PRICE_ACCOUNT_LEN];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+        data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT.to_le_bytes());
+        data[12..16].copy_from_slice(&(PYTH_PRICE_ACCOUNT_LEN as u32).to_le_bytes());
+        data[20..24].copy_from_slice(&self.expo.to_le_bytes());
+        data[96..104].copy_from_slice(&self.publish_time.to_le_bytes());
+        data[208..216].copy_from_slice(&self.price.to_le_bytes());
+        data[216..224].copy_from_slice(&self.conf.to_le_bytes());
+        data[224..228].copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+        data
+    }
+}
+
+impl QuotePricing {
+    /// The most the milestone can pay out in tokens, and so the least its escrow has to be.
+    pub fn max_payout(&self) -> Option<u64> {
+        let bps = TOTAL_BPS as u128 + self.max_slippage_bps as u128;
+        let max = self.reference_amount as u128 * bps / TOTAL_BPS as u128;
+        u64::try_from(max).ok()
+    }
+
+    /// Tokens, in base units of a mint with `mint_decimals`, that `quote_amount` buys at `feed`'s price
+    /// at `now`, rounded down. Fails when the price breaks the staleness, confidence or slippage limits.
+    pub fn payout(&self, feed: &AccountInfo, mint_decimals: u8, now: i64) -> Result<u64> {
+        require_keys_eq!(feed.key(), self.feed, PriceError::PriceFeedMismatch);
+        let price = PythPrice::parse(&feed.try_borrow_data()?)?;
+        require!(price.price > 0, PriceError::NonPositivePrice);
+        require!(
+            now.saturating_sub(price.publish_time) <= self.max_staleness as i64,
+            PriceError::StalePrice
+        );
+        require!(
+            price.conf as u128 * TOTAL_BPS as u128 <= price.price as u128 * self.max_confidence_bps as u128,
+            PriceError::PriceTooUncertain
+        );
+
+        // tokens = quote / 10^QUOTE_DECIMALS / (price * 10^expo) * 10^mint_decimals
+        let scale = mint_decimals as i32 - QUOTE_DECIMALS as i32 - price.expo;
+        let pow = |exp: i32| 10u128.checked_pow(exp.unsigned_abs()).ok_or(PriceError::ConversionOverflow);
+        let (numerator, denominator) = if scale >= 0 {
+            (self.quote_amount as u128).checked_mul(pow(scale)?).map(|n| (n, price.price as u128))
+        } else {
+            (price.price as u128).checked_mul(pow(scale)?).map(|d| (self.quote_amount as u128, d))
+        }
+        .ok_or(PriceError::ConversionOverflow)?;
+        let tokens = u64::try_from(numerator / denominator).map_err(|_| PriceError::ConversionOverflow)?;
+
+        require!(tokens <= self.max_payout().ok_or(PriceError::ConversionOverflow)?, PriceError::SlippageExceeded);
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+/// Track record of one wallet across every PIVOX contract it has been party to. Only created and written
+/// by the program (as a PDA of the wallet), so the counters can be trusted by anyone reading them.
+/// Counters saturate rather than fail, so statistics can never block a payment.
+#[account]
+#[derive(InitSpace)]
+pub struct Profile {
+    pub owner: Pubkey,
+    pub contracts_completed: u64,
+    pub contracts_cancelled: u64,
+    /// Paid out of vaults this wallet funded as client.
+    pub volume_paid: u64,
+    /// Received from vaults as freelancer.
+    pub volume_earned: u64,
+    pub milestones_delivered: u64,
+    /// Milestones released before the contract's `project_start + project_duration`.
+    pub milestones_on_time: u64,
+    /// Disputes settled against this wallet as freelancer with a `DisputeLost` bond slash, which the client
+    /// and the freelancer both sign.
+    pub disputes_lost: u64,
+    pub bump: u8,
+}
+
+impl Profile {
+    /// Claims a freshly `init_if_needed` account for `owner`; a no-op on an existing profile.
+    pub fn claim(&mut self, owner: Pubkey, bump: u8) {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.bump = bump;
+        }
+    }
+
+    pub fn record_paid(&mut self, amount: u64) {
+        self.volume_paid = self.volume_paid.saturating_add(amount);
+    }
+
+    pub fn record_earned(&mut self, amount: u64) {
+        self.volume_earned = self.volume_earned.saturating_add(amount);
+    }
+
+    pub fn record_delivery(&mut self, on_time: bool) {
+        self.milestones_delivered = self.milestones_delivered.saturating_add(1);
+        if on_time {
+            self.milestones_on_time = self.milestones_on_time.saturating_add(1);
+        }
+    }
+
+    pub fn record_termination(&mut self, completed: bool) {
+        if completed {
+            self.contracts_completed = self.contracts_completed.saturating_add(1);
+        } else {
+            self.contracts_cancelled = self.contracts_cancelled.saturating_add(1);
+        }
+    }
+
+    pub fn record_dispute_lost(&mut self) {
+        self.disputes_lost = self.disputes_lost.saturating_add(1);
+    }
+
+    /// On-time delivery rate in basis points, or `None` before the first delivery.
+    pub fn on_time_rate_bps(&self) -> Option<u64> {
+        (self.milestones_delivered > 0)
+            .then(|| (self.milestones_on_time as u128 * 10_000 / self.milestones_delivered as u128) as u64)
+    }
+}
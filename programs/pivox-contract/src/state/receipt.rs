@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_INVOICE_LEN: usize = 32;
+
+/// Proof of one payment out of a contract's escrow, for invoicing. Receipts are PDAs of the contract's `id`
+/// and its running receipt number, so they can be listed without an index, also after a reassignment.
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentReceipt {
+    /// The contract's `id`: its address unless it has been reassigned.
+    pub contract: Pubkey,
+    /// Position in the contract's receipt sequence, which is also part of the receipt's seeds.
+    pub number: u64,
+    pub kind: ReceiptKind,
+    /// Set for milestone releases.
+    pub milestone_index: Option<u8>,
+    /// Who the payment is from: the client, or the escrow vault for refunds.
+    pub payer: Pubkey,
+    /// The freelancer for releases and terminations, even when a release is split between payees; the
+    /// client for refunds, including the parent freelancer for what a settled subcontract didn't spend.
+    pub payee: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub paid_at: i64,
+    /// Invoice number supplied with the payment; empty when there is none.
+    #[max_len(32)]
+    pub invoice: String,
+    /// Paid the receipt's rent and can close it to get it back.
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ReceiptKind {
+    MilestoneRelease,
+    /// The freelancer's share of the vault, withdrawn after termination.
+    Termination,
+    /// Escrow returned to the client.
+    Refund,
+}
+
+#[error_code(offset = 7300)]
+pub enum ReceiptError {
+    #[msg("Receipt account is not the contract's next receipt address")]
+    InvalidReceiptAccount,
+    #[msg("Invoice reference is longer than 32 bytes")]
+    InvoiceTooLong,
+    #[msg("Only the wallet that paid the receipt's rent can close it")]
+    Unauthorized,
+}
+
+/// A payment to put on a receipt; the contract, time, rent payer and bump are filled in when it is issued.
+pub struct ReceiptDetails {
+    pub kind: ReceiptKind,
+    pub milestone_index: Option<u8>,
+    pub payer: Pubkey,
+    /// The freelancer for releases and terminations, even when a release is split between payees; the
+    /// client for refunds.
+    pub payee: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub invoice: String,
+}
+
+pub fn validate_invoice(invoice: &str) -> Result<()> {
+    require!(invoice.len() <= MAX_INVOICE_LEN, ReceiptError::InvoiceTooLong);
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Milestone;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum TemplateAmount {
+    /// Share of whatever is left of the budget after fixed amounts, in whole percent.
+    Percent(u8),
+    Fixed(u64),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub struct TemplateMilestone {
+    #[max_len(100)]
+    pub description: String,
+    pub amount: TemplateAmount,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MilestoneTemplate {
+    pub creator: Pubkey,
+    #[max_len(32)]
+    pub name: String,
+    #[max_len(10)]
+    pub milestones: Vec<TemplateMilestone>,
+    pub bump: u8,
+}
+
+#[error_code(offset = 6700)]
+pub enum TemplateError {
+    #[msg("Template name must be 1 to 32 bytes")]
+    InvalidName,
+    #[msg("Template must have between 1 and 10 milestones")]
+    InvalidMilestoneCount,
+    #[msg("Milestone description is longer than 100 bytes")]
+    DescriptionTooLong,
+    #[msg("Percentages must each be 1-100 and add up to 100")]
+    InvalidPercentages,
+    #[msg("Budget does not cover the template's fixed amounts")]
+    BudgetMismatch,
+    #[msg("Template would produce a milestone with no payment")]
+    ZeroAmount,
+    #[msg("No milestone template was passed")]
+    MissingTemplate,
+}
+
+pub const MAX_TEMPLATE_NAME_LEN: usize = 32;
+pub const MAX_TEMPLATE_MILESTONES: usize = 10;
+pub const MAX_DESCRIPTION_LEN: usize = 100;
+
+impl MilestoneTemplate {
+    pub fn validate(name: &str, milestones: &[TemplateMilestone]) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_TEMPLATE_NAME_LEN,
+            TemplateError::InvalidName
+        );
+        require!(
+            !milestones.is_empty() && milestones.len() <= MAX_TEMPLATE_MILESTONES,
+            TemplateError::InvalidMilestoneCount
+        );
+
+        let mut percent_total: u16 = 0;
+        let mut has_percent = false;
+        for m in milestones {
+            require!(m.description.len() <= MAX_DESCRIPTION_LEN, TemplateError::DescriptionTooLong);
+            match m.amount {
+                TemplateAmount::Percent(p) => {
+                    require!((1..=100).contains(&p), TemplateError::InvalidPercentages);
+                    percent_total += p as u16;
+                    has_percent = true;
+                }
+                TemplateAmount::Fixed(amount) => require!(amount > 0, TemplateError::ZeroAmount),
+            }
+        }
+        require!(!has_percent || percent_total == 100, TemplateError::InvalidPercentages);
+        Ok(())
+    }
+
+    /// Builds a contract's milestones for `total_budget`. Fixed amounts are taken first and the rest is
+    /// split by percentage, with any rounding remainder going to the last percentage milestone.
+    pub fn instantiate(&self, total_budget: u64) -> Result<Vec<Milestone>> {
+        let fixed_total = self
+            .milestones
+            .iter()
+            .try_fold(0u64, |acc, m| match m.amount {
+                TemplateAmount::Fixed(amount) => acc.checked_add(amount),
+                TemplateAmount::Percent(_) => Some(acc),
+            })
+            .ok_or(TemplateError::BudgetMismatch)?;
+        let remainder = total_budget
+            .checked_sub(fixed_total)
+            .ok_or(TemplateError::BudgetMismatch)?;
+
+        let last_percent = self
+            .milestones
+            .iter()
+            .rposition(|m| matches!(m.amount, TemplateAmount::Percent(_)));
+        // Without percentage milestones there is nothing to absorb the remainder.
+        require!(last_percent.is_some() || remainder == 0, TemplateError::BudgetMismatch);
+
+        let mut allocated = 0u64;
+        let mut milestones = Vec::with_capacity(self.milestones.len());
+        for (index, m) in self.milestones.iter().enumerate() {
+            let amount = match m.amount {
+                TemplateAmount::Fixed(amount) => amount,
+                TemplateAmount::Percent(_) if Some(index) == last_percent => remainder - allocated,
+                TemplateAmount::Percent(p) => {
+                    let share = (remainder as u128 * p as u128 / 100) as u64;
+                    allocated += share;
+                    share
+                }
+            };
+            require!(amount > 0, TemplateError::ZeroAmount);
+
+            milestones.push(Milestone {
+                description: m.description.clone(),
+                amount,
+                freelancer_submitted: false,
+                client_approved: false,
+                freelancer_confirmed: false,
+                is_released: false,
+                depends_on: vec![],
+                subcontract: None,
+                payees: vec![],
+                pricing: None,
+                funded: false,
+                cancelled: false,
+            });
+        }
+        Ok(milestones)
+    }
+}
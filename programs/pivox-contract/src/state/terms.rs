@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::state::{Milestone, MilestoneOrdering, Payee, ReleasePolicy};
+
+#[error_code(offset = 7500)]
+pub enum TermsError {
+    #[msg("Client and freelancer shares must add up to 100")]
+    InvalidShares,
+    #[msg("A contract needs at least one milestone")]
+    NoMilestones,
+    #[msg("Milestone amount must be greater than zero")]
+    ZeroAmountMilestone,
+    #[msg("Milestone amounts add up to more than a u64 can hold")]
+    MilestoneTotalOverflow,
+    #[msg("Project start is in the past")]
+    StartInPast,
+    #[msg("New milestones can't already be funded, submitted, approved, confirmed, released or cancelled")]
+    MilestoneAlreadyStarted,
+    #[msg("Priced milestone needs a quote amount and a reference token amount")]
+    InvalidPricing,
+    #[msg("Priced milestone's amount doesn't cover its reference amount plus the slippage bound")]
+    PricingNotCovered,
+}
+
+/// What the freelancer signs a proposed contract with through `approve` or `approve_from_template`, apart
+/// from the milestones.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ContractTerms {
+    pub client_share: u8,
+    pub freelancer_share: u8,
+    pub initial_payment: u64,
+    pub project_start: i128,
+    pub project_duration: u64,
+    pub dispute_resolution: String,
+    pub ordering: MilestoneOrdering,
+    pub release_policy: ReleasePolicy,
+    /// Default release split; empty pays the freelancer.
+    pub payees: Vec<Payee>,
+    /// Performance bond the freelancer posts; 0 for none.
+    pub bond_amount: u64,
+    /// First deposit the client co-signs; 0 for none.
+    pub deposit: u64,
+}
+
+impl ContractTerms {
+    /// What `propose` binds the client to and `approve` has to match: a hash of these terms and
+    /// `milestones`. The first deposit is left out, since the client co-signs it anyway.
+    pub fn hash(&self, milestones: &[Milestone]) -> Result<[u8; 32]> {
+        let mut terms = Vec::new();
+        ContractTerms { deposit: 0, ..self.clone() }.serialize(&mut terms)?;
+        let mut schedule = Vec::new();
+        milestones.serialize(&mut schedule)?;
+        Ok(hashv(&[&terms, &schedule]).to_bytes())
+    }
+}
+
+/// Checks the terms a contract is signed with: shares that split the whole, a start that hasn't passed
+/// and a valid milestone schedule.
+pub fn validate_terms(
+    client_share: u8,
+    freelancer_share: u8,
+    project_start: i128,
+    milestones: &[Milestone],
+) -> Result<()> {
+    require!(client_share as u16 + freelancer_share as u16 == 100, TermsError::InvalidShares);
+    require!(project_start >= Clock::get()?.unix_timestamp as i128, TermsError::StartInPast);
+    require!(!milestones.is_empty(), TermsError::NoMilestones);
+    validate_new_milestones(0, milestones)
+}
+
+/// Checks milestones being added to a contract whose milestones already total `existing_total`: every
+/// amount is positive, the new total fits in a u64, no progress flag is set yet and a priced milestone's
+/// escrow covers its slippage bound.
+pub fn validate_new_milestones(existing_total: u64, milestones: &[Milestone]) -> Result<()> {
+    let mut total = existing_total;
+    for milestone in milestones {
+        require!(milestone.amount > 0, TermsError::ZeroAmountMilestone);
+        total = total.checked_add(milestone.amount).ok_or(TermsError::MilestoneTotalOverflow)?;
+        require!(
+            !(milestone.funded
+                || milestone.freelancer_submitted
+                || milestone.client_approved
+                || milestone.freelancer_confirmed
+                || milestone.is_released
+                || milestone.cancelled),
+            TermsError::MilestoneAlreadyStarted
+        );
+        if let Some(pricing) = milestone.pricing {
+            require!(pricing.quote_amount > 0 && pricing.reference_amount > 0, TermsError::InvalidPricing);
+            let covered = pricing.max_payout().is_some_and(|max| max <= milestone.amount);
+            require!(covered, TermsError::PricingNotCovered);
+        }
+    }
+    Ok(())
+}
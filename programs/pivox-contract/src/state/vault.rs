@@ -3,6 +3,8 @@ use anchor_lang::prelude::*;
 #[account]
 #[derive(InitSpace)]
 pub struct VaultAccount {
+    /// `LAYOUT_VERSION` this account was written with.
+    pub version: u8,
     pub client: Pubkey,
     pub freelancer: Pubkey,
     pub balance: u64,
@@ -12,4 +14,74 @@ pub struct VaultAccount {
     pub vault_bump: u8,
     #[max_len(10)]
     pub vault_status: String,
+    // Lifetime ledger; `balance` always equals
+    // deposited - released - refunded - written_off.
+    pub total_deposited: u64,
+    pub total_released: u64,
+    pub total_refunded: u64,
+    pub total_written_off: u64,
+    /// What each party has withdrawn after termination; see [`VaultAccount::entitlement`].
+    pub client_withdrawn: u64,
+    pub freelancer_withdrawn: u64,
+}
+
+#[error_code(offset = 6600)]
+pub enum LedgerError {
+    #[msg("Ledger counter overflow")]
+    Overflow,
+    #[msg("Vault balance is lower than the amount being paid out")]
+    InsufficientBalance,
+    #[msg("Only the client or the freelancer can reconcile the vault")]
+    Unauthorized,
+    #[msg("Ledger counter underflow")]
+    Underflow,
+}
+
+impl VaultAccount {
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.balance = self.balance.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        self.total_deposited = self.total_deposited.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn record_release(&mut self, amount: u64) -> Result<()> {
+        self.balance = self.balance.checked_sub(amount).ok_or(LedgerError::InsufficientBalance)?;
+        self.total_released = self.total_released.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn record_refund(&mut self, amount: u64) -> Result<()> {
+        self.balance = self.balance.checked_sub(amount).ok_or(LedgerError::InsufficientBalance)?;
+        self.total_refunded = self.total_refunded.checked_add(amount).ok_or(LedgerError::Overflow)?;
+        Ok(())
+    }
+
+    /// What is left of `party`'s share of the vault after termination: the vault's balance plus everything
+    /// already withdrawn is split by `client_share`, with the rounding remainder going to the freelancer.
+    pub fn entitlement(&self, party: Pubkey) -> u64 {
+        let pool = self.balance as u128 + self.client_withdrawn as u128 + self.freelancer_withdrawn as u128;
+        let client_total = (pool * self.client_share as u128 / 100) as u64;
+        if party == self.client {
+            client_total.saturating_sub(self.client_withdrawn)
+        } else if party == self.freelancer {
+            ((pool - client_total as u128) as u64).saturating_sub(self.freelancer_withdrawn)
+        } else {
+            0
+        }
+    }
+
+    /// Brings `balance` in line with what the vault ATA actually holds. Untracked tokens are booked as
+    /// deposits and missing tokens are written off. Returns `(surplus, deficit)`.
+    pub fn sync_to(&mut self, ata_amount: u64) -> Result<(u64, u64)> {
+        if ata_amount > self.balance {
+            let surplus = ata_amount - self.balance;
+            self.record_deposit(surplus)?;
+            Ok((surplus, 0))
+        } else {
+            let deficit = self.balance - ata_amount;
+            self.balance = ata_amount;
+            self.total_written_off = self.total_written_off.checked_add(deficit).ok_or(LedgerError::Overflow)?;
+            Ok((0, deficit))
+        }
+    }
 }
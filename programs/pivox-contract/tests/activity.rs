@@ -0,0 +1,157 @@
+mod common;
+
+use anchor_lang::prelude::Clock;
+use common::*;
+use pivox_client::{instructions, ActivityAction, ActivityLog, BonusSource, ContractAddresses};
+use pivox_contract::MAX_ACTIVITY_ENTRIES;
+
+fn activity(env: &TestEnv) -> ActivityLog {
+    env.account(&env.addresses.activity_log)
+}
+
+fn actions(log: &ActivityLog) -> Vec<ActivityAction> {
+    log.chronological().map(|e| e.action).collect()
+}
+
+#[test]
+fn the_milestone_lifecycle_is_logged_in_order() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    let log = activity(&env);
+    assert_eq!(log.contract, env.addresses.contract);
+    assert_eq!(log.total, 6);
+    assert_eq!(
+        actions(&log),
+        vec![
+            ActivityAction::Created,
+            ActivityAction::Deposited,
+            ActivityAction::MilestoneSubmitted,
+            ActivityAction::MilestoneApproved,
+            ActivityAction::MilestoneConfirmed,
+            ActivityAction::MilestoneReleased,
+        ]
+    );
+
+    let (client, freelancer) = (env.addresses.client, env.addresses.freelancer);
+    let entries: Vec<_> = log.chronological().map(|e| (e.actor, e.milestone_index, e.amount)).collect();
+    assert_eq!(entries[0], (freelancer, None, milestones_total()));
+    assert_eq!(entries[1], (client, None, milestones_total()));
+    assert_eq!(entries[2], (freelancer, Some(0), 0));
+    assert_eq!(entries[3], (client, Some(0), 0));
+    assert_eq!(entries[5], (client, Some(0), milestones()[0].amount));
+    assert!(log.chronological().all(|e| e.slot == env.svm.get_sysvar::<Clock>().slot));
+}
+
+#[test]
+fn both_termination_approvals_are_logged() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    let log = activity(&env);
+    let tail: Vec<_> = log.chronological().skip(2).map(|e| (e.action, e.actor, e.amount)).collect();
+    assert_eq!(
+        tail,
+        vec![
+            (ActivityAction::TerminationApproved, env.addresses.client, 0),
+            (ActivityAction::TerminationApproved, env.addresses.freelancer, 0),
+            (ActivityAction::Terminated, env.addresses.freelancer, milestones_total()),
+        ]
+    );
+}
+
+#[test]
+fn withdrawals_after_termination_are_logged() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    env.withdraw(Party::Freelancer).unwrap();
+    env.withdraw(Party::Client).unwrap();
+
+    let share = milestones()[0].amount / 2;
+    let log = activity(&env);
+    let tail: Vec<_> = log.chronological().skip(log.entries.len() - 2).map(|e| (e.action, e.actor, e.amount)).collect();
+    assert_eq!(
+        tail,
+        vec![
+            (ActivityAction::Withdrawn, env.addresses.freelancer, share),
+            (ActivityAction::Withdrawn, env.addresses.client, share),
+        ]
+    );
+}
+
+#[test]
+fn bonuses_and_reconciliations_are_logged() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    let ix = instructions::pay_bonus(&env.addresses, 2_000_000, BonusSource::Direct, "Shipped early");
+    env.exec(&[ix], Party::Client).unwrap();
+    env.transfer_to_vault(500_000).unwrap();
+    env.reconcile(Party::Freelancer).unwrap();
+    // Nothing moves when the ledger already matches, so nothing is logged.
+    env.reconcile(Party::Client).unwrap();
+
+    let log = activity(&env);
+    assert_eq!(log.total, 4);
+    let tail: Vec<_> = log.chronological().skip(2).map(|e| (e.action, e.actor, e.amount)).collect();
+    assert_eq!(
+        tail,
+        vec![
+            (ActivityAction::BonusPaid, env.addresses.client, 2_000_000),
+            (ActivityAction::Reconciled, env.addresses.freelancer, 500_000),
+        ]
+    );
+}
+
+#[test]
+fn a_full_log_overwrites_its_oldest_entries() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    let deposits = MAX_ACTIVITY_ENTRIES as u64 + 6;
+    for amount in 1..=deposits {
+        env.deposit(amount).unwrap();
+    }
+
+    let log = activity(&env);
+    assert_eq!(log.total, deposits + 1);
+    assert_eq!(log.entries.len(), MAX_ACTIVITY_ENTRIES);
+    // `Created` and the first six deposits have been overwritten.
+    let amounts: Vec<u64> = log.chronological().map(|e| e.amount).collect();
+    assert_eq!(amounts, (7..=deposits).collect::<Vec<_>>());
+    assert!(log.chronological().all(|e| e.action == ActivityAction::Deposited));
+}
+
+#[test]
+fn the_log_moves_with_a_reassigned_contract() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.submit(0, Party::Freelancer).unwrap();
+    let old = env.addresses;
+    let (client, outsider) = (old.client, env.pubkey(Party::Outsider));
+
+    let ix = instructions::reassign_party(&old, &client, &outsider, false, 3);
+    let signers = (env.keypair(Party::Outsider), env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    env.exec_with(&[ix], &[&signers.0, &signers.1, &signers.2]).unwrap();
+
+    assert!(env.svm.get_account(&old.activity_log).is_none_or(|a| a.data.is_empty()));
+    let new = ContractAddresses::new(&client, &outsider, &env.mint);
+    let log: ActivityLog = env.account(&new.activity_log);
+    assert_eq!(log.contract, new.contract);
+    assert_eq!(log.total, 4);
+    let last = log.chronological().last().unwrap();
+    assert_eq!((last.action, last.actor), (ActivityAction::Reassigned, outsider));
+
+    // The new freelancer's actions land in the moved log.
+    let mut env = env.with_outsider_as_freelancer();
+    env.approve_milestone(0, Party::Client).unwrap();
+    assert_eq!(activity(&env).total, 5);
+}
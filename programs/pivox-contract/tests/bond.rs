@@ -0,0 +1,237 @@
+mod common;
+
+use common::*;
+use pivox_client::{instructions, ActivityAction, ActivityLog, Bond, Profile, SlashReason};
+use pivox_contract::{BondError, ABANDONMENT_GRACE_PERIOD};
+
+const BOND: u64 = 10_000_000;
+
+/// A contract the client hasn't funded yet, where the freelancer posted `BOND` when signing.
+fn unfunded_bonded_env() -> TestEnv {
+    let mut env = TestEnv::new();
+    env.mint_to(Party::Freelancer, BOND);
+    let client = env.pubkey(Party::Client);
+    let freelancer = env.pubkey(Party::Freelancer);
+    let args = instructions::ApproveArgs { bond_amount: BOND, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer)
+        .unwrap();
+    env
+}
+
+/// A funded contract where the freelancer posted `BOND` when signing.
+fn bonded_env() -> TestEnv {
+    let mut env = unfunded_bonded_env();
+    env.deposit(milestones_total()).unwrap();
+    env
+}
+
+fn slash(env: &mut TestEnv, party: Party, amount: u64, reason: SlashReason) -> litesvm::types::TransactionResult {
+    let signer = env.pubkey(party);
+    env.exec(&[instructions::slash_bond(&env.addresses, &signer, amount, reason)], party)
+}
+
+fn return_bond(env: &mut TestEnv, party: Party) -> litesvm::types::TransactionResult {
+    let signer = env.pubkey(party);
+    env.exec(&[instructions::return_bond(&env.addresses, &signer)], party)
+}
+
+fn bond(env: &TestEnv) -> Bond {
+    env.account(&env.addresses.bond)
+}
+
+fn past_deadline(env: &mut TestEnv) {
+    env.warp_to(START_TIME + PROJECT_DURATION as i64 + 1);
+}
+
+#[test]
+fn signing_posts_the_bond_into_its_own_vault() {
+    let env = bonded_env();
+
+    assert_eq!(env.token_balance(&env.addresses.bond_ata), BOND);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 0);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total());
+    let bond = bond(&env);
+    assert_eq!(bond.posted, BOND);
+    assert_eq!(bond.freelancer, env.pubkey(Party::Freelancer));
+}
+
+#[test]
+fn unbonded_contracts_have_no_bond_account() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    assert!(env.svm.get_account(&env.addresses.bond).is_none());
+}
+
+#[test]
+fn bond_is_returned_after_termination() {
+    let mut env = bonded_env();
+    for index in 0..3 {
+        env.complete_milestone(index);
+        env.release(index, Party::Client).unwrap();
+    }
+    assert_error(return_bond(&mut env, Party::Freelancer), BondError::NotTerminated);
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+    return_bond(&mut env, Party::Freelancer).unwrap();
+
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones_total() + BOND);
+    assert_eq!(env.token_balance(&env.addresses.bond_ata), 0);
+    assert!(env.svm.get_account(&env.addresses.bond).is_none_or(|a| a.data.is_empty()));
+}
+
+#[test]
+fn missed_deadline_slashes_only_the_unreleased_share() {
+    let mut env = bonded_env();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    assert_error(slash(&mut env, Party::Client, 1, SlashReason::MissedDeadline), BondError::DeadlineNotPassed);
+
+    past_deadline(&mut env);
+    // Half of the schedule (milestone 0) was delivered, so half of the bond is at stake.
+    assert_error(
+        slash(&mut env, Party::Client, BOND / 2 + 1, SlashReason::MissedDeadline),
+        BondError::ExceedsSlashable,
+    );
+    assert_error(slash(&mut env, Party::Freelancer, 1, SlashReason::MissedDeadline), BondError::Unauthorized);
+    let client_before = env.token_balance(&env.addresses.client_ata);
+    slash(&mut env, Party::Client, BOND / 2, SlashReason::MissedDeadline).unwrap();
+
+    assert_eq!(env.token_balance(&env.addresses.client_ata), client_before + BOND / 2);
+    assert_eq!(bond(&env).slashed, BOND / 2);
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+    assert_error(slash(&mut env, Party::Client, 1, SlashReason::DisputeLost), BondError::ContractTerminated);
+    return_bond(&mut env, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount + BOND / 2);
+}
+
+#[test]
+fn submitted_milestones_are_not_slashed_for_the_deadline() {
+    let mut env = bonded_env();
+    env.complete_milestone(0);
+    env.submit(1, Party::Freelancer).unwrap();
+    past_deadline(&mut env);
+
+    // Milestone 0 waits on the client's release and milestone 1 on their approval; only milestone 2 counts.
+    let slashable = BOND * milestones()[2].amount / milestones_total();
+    assert_error(
+        slash(&mut env, Party::Client, slashable + 1, SlashReason::MissedDeadline),
+        BondError::ExceedsSlashable,
+    );
+    slash(&mut env, Party::Client, slashable, SlashReason::MissedDeadline).unwrap();
+
+    env.submit(2, Party::Freelancer).unwrap();
+    assert_error(slash(&mut env, Party::Client, 1, SlashReason::MissedDeadline), BondError::NothingOutstanding);
+}
+
+#[test]
+fn unfunded_milestones_are_not_slashed() {
+    let mut env = unfunded_bonded_env();
+    env.warp_to(START_TIME + PROJECT_DURATION as i64 + ABANDONMENT_GRACE_PERIOD + 1);
+
+    // The freelancer couldn't have started anything the client never paid for.
+    assert_error(slash(&mut env, Party::Client, 1, SlashReason::MissedDeadline), BondError::NothingOutstanding);
+    assert_error(slash(&mut env, Party::Client, 1, SlashReason::Abandoned), BondError::NothingOutstanding);
+
+    // Funding milestone 2 on its own puts only its share of the bond at stake.
+    env.fund_milestone(2).unwrap();
+    let slashable = BOND * milestones()[2].amount / milestones_total();
+    assert_error(
+        slash(&mut env, Party::Client, slashable + 1, SlashReason::MissedDeadline),
+        BondError::ExceedsSlashable,
+    );
+    slash(&mut env, Party::Client, slashable, SlashReason::MissedDeadline).unwrap();
+}
+
+#[test]
+fn abandonment_forfeits_the_whole_bond() {
+    let mut env = bonded_env();
+    past_deadline(&mut env);
+    assert_error(slash(&mut env, Party::Client, BOND, SlashReason::Abandoned), BondError::NotAbandoned);
+
+    env.warp_by(ABANDONMENT_GRACE_PERIOD);
+    slash(&mut env, Party::Client, BOND, SlashReason::Abandoned).unwrap();
+    assert_eq!(bond(&env).remaining(), 0);
+}
+
+#[test]
+fn outstanding_submissions_are_not_abandonment() {
+    let mut env = bonded_env();
+    env.submit(1, Party::Freelancer).unwrap();
+    env.warp_to(START_TIME + PROJECT_DURATION as i64 + ABANDONMENT_GRACE_PERIOD + 1);
+
+    assert_error(slash(&mut env, Party::Client, BOND, SlashReason::Abandoned), BondError::NotAbandoned);
+}
+
+/// A DisputeLost slash signed by the client with the freelancer co-signing.
+fn dispute_lost(env: &mut TestEnv, amount: u64) -> litesvm::types::TransactionResult {
+    let ix = instructions::slash_bond(&env.addresses, &env.addresses.client, amount, SlashReason::DisputeLost);
+    let (client, freelancer) = (env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    env.exec_with(&[ix], &[&client, &freelancer])
+}
+
+#[test]
+fn a_lost_dispute_needs_both_parties() {
+    let mut env = bonded_env();
+    assert_error(slash(&mut env, Party::Freelancer, BOND, SlashReason::DisputeLost), BondError::Unauthorized);
+    let mut ix = instructions::slash_bond(&env.addresses, &env.addresses.client, BOND, SlashReason::DisputeLost);
+    ix.accounts[1].is_signer = false;
+    assert_error(env.exec(&[ix], Party::Client), BondError::Unauthorized);
+
+    dispute_lost(&mut env, 3_000_000).unwrap();
+    assert_error(dispute_lost(&mut env, BOND), BondError::ExceedsSlashable);
+
+    let profile: Profile = env.account(&env.addresses.freelancer_profile);
+    assert_eq!(profile.disputes_lost, 1);
+    assert_eq!(bond(&env).remaining(), BOND - 3_000_000);
+}
+
+#[test]
+fn posting_a_bond_needs_the_bond_accounts() {
+    let mut env = TestEnv::new();
+    env.mint_to(Party::Freelancer, BOND);
+    let client = env.pubkey(Party::Client);
+    let freelancer = env.pubkey(Party::Freelancer);
+    let args = instructions::ApproveArgs { bond_amount: BOND, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+
+    let mut ix = instructions::approve(&env.addresses, args);
+    for meta in ix.accounts.iter_mut() {
+        if meta.pubkey == env.addresses.bond || meta.pubkey == env.addresses.bond_ata {
+            meta.pubkey = pivox_client::PROGRAM_ID;
+            meta.is_writable = false;
+        }
+    }
+    assert_error(env.exec(&[ix], Party::Freelancer), BondError::MissingBondAccounts);
+}
+
+#[test]
+fn bond_movements_are_logged() {
+    let mut env = bonded_env();
+    past_deadline(&mut env);
+    slash(&mut env, Party::Client, 1_000_000, SlashReason::MissedDeadline).unwrap();
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+    return_bond(&mut env, Party::Client).unwrap();
+
+    let log: ActivityLog = env.account(&env.addresses.activity_log);
+    let bond_entries: Vec<_> = log
+        .chronological()
+        .filter(|e| {
+            matches!(e.action, ActivityAction::BondPosted | ActivityAction::BondSlashed | ActivityAction::BondReturned)
+        })
+        .map(|e| (e.action, e.actor, e.amount))
+        .collect();
+    let (client, freelancer) = (env.addresses.client, env.addresses.freelancer);
+    assert_eq!(
+        bond_entries,
+        vec![
+            (ActivityAction::BondPosted, freelancer, BOND),
+            (ActivityAction::BondSlashed, client, 1_000_000),
+            (ActivityAction::BondReturned, client, BOND - 1_000_000),
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::*;
+use pivox_client::{decode_events, instructions, BonusPaid, BonusSource, PayoutKind};
+use pivox_contract::BonusError;
+
+fn pay_bonus(env: &mut TestEnv, party: Party, amount: u64, source: BonusSource, memo: &str) -> litesvm::types::TransactionResult {
+    let mut ix = instructions::pay_bonus(&env.addresses, amount, source, memo);
+    ix.accounts[0].pubkey = env.pubkey(party);
+    env.exec(&[ix], party)
+}
+
+#[test]
+fn client_pays_a_bonus_from_their_wallet() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let client_before = env.token_balance(&env.addresses.client_ata);
+
+    let meta = pay_bonus(&mut env, Party::Client, 2_000_000, BonusSource::Direct, "Shipped a week early").unwrap();
+
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 2_000_000);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), client_before - 2_000_000);
+    // Escrow is untouched.
+    assert_eq!(env.vault().balance, milestones_total());
+
+    let events: Vec<BonusPaid> = decode_events(&meta.logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amount, 2_000_000);
+    assert_eq!(events[0].source, BonusSource::Direct);
+    assert_eq!(events[0].memo, "Shipped a week early");
+    assert_eq!(events[0].freelancer, env.pubkey(Party::Freelancer));
+}
+
+#[test]
+fn bonuses_and_releases_share_the_payout_history() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    pay_bonus(&mut env, Party::Client, 1_000, BonusSource::Direct, "Thanks!").unwrap();
+
+    let payouts = env.contract().payouts;
+    assert_eq!(payouts.len(), 2);
+    assert_eq!(payouts[0].kind, PayoutKind::Milestone { index: 0 });
+    assert_eq!(payouts[0].amount, milestones()[0].amount);
+    assert_eq!(payouts[1].kind, PayoutKind::Bonus);
+    assert_eq!(payouts[1].memo, "Thanks!");
+    assert_eq!(payouts[1].paid_at, env.now());
+}
+
+#[test]
+fn escrow_bonuses_only_spend_the_surplus() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    assert_error(
+        pay_bonus(&mut env, Party::Client, 1, BonusSource::EscrowSurplus, ""),
+        BonusError::InsufficientSurplus,
+    );
+
+    env.transfer_to_vault(3_000_000).unwrap();
+    assert_error(
+        pay_bonus(&mut env, Party::Client, 3_000_001, BonusSource::EscrowSurplus, ""),
+        BonusError::InsufficientSurplus,
+    );
+    pay_bonus(&mut env, Party::Client, 3_000_000, BonusSource::EscrowSurplus, "From the leftovers").unwrap();
+
+    let vault = env.vault();
+    assert_eq!(vault.balance, milestones_total());
+    assert_eq!(vault.total_released, 3_000_000);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 3_000_000);
+
+    // The milestones are still fully covered.
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+}
+
+#[test]
+fn only_the_client_pays_bonuses() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(pay_bonus(&mut env, Party::Freelancer, 1, BonusSource::Direct, ""), BonusError::Unauthorized);
+    assert_error(pay_bonus(&mut env, Party::Client, 0, BonusSource::Direct, ""), BonusError::ZeroAmount);
+    assert_error(
+        pay_bonus(&mut env, Party::Client, 1, BonusSource::Direct, &"x".repeat(65)),
+        BonusError::MemoTooLong,
+    );
+}
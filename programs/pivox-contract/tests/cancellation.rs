@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use pivox_client::{instructions, receipt_address, MilestoneOrdering, PaymentReceipt, ReceiptKind};
+use pivox_contract::{ContractError, MilestoneError, VaultDepositError};
+
+#[test]
+fn cancelling_a_milestone_refunds_its_escrow_and_the_rest_carries_on() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let dropped = milestones()[1].amount;
+
+    env.cancel_milestone(1).unwrap();
+
+    let milestone = env.milestone(1);
+    assert!(milestone.cancelled && !milestone.is_released);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - milestones_total() + dropped);
+    let contract = env.contract();
+    assert_eq!((contract.milestones_total, contract.unreleased), (milestones_total() - dropped, milestones_total() - dropped));
+    assert_eq!(env.vault().total_refunded, dropped);
+    let receipt: PaymentReceipt = env.account(&receipt_address(&env.addresses.contract, 0).0);
+    assert_eq!((receipt.kind, receipt.milestone_index, receipt.amount), (ReceiptKind::Refund, Some(1), dropped));
+
+    // The vault already holds everything the remaining milestones need.
+    assert_error(env.deposit(1), VaultDepositError::InsufficientFunds);
+    for index in [0, 2] {
+        env.complete_milestone(index);
+        env.release(index, Party::Client).unwrap();
+    }
+    assert!(env.contract().is_complete().unwrap());
+    assert_eq!(env.vault().balance, 0);
+}
+
+#[test]
+fn cancelling_needs_both_signatures() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    let mut ix = instructions::cancel_milestone(&env.addresses, 0, 0);
+    ix.accounts[1].is_signer = false;
+    assert_fails(env.exec(&[ix], Party::Client));
+    assert!(!env.milestone(0).cancelled);
+}
+
+#[test]
+fn a_cancelled_milestone_is_never_worked_on_or_paid() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.submit(0, Party::Freelancer).unwrap();
+    env.cancel_milestone(0).unwrap();
+    assert_eq!(env.contract().submitted_count, 0);
+
+    assert_error(env.approve_milestone(0, Party::Client), MilestoneError::Cancelled);
+    assert_error(env.release(0, Party::Client), ContractError::MilestoneError);
+    assert_error(env.cancel_milestone(0), MilestoneError::Cancelled);
+
+    env.complete_milestone(1);
+    env.release(1, Party::Client).unwrap();
+    assert_error(env.cancel_milestone(1), ContractError::AlreadyReleased);
+}
+
+#[test]
+fn only_funding_the_milestone_holds_is_refunded() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    env.fund_milestone(2).unwrap();
+    env.deposit(40_000_000).unwrap();
+
+    // Untagged deposits only cover part of milestones 0 and 1, so none of them is milestone 1's.
+    env.cancel_milestone(1).unwrap();
+    assert_eq!(env.vault().total_refunded, 0);
+
+    env.cancel_milestone(2).unwrap();
+    assert_eq!(env.vault().total_refunded, milestones()[2].amount);
+    assert_eq!(env.contract().unsubmitted_funding, 0);
+    assert_eq!(env.contract().unsubmitted, milestones()[0].amount);
+
+    // What is left exactly covers milestone 0 now.
+    env.deposit(milestones()[0].amount - 40_000_000).unwrap();
+    env.complete_milestone(0);
+}
+
+#[test]
+fn a_cancelled_milestone_no_longer_holds_up_the_next() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let args = instructions::ApproveArgs { ordering: MilestoneOrdering::Sequential, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer).unwrap();
+    env.deposit(milestones_total()).unwrap();
+
+    assert_error(env.submit(1, Party::Freelancer), MilestoneError::OutOfOrder);
+    env.cancel_milestone(0).unwrap();
+    env.complete_milestone(1);
+}
@@ -0,0 +1,393 @@
+//! Shared LiteSVM harness for the program tests.
+//!
+//! The tests load the compiled program from `target/deploy/pivox_contract.so`, so run `anchor build`
+//! (or `cargo build-sbf`) before `cargo test`. Nothing talks to a network.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use anchor_lang::{solana_program::program_pack::Pack, AccountDeserialize};
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use litesvm::{types::TransactionResult, LiteSVM};
+use litesvm_token::{CreateAssociatedTokenAccount, CreateMint, MintTo};
+use pivox_client::{
+    instructions, ContractAddresses, Contract, Milestone, MilestoneAccount, MilestoneOrdering, ReleasePolicy, VaultAccount,
+};
+use solana_sdk::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+pub const CLIENT_FUNDS: u64 = 1_000_000_000;
+pub const PROJECT_DURATION: u64 = 60 * 60 * 24 * 30;
+pub const START_TIME: i64 = 1_767_225_600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Party {
+    Client,
+    Freelancer,
+    Outsider,
+}
+
+pub struct TestEnv {
+    pub svm: LiteSVM,
+    pub client: Keypair,
+    pub freelancer: Keypair,
+    pub outsider: Keypair,
+    pub mint: Pubkey,
+    pub addresses: ContractAddresses,
+}
+
+pub fn milestone(description: &str, amount: u64) -> Milestone {
+    Milestone {
+        description: description.to_string(),
+        amount,
+        freelancer_submitted: false,
+        client_approved: false,
+        freelancer_confirmed: false,
+        is_released: false,
+        depends_on: vec![],
+        subcontract: None,
+        payees: vec![],
+        pricing: None,
+        funded: false,
+        cancelled: false,
+    }
+}
+
+pub fn milestones() -> Vec<Milestone> {
+    vec![
+        milestone("Design Phase", 50_000_000),
+        milestone("Development Phase", 30_000_000),
+        milestone("Testing Phase", 20_000_000),
+    ]
+}
+
+pub fn milestones_total() -> u64 {
+    milestones().iter().map(|m| m.amount).sum()
+}
+
+pub fn approve_args(milestones: Vec<Milestone>) -> instructions::ApproveArgs {
+    instructions::ApproveArgs {
+        client_share: 50,
+        freelancer_share: 50,
+        initial_payment: 0,
+        project_start: START_TIME as i128,
+        project_duration: PROJECT_DURATION,
+        dispute_resolution: "Dispute Clause".to_string(),
+        ordering: MilestoneOrdering::Any,
+        release_policy: ReleasePolicy::ClientApproval,
+        payees: vec![],
+        bond_amount: 0,
+        deposit: 0,
+        milestones,
+    }
+}
+
+/// The client proposing `args` to `freelancer`, for the freelancer to accept with exactly those terms.
+pub fn proposal(client: &Pubkey, freelancer: &Pubkey, args: &instructions::ApproveArgs) -> Instruction {
+    instructions::propose(client, freelancer, args.terms_hash().unwrap())
+}
+
+fn program_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy/pivox_contract.so")
+}
+
+impl TestEnv {
+    /// A fresh VM with the program loaded, a 6-decimal mint, funded wallets and ATAs for every party.
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let path = program_path();
+        svm.add_program_from_file(pivox_client::PROGRAM_ID, &path)
+            .unwrap_or_else(|e| panic!("loading {} failed ({e}); run `anchor build` first", path.display()));
+
+        let client = Keypair::new();
+        let freelancer = Keypair::new();
+        let outsider = Keypair::new();
+        for kp in [&client, &freelancer, &outsider] {
+            svm.airdrop(&kp.pubkey(), 10_000_000_000).unwrap();
+        }
+
+        let mint = CreateMint::new(&mut svm, &client)
+            .authority(&client.pubkey())
+            .decimals(6)
+            .send()
+            .unwrap();
+        for owner in [&client, &freelancer, &outsider] {
+            CreateAssociatedTokenAccount::new(&mut svm, &client, &mint)
+                .owner(&owner.pubkey())
+                .send()
+                .unwrap();
+        }
+
+        let addresses = ContractAddresses::new(&client.pubkey(), &freelancer.pubkey(), &mint);
+        MintTo::new(&mut svm, &client, &mint, &addresses.client_ata, CLIENT_FUNDS)
+            .send()
+            .unwrap();
+
+        let mut env = Self { svm, client, freelancer, outsider, mint, addresses };
+        env.warp_to(START_TIME);
+        env
+    }
+
+    /// The same VM with the outsider and freelancer swapped, for a second contract with the same client or
+    /// for the contract after it was reassigned to the outsider.
+    pub fn with_outsider_as_freelancer(mut self) -> Self {
+        std::mem::swap(&mut self.freelancer, &mut self.outsider);
+        self.addresses = ContractAddresses::new(&self.client.pubkey(), &self.freelancer.pubkey(), &self.mint);
+        if self.svm.get_account(&self.addresses.contract).is_some_and(|a| !a.data.is_empty()) {
+            // A reassigned contract keeps the id its milestone accounts were derived from.
+            self.addresses = self.addresses.with_contract_id(self.contract().id);
+        }
+        self
+    }
+
+    pub fn keypair(&self, party: Party) -> Keypair {
+        match party {
+            Party::Client => self.client.insecure_clone(),
+            Party::Freelancer => self.freelancer.insecure_clone(),
+            Party::Outsider => self.outsider.insecure_clone(),
+        }
+    }
+
+    pub fn pubkey(&self, party: Party) -> Pubkey {
+        self.keypair(party).pubkey()
+    }
+
+    pub fn outsider_ata(&self) -> Pubkey {
+        get_associated_token_address(&self.outsider.pubkey(), &self.mint)
+    }
+
+    /// Mints `amount` of the contract mint to `party`'s ATA; the client is the mint authority.
+    pub fn mint_to(&mut self, party: Party, amount: u64) {
+        let client = self.client.insecure_clone();
+        let ata = get_associated_token_address(&self.pubkey(party), &self.mint);
+        MintTo::new(&mut self.svm, &client, &self.mint, &ata, amount).send().unwrap();
+    }
+
+    /// Sends `ixs` signed and paid for by `party`.
+    pub fn exec(&mut self, ixs: &[Instruction], party: Party) -> TransactionResult {
+        let signer = self.keypair(party);
+        self.exec_with(ixs, &[&signer])
+    }
+
+    /// Sends `ixs` with the first signer as fee payer. The blockhash is rotated afterwards so an identical
+    /// transaction can be replayed on purpose.
+    pub fn exec_with(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TransactionResult {
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&signers[0].pubkey()),
+            signers,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    pub fn create_vault_ata(&mut self) {
+        let client = self.client.insecure_clone();
+        CreateAssociatedTokenAccount::new(&mut self.svm, &client, &self.mint)
+            .owner(&self.addresses.vault_account)
+            .send()
+            .unwrap();
+    }
+
+    /// The two-step flow: the client proposes, the freelancer signs the terms, which opens the vault.
+    pub fn create_contract_with(&mut self, milestones: Vec<Milestone>) {
+        let client = self.pubkey(Party::Client);
+        let freelancer = self.pubkey(Party::Freelancer);
+        let args = approve_args(milestones);
+        self.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+        self.exec(&[instructions::approve(&self.addresses, args)], Party::Freelancer).unwrap();
+    }
+
+    pub fn create_contract(&mut self) {
+        self.create_contract_with(milestones());
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> TransactionResult {
+        self.deposit_from(Party::Client, amount)
+    }
+
+    pub fn deposit_from(&mut self, party: Party, amount: u64) -> TransactionResult {
+        let depositor = self.pubkey(party);
+        self.exec(&[instructions::deposit_funds(&self.addresses, &depositor, amount)], party)
+    }
+
+    /// Escrows milestone `index` on its own, with a deposit from the client tagged to it.
+    pub fn fund_milestone(&mut self, index: u64) -> TransactionResult {
+        let client = self.pubkey(Party::Client);
+        let amount = self.milestone(index).escrow().unwrap();
+        self.exec(&[instructions::fund_milestone(&self.addresses, &client, index, amount)], Party::Client)
+    }
+
+    pub fn create_funded_contract(&mut self) {
+        self.create_contract();
+        self.deposit(milestones_total()).unwrap();
+    }
+
+    /// Milestones that must be released before `index`; none when the milestone does not exist.
+    fn prerequisites(&self, index: u64) -> Vec<u8> {
+        self.find_milestone(index)
+            .map(|m| self.contract().prerequisites(index as u8, &m))
+            .unwrap_or_default()
+    }
+
+    pub fn submit(&mut self, index: u64, party: Party) -> TransactionResult {
+        let prerequisites = self.prerequisites(index);
+        let mut ix = instructions::freelancer_submit_milestone(&self.addresses, index, &prerequisites);
+        ix.accounts[0].pubkey = self.pubkey(party);
+        self.exec(&[ix], party)
+    }
+
+    pub fn approve_milestone(&mut self, index: u64, party: Party) -> TransactionResult {
+        let prerequisites = self.prerequisites(index);
+        let mut ix = instructions::client_approve_milestone(&self.addresses, index, &prerequisites);
+        ix.accounts[0].pubkey = self.pubkey(party);
+        self.exec(&[ix], party)
+    }
+
+    pub fn confirm(&mut self, index: u64, party: Party) -> TransactionResult {
+        let mut ix = instructions::freelancer_confirm_milestone(&self.addresses, index);
+        ix.accounts[0].pubkey = self.pubkey(party);
+        self.exec(&[ix], party)
+    }
+
+    pub fn release(&mut self, index: u64, party: Party) -> TransactionResult {
+        let signer = self.pubkey(party);
+        let contract = self.contract();
+        let milestone = self.find_milestone(index);
+        let payees = milestone.as_ref().map(|m| contract.payees_for(m).to_vec()).unwrap_or_default();
+        let price_feed = milestone.and_then(|m| m.pricing).map(|pricing| pricing.feed);
+        let receipt_number = contract.receipt_count;
+        let ix = instructions::release_milestone_payment(&self.addresses, &signer, index, &payees, price_feed, receipt_number, "");
+        self.exec(&[ix], party)
+    }
+
+    pub fn terminate(&mut self, party: Party) -> TransactionResult {
+        let signer = self.pubkey(party);
+        let receipt_number = self.contract().receipt_count;
+        self.exec(&[instructions::complete_or_cancel_contract(&self.addresses, &signer, receipt_number, "")], party)
+    }
+
+    pub fn withdraw(&mut self, party: Party) -> TransactionResult {
+        let signer = self.pubkey(party);
+        let receipt_number = self.contract().receipt_count;
+        self.exec(&[instructions::withdraw_funds(&self.addresses, &signer, receipt_number)], party)
+    }
+
+    /// Both parties cancel milestone `index`, with the client paying.
+    pub fn cancel_milestone(&mut self, index: u64) -> TransactionResult {
+        let receipt_number = self.contract().receipt_count;
+        let ix = instructions::cancel_milestone(&self.addresses, index, receipt_number);
+        let (client, freelancer) = (self.keypair(Party::Client), self.keypair(Party::Freelancer));
+        self.exec_with(&[ix], &[&client, &freelancer])
+    }
+
+    pub fn reconcile(&mut self, party: Party) -> TransactionResult {
+        let signer = self.pubkey(party);
+        let receipt_number = self.contract().receipt_count;
+        self.exec(&[instructions::reconcile(&self.addresses, &signer, receipt_number)], party)
+    }
+
+    /// Sends tokens from the client straight to the vault ATA, bypassing `deposit_funds`.
+    pub fn transfer_to_vault(&mut self, amount: u64) -> TransactionResult {
+        let client = self.pubkey(Party::Client);
+        let ix = spl_token::instruction::transfer(
+            &spl_token::ID,
+            &self.addresses.client_ata,
+            &self.addresses.vault_ata,
+            &client,
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.exec(&[ix], Party::Client)
+    }
+
+    /// Drives milestone `index` through submit, approve and confirm.
+    pub fn complete_milestone(&mut self, index: u64) {
+        self.submit(index, Party::Freelancer).unwrap();
+        self.approve_milestone(index, Party::Client).unwrap();
+        self.confirm(index, Party::Freelancer).unwrap();
+    }
+
+    pub fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).expect("account exists");
+        T::try_deserialize(&mut account.data.as_slice()).expect("account decodes")
+    }
+
+    pub fn contract(&self) -> Contract {
+        self.account(&self.addresses.contract)
+    }
+
+    pub fn milestone(&self, index: u64) -> MilestoneAccount {
+        self.account(&self.addresses.milestone(index as u8))
+    }
+
+    /// Every milestone of the contract, in index order.
+    pub fn all_milestones(&self) -> Vec<Milestone> {
+        (0..self.contract().milestone_count as u64).map(|i| self.milestone(i).milestone).collect()
+    }
+
+    pub fn find_milestone(&self, index: u64) -> Option<MilestoneAccount> {
+        let account = self.svm.get_account(&self.addresses.milestone(index as u8))?;
+        (!account.data.is_empty()).then(|| self.milestone(index))
+    }
+
+    pub fn vault(&self) -> VaultAccount {
+        self.account(&self.addresses.vault_account)
+    }
+
+    pub fn token_balance(&self, ata: &Pubkey) -> u64 {
+        match self.svm.get_account(ata) {
+            Some(account) if !account.data.is_empty() => spl_token::state::Account::unpack(&account.data).unwrap().amount,
+            _ => 0,
+        }
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar::<Clock>(&clock);
+    }
+
+    pub fn warp_by(&mut self, seconds: i64) {
+        let now = self.now();
+        self.warp_to(now + seconds);
+    }
+}
+
+/// Asserts the transaction failed with the given PIVOX (or anchor) error code.
+pub fn assert_error(result: TransactionResult, expected: impl Into<u32>) {
+    let expected = expected.into();
+    match result {
+        Ok(_) => panic!("expected error {expected}, transaction succeeded"),
+        Err(failed) => match failed.err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => assert_eq!(
+                code,
+                expected,
+                "expected {:?}, got {:?}\n{}",
+                pivox_client::decode_error(expected).map(|e| e.to_string()),
+                pivox_client::decode_error(code).map(|e| e.to_string()),
+                failed.meta.logs.join("\n")
+            ),
+            other => panic!("expected custom error {expected}, got {other:?}\n{}", failed.meta.logs.join("\n")),
+        },
+    }
+}
+
+/// Asserts the transaction failed, for failures that come from the runtime rather than the program.
+pub fn assert_fails(result: TransactionResult) {
+    assert!(result.is_err(), "transaction should have failed");
+}
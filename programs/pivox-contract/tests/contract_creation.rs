@@ -0,0 +1,363 @@
+mod common;
+
+use common::*;
+use litesvm::types::TransactionResult;
+use pivox_client::{
+    instructions, offer, ActivityAction, ActivityLog, Milestone, MilestoneApproval, MilestoneOrdering, OfferTerms,
+    ReleasePolicy,
+};
+use pivox_contract::{
+    instructions::milestone_signature::ErrorCode as SignatureError, OfferError, TermsError, VaultDepositError,
+};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+#[test]
+fn propose_records_the_client_approval() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+
+    let args = approve_args(milestones());
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+
+    let approval: MilestoneApproval = env.account(&env.addresses.milestone_approval);
+    assert_eq!(approval.approved_by, vec![client]);
+    assert_eq!(approval.approvals, 1);
+    assert_eq!(approval.threshold, 2);
+    assert!(!approval.is_signed);
+    assert_eq!(approval.terms_hash, args.terms_hash().unwrap());
+}
+
+#[test]
+fn a_contract_cannot_be_proposed_twice() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let ix = proposal(&client, &freelancer, &approve_args(milestones()));
+
+    env.exec(std::slice::from_ref(&ix), Party::Client).unwrap();
+    assert_fails(env.exec(&[ix], Party::Client));
+}
+
+#[test]
+fn propose_requires_the_client_signature() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let mut ix = proposal(&client, &freelancer, &approve_args(milestones()));
+    ix.accounts.iter_mut().filter(|meta| meta.pubkey == client).for_each(|meta| meta.is_signer = false);
+    assert_fails(env.exec(&[ix], Party::Freelancer));
+}
+
+#[test]
+fn approve_creates_contract_and_vault() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    let contract = env.contract();
+    assert_eq!(contract.client, env.pubkey(Party::Client));
+    assert_eq!(contract.freelancer, env.pubkey(Party::Freelancer));
+    assert_eq!(contract.status, "active");
+    assert_eq!(contract.milestone_count, 3);
+    assert_eq!(contract.milestones_total, milestones_total());
+    let descriptions: Vec<_> = env.all_milestones().into_iter().map(|m| m.description).collect();
+    assert_eq!(descriptions, ["Design Phase", "Development Phase", "Testing Phase"]);
+    assert_eq!(contract.contract_bump, env.addresses.contract_bump);
+
+    let vault = env.vault();
+    assert_eq!(vault.balance, 0);
+    assert_eq!(vault.vault_bump, env.addresses.vault_bump);
+    assert_eq!(vault.vault_status, "active");
+
+    let approval: MilestoneApproval = env.account(&env.addresses.milestone_approval);
+    assert!(approval.is_signed);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), 0);
+}
+
+#[test]
+fn approve_cannot_be_repeated() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    let ix = instructions::approve(&env.addresses, approve_args(milestones()));
+    assert_fails(env.exec(&[ix], Party::Freelancer));
+}
+
+/// Opens the milestone approval and has the freelancer sign `args`.
+fn approve_with(env: &mut TestEnv, args: instructions::ApproveArgs) -> TransactionResult {
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer)
+}
+
+#[test]
+fn approve_must_match_the_proposed_terms() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let proposed = approve_args(milestones());
+    env.exec(&[proposal(&client, &freelancer, &proposed)], Party::Client).unwrap();
+
+    let shares = instructions::ApproveArgs { client_share: 10, freelancer_share: 90, ..proposed.clone() };
+    let mut schedule = proposed.clone();
+    schedule.milestones[0].amount += 1;
+    let policy = instructions::ApproveArgs { release_policy: ReleasePolicy::FreelancerOnly, ..proposed.clone() };
+    for args in [shares, schedule, policy] {
+        let ix = instructions::approve(&env.addresses, args);
+        assert_error(env.exec(&[ix], Party::Freelancer), SignatureError::TermsMismatch);
+    }
+
+    env.exec(&[instructions::approve(&env.addresses, proposed)], Party::Freelancer).unwrap();
+    assert!(env.account::<MilestoneApproval>(&env.addresses.milestone_approval).is_signed);
+}
+
+/// The freelancer's approval with a first deposit, co-signed by the client.
+fn approve_with_deposit(env: &mut TestEnv, deposit: u64) -> TransactionResult {
+    let (client, freelancer) = (env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    let args = instructions::ApproveArgs { deposit, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client.pubkey(), &freelancer.pubkey(), &args)], Party::Client).unwrap();
+    env.exec_with(&[instructions::approve(&env.addresses, args)], &[&freelancer, &client])
+}
+
+#[test]
+fn approve_can_make_the_first_deposit() {
+    let mut env = TestEnv::new();
+    approve_with_deposit(&mut env, 40_000_000).unwrap();
+
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), 40_000_000);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - 40_000_000);
+    let vault = env.vault();
+    assert_eq!((vault.balance, vault.total_deposited), (40_000_000, 40_000_000));
+
+    let log: ActivityLog = env.account(&env.addresses.activity_log);
+    let entries: Vec<_> = log.chronological().map(|e| (e.action, e.actor, e.amount)).collect();
+    assert_eq!(entries[1], (ActivityAction::Deposited, env.addresses.client, 40_000_000));
+}
+
+#[test]
+fn the_first_deposit_needs_the_client_signature() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let args = instructions::ApproveArgs { deposit: 40_000_000, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    let mut ix = instructions::approve(&env.addresses, args);
+    ix.accounts.iter_mut().filter(|meta| meta.pubkey == client).for_each(|meta| meta.is_signer = false);
+    assert_error(env.exec(&[ix], Party::Freelancer), SignatureError::ClientSignatureRequired);
+}
+
+#[test]
+fn the_first_deposit_cannot_exceed_the_milestones() {
+    let mut env = TestEnv::new();
+    assert_error(approve_with_deposit(&mut env, milestones_total() + 1), VaultDepositError::Overflow);
+}
+
+#[test]
+fn approve_rejects_shares_that_do_not_add_up_to_100() {
+    let mut env = TestEnv::new();
+    let args = instructions::ApproveArgs { client_share: 60, ..approve_args(milestones()) };
+    assert_error(approve_with(&mut env, args), TermsError::InvalidShares);
+}
+
+#[test]
+fn approve_rejects_a_start_in_the_past() {
+    let mut env = TestEnv::new();
+    let args = instructions::ApproveArgs { project_start: START_TIME as i128 - 1, ..approve_args(milestones()) };
+    assert_error(approve_with(&mut env, args), TermsError::StartInPast);
+}
+
+#[test]
+fn approve_rejects_an_empty_schedule() {
+    let mut env = TestEnv::new();
+    assert_error(approve_with(&mut env, approve_args(vec![])), TermsError::NoMilestones);
+}
+
+#[test]
+fn approve_rejects_zero_amount_milestones() {
+    let mut env = TestEnv::new();
+    let plan = vec![milestone("Design", 10_000_000), milestone("Review", 0)];
+    assert_error(approve_with(&mut env, approve_args(plan)), TermsError::ZeroAmountMilestone);
+}
+
+#[test]
+fn approve_rejects_milestone_totals_that_overflow() {
+    let mut env = TestEnv::new();
+    let plan = vec![milestone("Design", u64::MAX), milestone("Build", 1)];
+    assert_error(approve_with(&mut env, approve_args(plan)), TermsError::MilestoneTotalOverflow);
+}
+
+#[test]
+fn approve_rejects_milestones_passed_in_as_started() {
+    let started: [fn(&mut Milestone); 5] = [
+        |m| m.funded = true,
+        |m| m.freelancer_submitted = true,
+        |m| m.client_approved = true,
+        |m| m.freelancer_confirmed = true,
+        |m| m.is_released = true,
+    ];
+    for start in started {
+        let mut env = TestEnv::new();
+        let mut plan = milestones();
+        start(&mut plan[1]);
+        assert_error(approve_with(&mut env, approve_args(plan)), TermsError::MilestoneAlreadyStarted);
+    }
+}
+
+#[test]
+fn approve_requires_an_open_milestone_approval() {
+    let mut env = TestEnv::new();
+    let ix = instructions::approve(&env.addresses, approve_args(milestones()));
+    assert_fails(env.exec(&[ix], Party::Freelancer));
+}
+
+fn offer_terms(env: &TestEnv, expires_at: i64, nonce: u64) -> OfferTerms {
+    OfferTerms {
+        client: env.pubkey(Party::Client),
+        freelancer: env.pubkey(Party::Freelancer),
+        mint: env.mint,
+        client_share: 50,
+        freelancer_share: 50,
+        initial_payment: 0,
+        // Starts when the offer expires, so it is never in the past while the offer can be accepted.
+        project_start: expires_at as i128,
+        project_duration: PROJECT_DURATION,
+        dispute_resolution: "Dispute Clause".to_string(),
+        ordering: MilestoneOrdering::Any,
+        release_policy: ReleasePolicy::ClientApproval,
+        payees: vec![],
+        milestones: milestones(),
+        expires_at,
+        nonce,
+    }
+}
+
+/// The ed25519 precompile instruction and `accept_offer`, with `signer` signing `signed_terms`.
+fn accept_ixs(
+    signer: &Keypair,
+    signed_terms: &OfferTerms,
+    submitted_terms: OfferTerms,
+) -> Vec<solana_sdk::instruction::Instruction> {
+    let message = offer::offer_message(signed_terms).unwrap();
+    let signature: [u8; 64] = signer.sign_message(&message).into();
+    vec![
+        offer::ed25519_instruction(&signer.pubkey(), &signature, &message),
+        instructions::accept_offer(submitted_terms),
+    ]
+}
+
+#[test]
+fn accept_offer_creates_the_contract_in_one_transaction() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 1);
+    let client = env.keypair(Party::Client);
+
+    env.exec(&accept_ixs(&client, &terms, terms.clone()), Party::Freelancer)
+        .unwrap();
+
+    let contract = env.contract();
+    assert_eq!(contract.client, env.pubkey(Party::Client));
+    assert_eq!(contract.milestone_count, 3);
+    let descriptions: Vec<_> = env.all_milestones().into_iter().map(|m| m.description).collect();
+    assert_eq!(descriptions, ["Design Phase", "Development Phase", "Testing Phase"]);
+    let approval: MilestoneApproval = env.account(&env.addresses.milestone_approval);
+    assert!(approval.is_signed);
+    assert_eq!(approval.approvals, 2);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), 0);
+}
+
+#[test]
+fn accept_offer_rejects_replay() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 7);
+    let client = env.keypair(Party::Client);
+    let ixs = accept_ixs(&client, &terms, terms.clone());
+
+    env.exec(&ixs, Party::Freelancer).unwrap();
+    assert_fails(env.exec(&ixs, Party::Freelancer));
+}
+
+#[test]
+fn accept_offer_rejects_expired_offers() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 1);
+    let client = env.keypair(Party::Client);
+
+    env.warp_by(3601);
+    assert_error(
+        env.exec(&accept_ixs(&client, &terms, terms.clone()), Party::Freelancer),
+        OfferError::OfferExpired,
+    );
+}
+
+#[test]
+fn accept_offer_is_valid_until_expiry() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 1);
+    let client = env.keypair(Party::Client);
+
+    env.warp_by(3600);
+    env.exec(&accept_ixs(&client, &terms, terms.clone()), Party::Freelancer)
+        .unwrap();
+}
+
+#[test]
+fn accept_offer_rejects_signatures_from_anyone_but_the_client() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 1);
+    let outsider = env.keypair(Party::Outsider);
+
+    assert_error(
+        env.exec(&accept_ixs(&outsider, &terms, terms.clone()), Party::Freelancer),
+        OfferError::SignerMismatch,
+    );
+}
+
+#[test]
+fn accept_offer_rejects_tampered_terms() {
+    let mut env = TestEnv::new();
+    let signed = offer_terms(&env, env.now() + 3600, 1);
+    let mut tampered = signed.clone();
+    tampered.milestones[0].amount = 1;
+    let client = env.keypair(Party::Client);
+
+    assert_error(
+        env.exec(&accept_ixs(&client, &signed, tampered), Party::Freelancer),
+        OfferError::TermsMismatch,
+    );
+}
+
+#[test]
+fn accept_offer_requires_the_signature_instruction() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 1);
+
+    assert_error(
+        env.exec(&[instructions::accept_offer(terms)], Party::Freelancer),
+        OfferError::MissingSignatureInstruction,
+    );
+}
+
+#[test]
+fn accept_offer_must_be_submitted_by_the_named_freelancer() {
+    let mut env = TestEnv::new();
+    let terms = offer_terms(&env, env.now() + 3600, 1);
+    let client = env.keypair(Party::Client);
+    let outsider = env.pubkey(Party::Outsider);
+
+    let mut ixs = accept_ixs(&client, &terms, terms.clone());
+    // Swap the freelancer account for the outsider; the signed terms still name the real freelancer.
+    for meta in ixs[1].accounts.iter_mut() {
+        if meta.pubkey == terms.freelancer {
+            meta.pubkey = outsider;
+        }
+    }
+    assert_fails(env.exec(&ixs, Party::Outsider));
+}
+
+#[test]
+fn accept_offer_validates_the_terms() {
+    let mut env = TestEnv::new();
+    let mut terms = offer_terms(&env, env.now() + 3600, 1);
+    terms.freelancer_share = 40;
+    let client = env.keypair(Party::Client);
+
+    assert_error(
+        env.exec(&accept_ixs(&client, &terms, terms.clone()), Party::Freelancer),
+        TermsError::InvalidShares,
+    );
+}
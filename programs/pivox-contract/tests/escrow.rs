@@ -0,0 +1,233 @@
+mod common;
+
+use common::*;
+use pivox_client::instructions;
+use pivox_contract::{ContractError, MilestoneError, VaultDepositError, VaultWithdrawError};
+
+#[test]
+fn deposit_funds_the_vault() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    env.deposit(40_000_000).unwrap();
+    env.deposit(milestones_total() - 40_000_000).unwrap();
+
+    assert_eq!(env.vault().balance, milestones_total());
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total());
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - milestones_total());
+}
+
+#[test]
+fn deposit_cannot_exceed_milestone_total() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    assert_error(env.deposit(milestones_total() + 1), VaultDepositError::Overflow);
+    assert_error(env.deposit(u64::MAX), VaultDepositError::Overflow);
+    assert_eq!(env.vault().balance, 0);
+}
+
+#[test]
+fn deposit_into_a_funded_vault_is_rejected() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(env.deposit(1), VaultDepositError::InsufficientFunds);
+}
+
+#[test]
+fn deposit_into_a_terminated_contract_is_rejected() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+    // The empty vault ATA was closed by the termination; recreate it so the deposit reaches the program.
+    assert!(env.svm.get_account(&env.addresses.vault_ata).is_none_or(|a| a.data.is_empty()));
+    env.create_vault_ata();
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), 0);
+
+    assert_error(env.deposit(1), MilestoneError::ContractTerminated);
+    assert_eq!(env.vault().total_deposited, 0);
+}
+
+#[test]
+fn release_pays_the_freelancer() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+
+    env.release(0, Party::Client).unwrap();
+
+    let amount = milestones()[0].amount;
+    assert!(env.milestone(0).is_released);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), amount);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total() - amount);
+    assert_eq!(env.vault().balance, milestones_total() - amount);
+}
+
+#[test]
+fn release_requires_submission_and_approval() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(env.release(0, Party::Client), ContractError::MilestoneError);
+    env.submit(0, Party::Freelancer).unwrap();
+    assert_error(env.release(0, Party::Client), ContractError::MilestoneError);
+    env.approve_milestone(0, Party::Client).unwrap();
+    env.release(0, Party::Client).unwrap();
+}
+
+#[test]
+fn release_cannot_be_repeated() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+
+    env.release(0, Party::Client).unwrap();
+    assert_error(env.release(0, Party::Client), ContractError::AlreadyReleased);
+}
+
+#[test]
+fn release_rejects_out_of_range_index() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(env.release(3, Party::Client), ContractError::MilestoneError);
+}
+
+#[test]
+fn outsiders_cannot_terminate() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(env.terminate(Party::Outsider), ContractError::Unauthorized);
+}
+
+#[test]
+fn termination_needs_both_parties() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.terminate(Party::Client).unwrap();
+    let contract = env.contract();
+    assert!(contract.client_approved && !contract.freelancer_approved);
+    assert_eq!(contract.status, "active");
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total());
+
+    env.terminate(Party::Freelancer).unwrap();
+    assert_eq!(env.contract().status, "terminated");
+    assert_eq!(env.vault().vault_status, "Terminated");
+    assert_eq!(env.vault().balance, 0);
+    // Nothing was submitted, so every milestone's escrow went back to the client.
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 0);
+    assert!(env.svm.get_account(&env.addresses.vault_ata).is_none_or(|a| a.data.is_empty()));
+}
+
+#[test]
+fn termination_after_the_project_window_leaves_the_remainder_to_withdraw() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    env.submit(1, Party::Freelancer).unwrap();
+
+    env.warp_by(PROJECT_DURATION as i64 + 1);
+    env.terminate(Party::Freelancer).unwrap();
+    env.terminate(Party::Client).unwrap();
+
+    // Only the unstarted milestone is refunded; the submitted one stays until each party withdraws.
+    let (released, submitted, unstarted) = (milestones()[0].amount, milestones()[1].amount, milestones()[2].amount);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), released);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - milestones_total() + unstarted);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), submitted);
+    assert_eq!(env.vault().balance, submitted);
+
+    env.withdraw(Party::Freelancer).unwrap();
+    env.withdraw(Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), released + submitted / 2);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - released - submitted / 2);
+    let vault = env.vault();
+    assert_eq!((vault.client_withdrawn, vault.freelancer_withdrawn, vault.balance), (submitted / 2, submitted / 2, 0));
+    assert!(env.svm.get_account(&env.addresses.vault_ata).is_none_or(|a| a.data.is_empty()));
+}
+
+#[test]
+fn approved_milestones_are_not_released_after_termination() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    // The approved milestone's escrow is split by share through `withdraw` now, not released on top of it.
+    env.withdraw(Party::Freelancer).unwrap();
+    assert_error(env.release(0, Party::Client), MilestoneError::ContractTerminated);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount / 2);
+}
+
+#[test]
+fn withdraw_requires_termination() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(
+        env.withdraw(Party::Client),
+        VaultWithdrawError::Unauthorized,
+    );
+}
+
+/// A contract split 30/70 that was terminated, with `late` tokens sent to the vault afterwards.
+fn terminated_with_late_funds(env: &mut TestEnv, late: u64) {
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let args = instructions::ApproveArgs { client_share: 30, freelancer_share: 70, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer).unwrap();
+    env.deposit(milestones_total()).unwrap();
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    env.create_vault_ata();
+    env.transfer_to_vault(late).unwrap();
+}
+
+#[test]
+fn each_party_withdraws_their_share() {
+    let mut env = TestEnv::new();
+    terminated_with_late_funds(&mut env, 1_000_001);
+    let client_before = env.token_balance(&env.addresses.client_ata);
+    let freelancer_before = env.token_balance(&env.addresses.freelancer_ata);
+
+    env.withdraw(Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.client_ata) - client_before, 300_000);
+    assert_error(env.withdraw(Party::Client), VaultWithdrawError::NothingToWithdraw);
+
+    // The freelancer's share takes the rounding remainder, and emptying the vault closes its ATA.
+    env.withdraw(Party::Freelancer).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata) - freelancer_before, 700_001);
+    assert!(env.svm.get_account(&env.addresses.vault_ata).is_none_or(|a| a.data.is_empty()));
+
+    let vault = env.vault();
+    assert_eq!((vault.client_withdrawn, vault.freelancer_withdrawn, vault.balance), (300_000, 700_001, 0));
+    assert_eq!(vault.total_refunded, milestones_total() + 300_000);
+}
+
+#[test]
+fn outsiders_cannot_withdraw() {
+    let mut env = TestEnv::new();
+    terminated_with_late_funds(&mut env, 1_000_000);
+    assert_error(env.withdraw(Party::Outsider), VaultWithdrawError::NotAParty);
+}
+
+#[test]
+fn withdrawals_only_go_to_the_signers_own_account() {
+    let mut env = TestEnv::new();
+    terminated_with_late_funds(&mut env, 1_000_000);
+
+    let client = env.pubkey(Party::Client);
+    let receipt_number = env.contract().receipt_count;
+    let mut ix = instructions::withdraw_funds(&env.addresses, &client, receipt_number);
+    let destination = ix.accounts.iter().position(|m| m.pubkey == env.addresses.client_ata).unwrap();
+    ix.accounts[destination].pubkey = env.addresses.freelancer_ata;
+    assert_fails(env.exec(&[ix], Party::Client));
+}
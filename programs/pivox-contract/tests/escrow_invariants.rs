@@ -0,0 +1,230 @@
+//! Property tests for the escrow state machine.
+//!
+//! Each case opens a milestone approval and then replays a random sequence of instructions from random
+//! signers. Whatever the program accepts or rejects, the invariants below must hold after every step.
+//! Raise `PROPTEST_CASES` for a longer run.
+
+mod common;
+
+use common::*;
+use pivox_client::instructions;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Approve(Party),
+    Deposit(Party, u64),
+    Submit(Party, u64),
+    ApproveMilestone(Party, u64),
+    Confirm(Party, u64),
+    Release(Party, u64),
+    Terminate(Party),
+    Withdraw(Party),
+    Reconcile(Party),
+    TransferToVault(u64),
+    Warp(i64),
+}
+
+fn party() -> impl Strategy<Value = Party> {
+    prop_oneof![Just(Party::Client), Just(Party::Freelancer), Just(Party::Outsider)]
+}
+
+/// Indices up to one past the last milestone, so out-of-range calls are exercised too.
+fn index() -> impl Strategy<Value = u64> {
+    0..=milestones().len() as u64
+}
+
+fn amount() -> impl Strategy<Value = u64> {
+    let total = milestones_total();
+    prop_oneof![
+        4 => 0..=total + 1,
+        1 => Just(total),
+        1 => Just(u64::MAX),
+    ]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        1 => party().prop_map(Op::Approve),
+        3 => (party(), amount()).prop_map(|(p, a)| Op::Deposit(p, a)),
+        3 => (party(), index()).prop_map(|(p, i)| Op::Submit(p, i)),
+        3 => (party(), index()).prop_map(|(p, i)| Op::ApproveMilestone(p, i)),
+        2 => (party(), index()).prop_map(|(p, i)| Op::Confirm(p, i)),
+        3 => (party(), index()).prop_map(|(p, i)| Op::Release(p, i)),
+        1 => party().prop_map(Op::Terminate),
+        1 => party().prop_map(Op::Withdraw),
+        1 => party().prop_map(Op::Reconcile),
+        1 => (1..milestones_total()).prop_map(Op::TransferToVault),
+        1 => (0..PROJECT_DURATION as i64 * 2).prop_map(Op::Warp),
+    ]
+}
+
+/// What the test expects the program to have done, built only from the instructions that succeeded.
+#[derive(Default)]
+struct Model {
+    created: bool,
+    terminated: bool,
+    released: Vec<bool>,
+    /// Tokens sent straight to the vault ATA that the ledger has not booked yet.
+    untracked: u64,
+}
+
+struct Balances {
+    client: u64,
+    freelancer: u64,
+    outsider: u64,
+    vault: u64,
+}
+
+impl Balances {
+    fn read(env: &TestEnv) -> Self {
+        Self {
+            client: env.token_balance(&env.addresses.client_ata),
+            freelancer: env.token_balance(&env.addresses.freelancer_ata),
+            outsider: env.token_balance(&env.outsider_ata()),
+            vault: env.token_balance(&env.addresses.vault_ata),
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.client + self.freelancer + self.outsider + self.vault
+    }
+}
+
+fn apply(env: &mut TestEnv, model: &mut Model, op: &Op) -> Result<(), TestCaseError> {
+    let before = Balances::read(env);
+
+    match *op {
+        Op::Approve(party) => {
+            let mut ix = instructions::approve(&env.addresses, approve_args(milestones()));
+            ix.accounts[0].pubkey = env.pubkey(party);
+            if env.exec(&[ix], party).is_ok() {
+                prop_assert!(!model.created, "contract created twice");
+                prop_assert_eq!(party, Party::Freelancer, "approve accepted from {:?}", party);
+                model.created = true;
+                model.released = vec![false; milestones().len()];
+            }
+        }
+        Op::Deposit(party, amount) => {
+            if env.deposit_from(party, amount).is_ok() {
+                prop_assert!(model.created && !model.terminated);
+                prop_assert!(before.vault.saturating_add(amount) <= milestones_total(), "vault overfunded");
+            }
+        }
+        Op::Submit(party, index) => {
+            if env.submit(index, party).is_ok() {
+                prop_assert_eq!(party, Party::Freelancer);
+            }
+        }
+        Op::ApproveMilestone(party, index) => {
+            if env.approve_milestone(index, party).is_ok() {
+                prop_assert_eq!(party, Party::Client);
+            }
+        }
+        Op::Confirm(party, index) => {
+            if env.confirm(index, party).is_ok() {
+                prop_assert_eq!(party, Party::Freelancer);
+            }
+        }
+        Op::Release(party, index) => {
+            if env.release(index, party).is_ok() {
+                prop_assert_eq!(party, Party::Client, "release accepted from {:?}", party);
+                let released = &mut model.released[index as usize];
+                prop_assert!(!*released, "milestone {} paid twice", index);
+                *released = true;
+
+                let after = Balances::read(env);
+                prop_assert_eq!(after.freelancer - before.freelancer, milestones()[index as usize].amount);
+            }
+        }
+        Op::Terminate(party) => {
+            if env.terminate(party).is_ok() {
+                prop_assert_ne!(party, Party::Outsider);
+                model.terminated = env.contract().status == "terminated";
+                if model.terminated {
+                    model.untracked = 0;
+                }
+            }
+        }
+        Op::Withdraw(party) => {
+            if model.created && env.withdraw(party).is_ok() {
+                prop_assert_ne!(party, Party::Outsider);
+                prop_assert!(model.terminated, "withdrawal before termination");
+                // Withdrawing books whatever reached the vault since termination.
+                model.untracked = 0;
+            }
+        }
+        Op::Reconcile(party) => {
+            if env.reconcile(party).is_ok() {
+                prop_assert_ne!(party, Party::Outsider);
+                model.untracked = 0;
+            }
+        }
+        Op::TransferToVault(amount) => {
+            if env.transfer_to_vault(amount).is_ok() {
+                model.untracked += amount;
+            }
+        }
+        Op::Warp(seconds) => env.warp_by(seconds),
+    }
+
+    check_invariants(env, model, &before)
+}
+
+fn check_invariants(env: &TestEnv, model: &Model, before: &Balances) -> Result<(), TestCaseError> {
+    let after = Balances::read(env);
+
+    prop_assert_eq!(after.total(), CLIENT_FUNDS, "tokens were created or destroyed");
+    prop_assert_eq!(after.outsider, 0, "an outsider received funds");
+    prop_assert!(after.freelancer >= before.freelancer, "the freelancer is only ever credited");
+
+    if model.created {
+        let vault = env.vault();
+        prop_assert_eq!(vault.balance + model.untracked, after.vault, "vault_account.balance drifted from the vault ATA");
+        prop_assert_eq!(
+            vault.balance,
+            vault.total_deposited - vault.total_released - vault.total_refunded - vault.total_written_off,
+            "ledger counters do not add up to the balance"
+        );
+        prop_assert_eq!(after.freelancer, vault.total_released, "freelancer payouts not booked as releases");
+        prop_assert_eq!(
+            after.client + vault.total_deposited + model.untracked,
+            CLIENT_FUNDS + vault.total_refunded,
+            "client funds not accounted for"
+        );
+
+        let released: u64 = milestones()
+            .iter()
+            .zip(&model.released)
+            .filter(|(_, released)| **released)
+            .map(|(m, _)| m.amount)
+            .sum();
+        // After termination the freelancer is only paid their withdrawn share of what was left.
+        prop_assert_eq!(after.freelancer, released + vault.freelancer_withdrawn, "freelancer paid for unreleased work");
+        if !model.terminated {
+            prop_assert_eq!((vault.client_withdrawn, vault.freelancer_withdrawn), (0, 0));
+        }
+
+        for (m, released) in env.all_milestones().iter().zip(&model.released) {
+            prop_assert_eq!(m.is_released, *released);
+        }
+    } else {
+        prop_assert_eq!(after.client, CLIENT_FUNDS);
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn escrow_invariants_hold(ops in prop::collection::vec(op(), 1..40)) {
+        let mut env = TestEnv::new();
+        let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+        env.exec(&[proposal(&client, &freelancer, &approve_args(milestones()))], Party::Client).unwrap();
+
+        let mut model = Model::default();
+        for op in &ops {
+            apply(&mut env, &mut model, op)?;
+        }
+    }
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use pivox_client::{instructions, ActivityAction, ActivityLog};
+use pivox_contract::{MilestoneError, VaultDepositError};
+
+#[test]
+fn milestones_can_be_funded_one_at_a_time() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    assert_error(env.submit(0, Party::Freelancer), MilestoneError::NotFunded);
+
+    assert_eq!(env.contract().unsubmitted, milestones_total());
+    env.fund_milestone(0).unwrap();
+    assert!(env.milestone(0).funded);
+    assert_eq!(env.contract().unsubmitted_funding, milestones()[0].amount);
+    env.complete_milestone(0);
+    assert_eq!(env.contract().unsubmitted_funding, 0);
+    assert_eq!(env.contract().unsubmitted, milestones_total() - milestones()[0].amount);
+    assert_error(env.submit(1, Party::Freelancer), MilestoneError::NotFunded);
+    env.release(0, Party::Client).unwrap();
+
+    env.fund_milestone(1).unwrap();
+    env.complete_milestone(1);
+    env.release(1, Party::Client).unwrap();
+    assert!(!env.milestone(2).funded);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount + milestones()[1].amount);
+    assert_eq!(env.vault().balance, 0);
+
+    let log: ActivityLog = env.account(&env.addresses.activity_log);
+    let deposits: Vec<_> = log
+        .chronological()
+        .filter(|e| e.action == ActivityAction::Deposited)
+        .map(|e| (e.milestone_index, e.amount))
+        .collect();
+    assert_eq!(deposits, vec![(Some(0), milestones()[0].amount), (Some(1), milestones()[1].amount)]);
+}
+
+#[test]
+fn an_untagged_deposit_covering_everything_funds_every_milestone() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    env.fund_milestone(1).unwrap();
+    env.deposit(milestones_total() - milestones()[1].amount - 1).unwrap();
+    assert_error(env.submit(0, Party::Freelancer), MilestoneError::NotFunded);
+
+    env.deposit(1).unwrap();
+    env.submit(0, Party::Freelancer).unwrap();
+    env.submit(2, Party::Freelancer).unwrap();
+    assert!(!env.milestone(0).funded, "only tagged deposits set the flag");
+}
+
+#[test]
+fn a_milestone_deposit_is_exactly_its_escrow() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    let client = env.pubkey(Party::Client);
+    let amount = milestones()[0].amount;
+
+    for wrong in [amount - 1, amount + 1] {
+        let ix = instructions::fund_milestone(&env.addresses, &client, 0, wrong);
+        assert_error(env.exec(&[ix], Party::Client), VaultDepositError::MilestoneAmountMismatch);
+    }
+    let mut ix = instructions::fund_milestone(&env.addresses, &client, 0, amount);
+    ix.accounts[8].pubkey = env.addresses.milestone(1);
+    assert_error(env.exec(&[ix], Party::Client), MilestoneError::InvalidMilestoneAccount);
+
+    env.fund_milestone(0).unwrap();
+    assert_error(env.fund_milestone(0), VaultDepositError::AlreadyFunded);
+    assert_eq!(env.vault().balance, amount);
+}
+
+#[test]
+fn termination_refunds_milestones_that_were_never_submitted() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    env.fund_milestone(0).unwrap();
+    env.fund_milestone(2).unwrap();
+    env.submit(0, Party::Freelancer).unwrap();
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    // Milestone 0 was delivered, so its escrow stays for the parties to withdraw; milestone 2's goes back.
+    let (delivered, unstarted) = (milestones()[0].amount, milestones()[2].amount);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - delivered);
+    let vault = env.vault();
+    assert_eq!((vault.total_released, vault.total_refunded, vault.balance), (0, unstarted, delivered));
+    assert_eq!(env.contract().unsubmitted_funding, 0);
+}
+
+#[test]
+fn termination_refunds_unsubmitted_milestones_funded_by_untagged_deposits() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    env.deposit(milestones_total()).unwrap();
+    env.submit(0, Party::Freelancer).unwrap();
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    let delivered = milestones()[0].amount;
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - delivered);
+    assert_eq!(env.vault().total_refunded, milestones_total() - delivered);
+    assert_eq!(env.vault().balance, delivered);
+}
+
+#[test]
+fn termination_refunds_untagged_deposits_beyond_the_submitted_milestones() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    env.fund_milestone(0).unwrap();
+    env.submit(0, Party::Freelancer).unwrap();
+    env.fund_milestone(2).unwrap();
+    env.deposit(10_000_000).unwrap();
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    // Milestone 2 gets its own funding back and milestone 1 the untagged deposit; milestone 0 was delivered.
+    let refunded = milestones()[2].amount + 10_000_000;
+    assert_eq!(env.vault().total_refunded, refunded);
+    assert_eq!(env.vault().balance, milestones()[0].amount);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::*;
+use pivox_client::VaultAccount;
+use pivox_contract::{LedgerError, VaultDepositError};
+
+fn assert_ledger_balances(vault: &VaultAccount) {
+    assert_eq!(
+        vault.balance,
+        vault.total_deposited - vault.total_released - vault.total_refunded - vault.total_written_off
+    );
+}
+
+#[test]
+fn deposits_and_releases_are_counted() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    let vault = env.vault();
+    assert_eq!(vault.total_deposited, milestones_total());
+    assert_eq!(vault.total_released, milestones()[0].amount);
+    assert_eq!(vault.total_refunded, 0);
+    assert_ledger_balances(&vault);
+}
+
+#[test]
+fn released_milestones_no_longer_need_funding() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    assert_error(env.deposit(1), VaultDepositError::InsufficientFunds);
+    assert_eq!(env.vault().total_deposited, milestones_total());
+}
+
+#[test]
+fn partial_funding_after_a_release_only_covers_the_rest() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+    let first = milestones()[0].amount;
+    env.fund_milestone(0).unwrap();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    let remaining = milestones_total() - first;
+    assert_error(env.deposit(remaining + 1), VaultDepositError::Overflow);
+    env.deposit(remaining).unwrap();
+    assert_eq!(env.vault().balance, remaining);
+    assert_ledger_balances(&env.vault());
+}
+
+#[test]
+fn reconcile_refunds_untracked_tokens_to_the_client() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.transfer_to_vault(5_000_000).unwrap();
+    let client_before = env.token_balance(&env.addresses.client_ata);
+
+    env.reconcile(Party::Freelancer).unwrap();
+
+    let vault = env.vault();
+    assert_eq!(vault.balance, milestones_total());
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total());
+    assert_eq!(env.token_balance(&env.addresses.client_ata), client_before + 5_000_000);
+    assert_eq!(vault.total_deposited, milestones_total() + 5_000_000);
+    assert_eq!(vault.total_refunded, 5_000_000);
+    assert_ledger_balances(&vault);
+}
+
+#[test]
+fn reconcile_is_a_no_op_when_the_ledger_matches() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let before = env.vault();
+
+    env.reconcile(Party::Client).unwrap();
+
+    let after = env.vault();
+    assert_eq!(after.balance, before.balance);
+    assert_eq!(after.total_deposited, before.total_deposited);
+    assert_eq!(after.total_refunded, 0);
+    assert_eq!(after.total_written_off, 0);
+}
+
+#[test]
+fn only_the_parties_can_reconcile() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(env.reconcile(Party::Outsider), LedgerError::Unauthorized);
+}
+
+#[test]
+fn termination_books_untracked_tokens_before_refunding() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.transfer_to_vault(1_000_000).unwrap();
+
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    // The milestones were never submitted, so their escrow is refunded and only the extra tokens stay.
+    let vault = env.vault();
+    assert_eq!(vault.balance, 1_000_000);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), vault.balance);
+    assert_eq!((vault.total_deposited, vault.total_refunded), (milestones_total() + 1_000_000, milestones_total()));
+    assert_ledger_balances(&vault);
+}
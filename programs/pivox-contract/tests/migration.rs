@@ -0,0 +1,194 @@
+mod common;
+
+use anchor_lang::{AnchorSerialize, Space};
+use common::*;
+use litesvm_token::MintTo;
+use pivox_client::{
+    decode_legacy_contract, instructions, layout_version, ActivityAction, ActivityLog, Contract, MilestoneApproval,
+    ReleasePolicy, VaultAccount, Versioned, LAYOUT_VERSION, PROGRAM_ID,
+};
+use pivox_contract::{state::decode_v0, MigrateError};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+// Byte snapshots of accounts written before layouts were versioned (layout 0), with placeholder keys:
+// client [1; 32], freelancer [2; 32], vault account [3; 32] and approval [4; 32].
+const CONTRACT_V0: &str = include_str!("fixtures/contract_v0.hex");
+const VAULT_ACCOUNT_V0: &str = include_str!("fixtures/vault_account_v0.hex");
+const MILESTONE_APPROVAL_V0: &str = include_str!("fixtures/milestone_approval_v0.hex");
+
+fn fixture(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+        .collect()
+}
+
+/// A layout 0 account as the old program wrote it: discriminator, fields, zero padding.
+fn encode_v0<T: Versioned>(value: &impl AnchorSerialize) -> Vec<u8> {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    value.serialize(&mut data).unwrap();
+    data.resize(T::V0_SPACE, 0);
+    data
+}
+
+fn set_program_account(env: &mut TestEnv, address: Pubkey, data: Vec<u8>) {
+    let lamports = Rent::default().minimum_balance(data.len());
+    env.svm
+        .set_account(address, Account { lamports, data, owner: PROGRAM_ID, executable: false, rent_epoch: 0 })
+        .unwrap();
+}
+
+/// Puts the snapshot contract, vault and approval at the environment's addresses, as if they had been
+/// created by the old program, and funds the vault with what the snapshot says it holds.
+fn install_layout_0(env: &mut TestEnv) {
+    let a = env.addresses;
+
+    let mut contract = decode_legacy_contract(&fixture(CONTRACT_V0)).unwrap();
+    contract.client = a.client;
+    contract.freelancer = a.freelancer;
+    contract.vault_account = a.vault_account;
+    contract.multisig_account = a.milestone_approval;
+    contract.contract_bump = a.contract_bump;
+    set_program_account(env, a.contract, encode_v0::<Contract>(&contract));
+
+    let mut vault = decode_v0::<VaultAccount>(&fixture(VAULT_ACCOUNT_V0)).unwrap();
+    vault.client = a.client;
+    vault.freelancer = a.freelancer;
+    vault.multisig_account = a.milestone_approval;
+    vault.vault_bump = a.vault_bump;
+    let balance = vault.balance;
+    set_program_account(env, a.vault_account, encode_v0::<VaultAccount>(&vault));
+
+    let mut approval = decode_v0::<MilestoneApproval>(&fixture(MILESTONE_APPROVAL_V0)).unwrap();
+    approval.client = a.client;
+    approval.freelancer = a.freelancer;
+    approval.approved_by = vec![a.client, a.freelancer];
+    approval.multisig_bump = a.milestone_approval_bump;
+    set_program_account(env, a.milestone_approval, encode_v0::<MilestoneApproval>(&approval));
+
+    env.create_vault_ata();
+    let client = env.client.insecure_clone();
+    MintTo::new(&mut env.svm, &client, &env.mint, &a.vault_ata, balance).send().unwrap();
+}
+
+fn migrate(env: &mut TestEnv, party: Party, legacy_milestones: u8) -> litesvm::types::TransactionResult {
+    let signer = env.pubkey(party);
+    env.exec(&[instructions::migrate(&env.addresses, &signer, legacy_milestones)], party)
+}
+
+#[test]
+fn layout_0_snapshots_decode() {
+    let data = fixture(CONTRACT_V0);
+    assert_eq!(layout_version::<Contract>(&data).unwrap(), 0);
+    let contract = decode_legacy_contract(&data).unwrap();
+    assert_eq!((contract.client, contract.freelancer), (Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32])));
+    assert_eq!(contract.project_start, START_TIME as i128);
+    assert_eq!((contract.client_share, contract.freelancer_share), (50, 50));
+    assert_eq!(contract.status, "active");
+    let milestones: Vec<_> = contract.milestones.iter().map(|m| (m.description.as_str(), m.amount)).collect();
+    assert_eq!(milestones, vec![("Design", 50_000_000), ("Build", 30_000_000), ("Launch", 20_000_000)]);
+    assert!(contract.milestones[0].is_released);
+    assert!(contract.milestones[1].freelancer_submitted && !contract.milestones[1].client_approved);
+    assert_eq!(encode_v0::<Contract>(&contract), data);
+
+    let data = fixture(VAULT_ACCOUNT_V0);
+    assert_eq!(layout_version::<VaultAccount>(&data).unwrap(), 0);
+    let vault = decode_v0::<VaultAccount>(&data).unwrap();
+    assert_eq!((vault.balance, vault.vault_bump, vault.vault_status.as_str()), (50_000_000, 253, "active"));
+    assert_eq!(vault.multisig_account, Pubkey::new_from_array([4; 32]));
+    assert_eq!(encode_v0::<VaultAccount>(&vault), data);
+
+    let data = fixture(MILESTONE_APPROVAL_V0);
+    assert_eq!(layout_version::<MilestoneApproval>(&data).unwrap(), 0);
+    let approval = decode_v0::<MilestoneApproval>(&data).unwrap();
+    assert_eq!((approval.threshold, approval.approvals, approval.is_signed), (1, 2, true));
+    assert_eq!(approval.approved_by.len(), 2);
+    assert_eq!(encode_v0::<MilestoneApproval>(&approval), data);
+}
+
+#[test]
+fn current_layouts_are_told_apart_from_layout_0() {
+    assert_ne!(Contract::V0_SPACE, 8 + Contract::INIT_SPACE);
+    assert_ne!(VaultAccount::V0_SPACE, 8 + VaultAccount::INIT_SPACE);
+    assert_ne!(MilestoneApproval::V0_SPACE, 8 + MilestoneApproval::INIT_SPACE);
+
+    let mut env = TestEnv::new();
+    env.create_contract();
+    let a = env.addresses;
+    for (address, version) in [
+        (a.contract, layout_version::<Contract> as fn(&[u8]) -> _),
+        (a.vault_account, layout_version::<VaultAccount>),
+        (a.milestone_approval, layout_version::<MilestoneApproval>),
+    ] {
+        let data = env.svm.get_account(&address).unwrap().data;
+        assert_eq!(version(&data).unwrap(), LAYOUT_VERSION);
+    }
+    assert_eq!(env.milestone(0).version, LAYOUT_VERSION);
+}
+
+#[test]
+fn migrate_upgrades_layout_0_accounts_in_place() {
+    let mut env = TestEnv::new();
+    install_layout_0(&mut env);
+    migrate(&mut env, Party::Freelancer, 3).unwrap();
+
+    let contract = env.contract();
+    assert_eq!(contract.version, LAYOUT_VERSION);
+    assert_eq!(contract.id, env.addresses.contract);
+    assert_eq!(contract.dispute_resolution, "Arbitration by a mutually agreed third party");
+    assert_eq!(contract.release_policy, ReleasePolicy::ClientApproval);
+    assert_eq!((contract.milestone_count, contract.milestones_total), (3, 100_000_000));
+    assert_eq!((contract.submitted_count, contract.released_count), (2, 1));
+    assert_eq!((contract.outstanding().unwrap(), contract.unsubmitted), (50_000_000, 20_000_000));
+
+    let milestones = env.all_milestones();
+    let descriptions: Vec<_> = milestones.iter().map(|m| m.description.as_str()).collect();
+    assert_eq!(descriptions, vec!["Design", "Build", "Launch"]);
+    assert!(milestones[0].is_released && milestones[1].freelancer_submitted && !milestones[2].freelancer_submitted);
+
+    let vault = env.vault();
+    assert_eq!(vault.version, LAYOUT_VERSION);
+    assert_eq!((vault.balance, vault.total_deposited, vault.total_released), (50_000_000, 50_000_000, 0));
+
+    let approval: MilestoneApproval = env.account(&env.addresses.milestone_approval);
+    assert_eq!(approval.version, LAYOUT_VERSION);
+    assert!(approval.is_signed);
+
+    let log: ActivityLog = env.account(&env.addresses.activity_log);
+    let actions: Vec<_> = log.chronological().map(|e| (e.action, e.actor)).collect();
+    assert_eq!(actions, vec![(ActivityAction::Migrated, env.addresses.freelancer)]);
+}
+
+#[test]
+fn migrated_contracts_carry_on_where_they_left_off() {
+    let mut env = TestEnv::new();
+    install_layout_0(&mut env);
+    migrate(&mut env, Party::Client, 3).unwrap();
+
+    env.approve_milestone(1, Party::Client).unwrap();
+    env.confirm(1, Party::Freelancer).unwrap();
+    env.release(1, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 30_000_000);
+    assert_eq!(env.contract().outstanding().unwrap(), 20_000_000);
+    assert_eq!(env.vault().balance, 20_000_000);
+}
+
+#[test]
+fn migrating_twice_is_rejected() {
+    let mut env = TestEnv::new();
+    install_layout_0(&mut env);
+    migrate(&mut env, Party::Client, 3).unwrap();
+    assert_error(migrate(&mut env, Party::Client, 0), MigrateError::AlreadyCurrent);
+
+    let mut env = TestEnv::new();
+    env.create_contract();
+    assert_error(migrate(&mut env, Party::Freelancer, 0), MigrateError::AlreadyCurrent);
+}
+
+#[test]
+fn only_the_parties_can_migrate() {
+    let mut env = TestEnv::new();
+    install_layout_0(&mut env);
+    assert_error(migrate(&mut env, Party::Outsider, 3), MigrateError::Unauthorized);
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use pivox_client::{instructions, Milestone, MilestoneAccount};
+use pivox_contract::{MilestoneError, TermsError};
+
+#[test]
+fn milestone_moves_through_submit_approve_confirm() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.complete_milestone(0);
+
+    let m = &env.milestone(0);
+    assert!(m.freelancer_submitted && m.client_approved && m.freelancer_confirmed);
+    assert!(!m.is_released);
+    assert!(!env.milestone(1).freelancer_submitted);
+}
+
+#[test]
+fn only_the_freelancer_submits() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    assert_error(env.submit(0, Party::Client), MilestoneError::InvalidSigner);
+    assert_error(env.submit(0, Party::Outsider), MilestoneError::InvalidSigner);
+}
+
+#[test]
+fn submit_cannot_be_repeated() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.submit(0, Party::Freelancer).unwrap();
+    assert_error(env.submit(0, Party::Freelancer), MilestoneError::AlreadySubmitted);
+}
+
+#[test]
+fn only_the_client_approves() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.submit(0, Party::Freelancer).unwrap();
+
+    assert_error(env.approve_milestone(0, Party::Freelancer), MilestoneError::InvalidSigner);
+    assert_error(env.approve_milestone(0, Party::Outsider), MilestoneError::InvalidSigner);
+}
+
+#[test]
+fn approve_requires_submission() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    assert_error(env.approve_milestone(0, Party::Client), MilestoneError::NotSubmitted);
+}
+
+#[test]
+fn approve_cannot_be_repeated() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.submit(0, Party::Freelancer).unwrap();
+
+    env.approve_milestone(0, Party::Client).unwrap();
+    assert_error(env.approve_milestone(0, Party::Client), MilestoneError::AlreadyApproved);
+}
+
+#[test]
+fn confirm_requires_approval() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    assert_error(env.confirm(0, Party::Freelancer), MilestoneError::NotSubmitted);
+    env.submit(0, Party::Freelancer).unwrap();
+    assert_error(env.confirm(0, Party::Freelancer), MilestoneError::NotApproved);
+}
+
+#[test]
+fn confirm_cannot_be_repeated() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.complete_milestone(0);
+    assert_error(env.confirm(0, Party::Freelancer), MilestoneError::AlreadyConfirmed);
+    assert_error(env.confirm(0, Party::Client), MilestoneError::InvalidSigner);
+}
+
+#[test]
+fn out_of_range_index_is_rejected() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    // There is no account for a milestone past the end.
+    assert_error(env.submit(3, Party::Freelancer), ErrorCode::AccountNotInitialized);
+    assert_error(env.approve_milestone(3, Party::Client), ErrorCode::AccountNotInitialized);
+    assert_error(env.confirm(3, Party::Freelancer), ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn milestones_progress_independently_over_time() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.complete_milestone(1);
+    env.warp_by(PROJECT_DURATION as i64 / 2);
+    env.complete_milestone(0);
+    env.warp_by(PROJECT_DURATION as i64);
+    env.submit(2, Party::Freelancer).unwrap();
+
+    let milestones = env.all_milestones();
+    assert!(milestones[0].freelancer_confirmed);
+    assert!(milestones[1].freelancer_confirmed);
+    assert!(milestones[2].freelancer_submitted);
+    assert!(!milestones[2].client_approved);
+}
+
+fn add_milestones(env: &mut TestEnv, milestones: Vec<Milestone>) -> litesvm::types::TransactionResult {
+    let count = env.contract().milestone_count as u8;
+    let ix = instructions::add_milestones(&env.addresses, count, milestones);
+    let (client, freelancer) = (env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    env.exec_with(&[ix], &[&client, &freelancer])
+}
+
+#[test]
+fn each_milestone_has_its_own_account() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    for (index, expected) in milestones().iter().enumerate() {
+        let account: MilestoneAccount = env.account(&env.addresses.milestone(index as u8));
+        assert_eq!((account.contract_id, account.index), (env.addresses.contract, index as u8));
+        assert_eq!(account.amount, expected.amount);
+    }
+    let contract = env.contract();
+    assert_eq!(contract.id, env.addresses.contract);
+    assert_eq!((contract.milestone_count, contract.unreleased), (3, milestones_total()));
+}
+
+#[test]
+fn contracts_are_not_limited_to_ten_milestones() {
+    let mut env = TestEnv::new();
+    let many: Vec<_> = (0..12).map(|i| milestone(&format!("Sprint {i}"), 1_000_000)).collect();
+    env.create_contract_with(many);
+    env.deposit(12_000_000).unwrap();
+
+    env.complete_milestone(11);
+    env.release(11, Party::Client).unwrap();
+
+    assert_eq!(env.contract().milestone_count, 12);
+    assert!(env.milestone(11).is_released);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 1_000_000);
+}
+
+#[test]
+fn both_parties_add_milestones_to_a_running_contract() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    add_milestones(&mut env, vec![milestone("Maintenance", 5_000_000)]).unwrap();
+
+    let contract = env.contract();
+    assert_eq!(contract.milestone_count, 4);
+    assert_eq!(contract.milestones_total, milestones_total() + 5_000_000);
+    assert_eq!(contract.outstanding().unwrap(), milestones_total() - milestones()[0].amount + 5_000_000);
+    assert_eq!(env.milestone(3).description, "Maintenance");
+
+    env.deposit(5_000_000).unwrap();
+    env.complete_milestone(3);
+    env.release(3, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount + 5_000_000);
+}
+
+#[test]
+fn adding_milestones_needs_both_signatures() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    let mut ix = instructions::add_milestones(&env.addresses, 3, vec![milestone("Extra", 1)]);
+    ix.accounts[1].is_signer = false;
+    assert_fails(env.exec(&[ix], Party::Client));
+}
+
+#[test]
+fn new_milestones_continue_the_numbering() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    // Accounts numbered from 0 again would overwrite existing milestones.
+    let ix = instructions::add_milestones(&env.addresses, 0, vec![milestone("Extra", 1)]);
+    let (client, freelancer) = (env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    assert_error(env.exec_with(&[ix], &[&client, &freelancer]), MilestoneError::InvalidMilestoneAccount);
+}
+
+#[test]
+fn terminated_contracts_take_no_new_milestones() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    assert_error(add_milestones(&mut env, vec![milestone("Extra", 1)]), MilestoneError::ContractTerminated);
+}
+
+#[test]
+fn terminated_contracts_take_no_submissions_or_approvals() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.submit(0, Party::Freelancer).unwrap();
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    assert_error(env.submit(1, Party::Freelancer), MilestoneError::ContractTerminated);
+    assert_error(env.approve_milestone(0, Party::Client), MilestoneError::ContractTerminated);
+}
+
+#[test]
+fn added_milestones_are_validated_like_new_contracts() {
+    let mut env = TestEnv::new();
+    env.create_contract();
+
+    assert_error(add_milestones(&mut env, vec![]), TermsError::NoMilestones);
+    assert_error(add_milestones(&mut env, vec![milestone("Extra", 0)]), TermsError::ZeroAmountMilestone);
+    let released = Milestone { is_released: true, ..milestone("Extra", 1) };
+    assert_error(add_milestones(&mut env, vec![released]), TermsError::MilestoneAlreadyStarted);
+    // The existing milestones count towards the total.
+    let huge = u64::MAX - milestones_total() + 1;
+    assert_error(add_milestones(&mut env, vec![milestone("Extra", huge)]), TermsError::MilestoneTotalOverflow);
+}
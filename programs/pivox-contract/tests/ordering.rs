@@ -0,0 +1,122 @@
+mod common;
+
+use common::*;
+use pivox_client::{instructions, Milestone, MilestoneOrdering};
+use pivox_contract::MilestoneError;
+
+fn approve_with(env: &mut TestEnv, ordering: MilestoneOrdering, milestones: Vec<Milestone>) -> litesvm::types::TransactionResult {
+    let client = env.pubkey(Party::Client);
+    let freelancer = env.pubkey(Party::Freelancer);
+    let args = instructions::ApproveArgs { ordering, ..approve_args(milestones) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer)
+}
+
+fn create_funded_with(env: &mut TestEnv, ordering: MilestoneOrdering, milestones: Vec<Milestone>) {
+    let total = milestones.iter().map(|m| m.amount).sum();
+    approve_with(env, ordering, milestones).unwrap();
+    env.deposit(total).unwrap();
+}
+
+fn depending_on(description: &str, amount: u64, depends_on: Vec<u8>) -> Milestone {
+    Milestone { depends_on, ..milestone(description, amount) }
+}
+
+/// Design, then Build and Copy in parallel, then Launch once both are paid.
+fn launch_plan() -> Vec<Milestone> {
+    vec![
+        milestone("Design", 10_000_000),
+        depending_on("Build", 10_000_000, vec![0]),
+        depending_on("Copy", 10_000_000, vec![0]),
+        depending_on("Launch", 10_000_000, vec![1, 2]),
+    ]
+}
+
+#[test]
+fn sequential_milestones_wait_for_the_previous_release() {
+    let mut env = TestEnv::new();
+    create_funded_with(&mut env, MilestoneOrdering::Sequential, milestones());
+
+    assert_error(env.submit(1, Party::Freelancer), MilestoneError::OutOfOrder);
+
+    env.complete_milestone(0);
+    assert_error(env.submit(1, Party::Freelancer), MilestoneError::OutOfOrder);
+
+    env.release(0, Party::Client).unwrap();
+    env.complete_milestone(1);
+    assert!(env.milestone(1).freelancer_confirmed);
+}
+
+#[test]
+fn dependencies_gate_only_their_own_milestones() {
+    let mut env = TestEnv::new();
+    create_funded_with(&mut env, MilestoneOrdering::Dependencies, launch_plan());
+
+    assert_error(env.submit(1, Party::Freelancer), MilestoneError::OutOfOrder);
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    // Build and Copy both only need Design.
+    env.complete_milestone(1);
+    env.complete_milestone(2);
+    env.release(1, Party::Client).unwrap();
+    assert_error(env.submit(3, Party::Freelancer), MilestoneError::OutOfOrder);
+
+    env.release(2, Party::Client).unwrap();
+    env.complete_milestone(3);
+}
+
+#[test]
+fn any_ordering_is_unchanged() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+
+    env.complete_milestone(2);
+    env.release(2, Party::Client).unwrap();
+    assert_eq!(env.contract().ordering, MilestoneOrdering::Any);
+}
+
+#[test]
+fn dependencies_must_point_at_earlier_milestones() {
+    let forward = vec![depending_on("A", 10, vec![1]), milestone("B", 10)];
+    let own = vec![milestone("A", 10), depending_on("B", 10, vec![1])];
+    let repeated = vec![milestone("A", 10), depending_on("B", 10, vec![0, 0])];
+
+    for plan in [forward, own, repeated] {
+        let mut env = TestEnv::new();
+        assert_error(
+            approve_with(&mut env, MilestoneOrdering::Dependencies, plan),
+            MilestoneError::InvalidDependency,
+        );
+    }
+}
+
+#[test]
+fn dependencies_need_the_dependencies_policy() {
+    let mut env = TestEnv::new();
+    assert_error(
+        approve_with(&mut env, MilestoneOrdering::Sequential, launch_plan()),
+        MilestoneError::InvalidDependency,
+    );
+}
+
+#[test]
+fn prerequisite_accounts_must_be_passed_and_match() {
+    let mut env = TestEnv::new();
+    create_funded_with(&mut env, MilestoneOrdering::Dependencies, launch_plan());
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    env.complete_milestone(1);
+    env.complete_milestone(2);
+    env.release(1, Party::Client).unwrap();
+    env.release(2, Party::Client).unwrap();
+
+    let ix = instructions::freelancer_submit_milestone(&env.addresses, 3, &[1]);
+    assert_error(env.exec(&[ix], Party::Freelancer), MilestoneError::MissingPrerequisites);
+    // Released milestones, but not the ones Launch depends on.
+    let ix = instructions::freelancer_submit_milestone(&env.addresses, 3, &[0, 2]);
+    assert_error(env.exec(&[ix], Party::Freelancer), MilestoneError::InvalidMilestoneAccount);
+
+    let ix = instructions::freelancer_submit_milestone(&env.addresses, 3, &[1, 2]);
+    env.exec(&[ix], Party::Freelancer).unwrap();
+}
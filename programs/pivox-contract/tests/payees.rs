@@ -0,0 +1,153 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use litesvm_token::CreateAssociatedTokenAccount;
+use pivox_client::{instructions, Milestone, Payee};
+use pivox_contract::{split_amount, PayeeError, TOTAL_BPS};
+use proptest::prelude::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+/// Fresh wallets with token accounts for the contract mint.
+fn team(env: &mut TestEnv, size: usize) -> Vec<Pubkey> {
+    let payer = env.keypair(Party::Client);
+    (0..size)
+        .map(|_| {
+            let wallet = Keypair::new().pubkey();
+            CreateAssociatedTokenAccount::new(&mut env.svm, &payer, &env.mint)
+                .owner(&wallet)
+                .send()
+                .unwrap();
+            wallet
+        })
+        .collect()
+}
+
+fn weighted(wallets: &[Pubkey], bps: &[u16]) -> Vec<Payee> {
+    wallets.iter().zip(bps).map(|(&wallet, &bps)| Payee { wallet, bps }).collect()
+}
+
+fn approve_with(env: &mut TestEnv, payees: Vec<Payee>, milestones: Vec<Milestone>) -> litesvm::types::TransactionResult {
+    let (client, freelancer) = (env.addresses.client, env.addresses.freelancer);
+    let args = instructions::ApproveArgs { payees, ..approve_args(milestones) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer)
+}
+
+fn create_funded_with(env: &mut TestEnv, payees: Vec<Payee>, milestones: Vec<Milestone>) {
+    let total = milestones.iter().map(|m| m.amount).sum();
+    approve_with(env, payees, milestones).unwrap();
+    env.deposit(total).unwrap();
+}
+
+fn balance_of(env: &TestEnv, wallet: &Pubkey) -> u64 {
+    env.token_balance(&get_associated_token_address(wallet, &env.mint))
+}
+
+#[test]
+fn contract_payees_split_every_release() {
+    let mut env = TestEnv::new();
+    let wallets = team(&mut env, 3);
+    create_funded_with(&mut env, weighted(&wallets, &[5_000, 3_000, 2_000]), milestones());
+
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    let paid: Vec<u64> = wallets.iter().map(|w| balance_of(&env, w)).collect();
+    assert_eq!(paid, vec![25_000_000, 15_000_000, 10_000_000]);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 0);
+    assert_eq!(env.vault().total_released, milestones()[0].amount);
+}
+
+#[test]
+fn rounding_leftover_goes_to_the_first_payee() {
+    let mut env = TestEnv::new();
+    let wallets = team(&mut env, 3);
+    create_funded_with(&mut env, weighted(&wallets, &[3_333, 3_333, 3_334]), vec![milestone("Audit", 100)]);
+
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    let paid: Vec<u64> = wallets.iter().map(|w| balance_of(&env, w)).collect();
+    assert_eq!(paid, vec![34, 33, 33]);
+}
+
+#[test]
+fn milestone_payees_override_the_contract_list() {
+    let mut env = TestEnv::new();
+    let wallets = team(&mut env, 2);
+    let plan = vec![
+        Milestone { payees: weighted(&wallets[1..], &[TOTAL_BPS]), ..milestone("Design", 10_000_000) },
+        milestone("Build", 20_000_000),
+    ];
+    create_funded_with(&mut env, weighted(&wallets, &[5_000, 5_000]), plan);
+
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    env.complete_milestone(1);
+    env.release(1, Party::Client).unwrap();
+
+    assert_eq!(balance_of(&env, &wallets[0]), 10_000_000);
+    assert_eq!(balance_of(&env, &wallets[1]), 20_000_000);
+}
+
+#[test]
+fn release_needs_every_payee_account_in_order() {
+    let mut env = TestEnv::new();
+    let wallets = team(&mut env, 2);
+    let payees = weighted(&wallets, &[6_000, 4_000]);
+    create_funded_with(&mut env, payees.clone(), milestones());
+    env.complete_milestone(0);
+    let client = env.addresses.client;
+
+    let missing = instructions::release_milestone_payment(&env.addresses, &client, 0, &payees[..1], None, 0, "");
+    assert_error(env.exec(&[missing], Party::Client), PayeeError::MissingPayeeAccounts);
+
+    let swapped = [payees[1], payees[0]];
+    let swapped = instructions::release_milestone_payment(&env.addresses, &client, 0, &swapped, None, 0, "");
+    assert_error(env.exec(&[swapped], Party::Client), PayeeError::PayeeAccountMismatch);
+
+    assert!(!env.milestone(0).is_released);
+}
+
+#[test]
+fn invalid_payee_lists_are_rejected() {
+    let wallets: Vec<Pubkey> = (0..9).map(|_| Keypair::new().pubkey()).collect();
+    let cases = [
+        (weighted(&wallets, &[5_000, 4_999]), PayeeError::InvalidTotal),
+        (weighted(&wallets, &[10_000, 0]), PayeeError::ZeroWeight),
+        (weighted(&[wallets[0], wallets[0]], &[5_000, 5_000]), PayeeError::DuplicatePayee),
+        (weighted(&wallets, &[1_250, 1_250, 1_250, 1_250, 1_250, 1_250, 1_250, 1_000, 250]), PayeeError::TooManyPayees),
+    ];
+
+    for (payees, expected) in cases {
+        let mut env = TestEnv::new();
+        assert_error(approve_with(&mut env, payees.clone(), milestones()), expected);
+
+        let mut env = TestEnv::new();
+        let plan = vec![Milestone { payees, ..milestone("Design", 10) }];
+        assert_error(approve_with(&mut env, vec![], plan), expected);
+    }
+}
+
+/// Up to eight weights summing to 10,000; the first takes whatever the others leave.
+fn payee_weights() -> impl Strategy<Value = Vec<u16>> {
+    prop::collection::vec(1u16..=1_250, 0..8).prop_map(|rest| {
+        let first = TOTAL_BPS - rest.iter().sum::<u16>();
+        std::iter::once(first).chain(rest).collect()
+    })
+}
+
+proptest! {
+    #[test]
+    fn splits_always_add_up(amount in any::<u64>(), weights in payee_weights()) {
+        let wallets: Vec<Pubkey> = weights.iter().map(|_| Pubkey::new_unique()).collect();
+        let payees = weighted(&wallets, &weights);
+        let shares = split_amount(amount, &payees);
+
+        prop_assert_eq!(shares.iter().map(|&s| s as u128).sum::<u128>(), amount as u128);
+        for (share, payee) in shares.iter().zip(&payees).skip(1) {
+            prop_assert_eq!(*share as u128, amount as u128 * payee.bps as u128 / TOTAL_BPS as u128);
+        }
+    }
+}
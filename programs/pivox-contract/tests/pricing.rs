@@ -0,0 +1,151 @@
+mod common;
+
+use common::*;
+use pivox_client::{
+    instructions, receipt_address, ContractAddresses, Milestone, PaymentReceipt, PythPrice, QuotePricing, ReceiptKind,
+};
+use pivox_contract::{PriceError, SubcontractError, TermsError};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+const ESCROW: u64 = 55_000_000;
+
+/// 100 USD of work, quoted when the token stood at 2.00 USD, with up to 10% more tokens allowed.
+fn priced_milestone(feed: Pubkey) -> Milestone {
+    Milestone {
+        pricing: Some(QuotePricing {
+            feed,
+            quote_amount: 100_000_000,
+            reference_amount: 50_000_000,
+            max_slippage_bps: 1_000,
+            max_staleness: 60,
+            max_confidence_bps: 100,
+        }),
+        ..milestone("Priced Phase", ESCROW)
+    }
+}
+
+/// A trading Pyth price account at `feed` quoting the token at `price * 10^-8` USD, published now.
+fn set_price(env: &mut TestEnv, feed: Pubkey, price: i64, conf: u64) {
+    let data = PythPrice { price, conf, expo: -8, publish_time: env.now() }.encode();
+    let lamports = Rent::default().minimum_balance(data.len());
+    env.svm
+        .set_account(feed, Account { lamports, data, owner: Pubkey::new_unique(), executable: false, rent_epoch: 0 })
+        .unwrap();
+}
+
+fn create_priced(env: &mut TestEnv) -> Pubkey {
+    let feed = Pubkey::new_unique();
+    env.create_contract_with(vec![priced_milestone(feed)]);
+    env.deposit(ESCROW).unwrap();
+    env.complete_milestone(0);
+    feed
+}
+
+#[test]
+fn release_pays_the_quote_at_the_current_price_and_refunds_the_rest() {
+    let mut env = TestEnv::new();
+    let feed = create_priced(&mut env);
+    set_price(&mut env, feed, 250_000_000, 0);
+
+    env.release(0, Party::Client).unwrap();
+
+    // 100 USD at 2.50 USD a token is 40 tokens; the other 15 go back to the client.
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 40_000_000);
+    assert_eq!(env.token_balance(&env.addresses.client_ata), CLIENT_FUNDS - 40_000_000);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), 0);
+    let vault = env.vault();
+    assert_eq!(vault.total_released, 40_000_000);
+    assert_eq!(vault.total_refunded, 15_000_000);
+    assert!(env.milestone(0).is_released);
+
+    // The payout and the refund each get a receipt.
+    let receipts: Vec<PaymentReceipt> =
+        (0..2).map(|number| env.account(&receipt_address(&env.addresses.contract, number).0)).collect();
+    assert_eq!((receipts[0].kind, receipts[0].amount), (ReceiptKind::MilestoneRelease, 40_000_000));
+    assert_eq!((receipts[1].kind, receipts[1].amount), (ReceiptKind::Refund, 15_000_000));
+    assert_eq!((receipts[1].payee, receipts[1].milestone_index), (env.addresses.client, Some(0)));
+}
+
+#[test]
+fn release_pays_up_to_the_slippage_bound() {
+    let mut env = TestEnv::new();
+    let feed = create_priced(&mut env);
+
+    // At 1.50 USD the quote is 66.66 tokens, past the 55 the client allowed.
+    set_price(&mut env, feed, 150_000_000, 0);
+    assert_error(env.release(0, Party::Client), PriceError::SlippageExceeded);
+
+    // At 1.82 USD it is 54.94 tokens, inside the bound.
+    set_price(&mut env, feed, 182_000_000, 0);
+    env.release(0, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 54_945_054);
+}
+
+#[test]
+fn stale_or_uncertain_prices_are_rejected() {
+    let mut env = TestEnv::new();
+    let feed = create_priced(&mut env);
+
+    set_price(&mut env, feed, 200_000_000, 0);
+    env.warp_by(61);
+    assert_error(env.release(0, Party::Client), PriceError::StalePrice);
+
+    // A confidence interval of 2% is wider than the 1% the milestone accepts.
+    set_price(&mut env, feed, 200_000_000, 4_000_000);
+    assert_error(env.release(0, Party::Client), PriceError::PriceTooUncertain);
+
+    set_price(&mut env, feed, 200_000_000, 2_000_000);
+    env.release(0, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), 50_000_000);
+}
+
+#[test]
+fn release_needs_the_milestones_own_feed() {
+    let mut env = TestEnv::new();
+    let feed = create_priced(&mut env);
+    set_price(&mut env, feed, 200_000_000, 0);
+    let other = Pubkey::new_unique();
+    set_price(&mut env, other, 400_000_000, 0);
+
+    let client = env.pubkey(Party::Client);
+    let receipt_number = env.contract().receipt_count;
+    let release = |price_feed| {
+        instructions::release_milestone_payment(&env.addresses, &client, 0, &[], price_feed, receipt_number, "")
+    };
+    let (missing, wrong) = (release(None), release(Some(other)));
+    assert_error(env.exec(&[missing], Party::Client), PriceError::MissingPriceFeed);
+    assert_error(env.exec(&[wrong], Party::Client), PriceError::PriceFeedMismatch);
+
+    // The right address, but not holding a Pyth price account.
+    let account = Account { lamports: 1_000_000_000, data: vec![0; 240], owner: Pubkey::new_unique(), executable: false, rent_epoch: 0 };
+    env.svm.set_account(feed, account).unwrap();
+    assert_error(env.release(0, Party::Client), PriceError::InvalidPriceAccount);
+}
+
+#[test]
+fn the_escrow_has_to_cover_the_slippage_bound() {
+    let mut env = TestEnv::new();
+    let mut milestone = priced_milestone(Pubkey::new_unique());
+    milestone.amount = ESCROW - 1;
+
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let args = approve_args(vec![milestone]);
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    let approve = instructions::approve(&env.addresses, args);
+    assert_error(env.exec(&[approve], Party::Freelancer), TermsError::PricingNotCovered);
+}
+
+#[test]
+fn priced_milestones_cannot_be_subcontracted() {
+    let mut env = TestEnv::new();
+    create_priced(&mut env);
+
+    let (freelancer, outsider) = (env.pubkey(Party::Freelancer), env.pubkey(Party::Outsider));
+    let child = ContractAddresses::new(&freelancer, &outsider, &env.mint);
+    let args = approve_args(vec![milestone("Logo", 20_000_000)]);
+    env.exec(&[proposal(&freelancer, &outsider, &args)], Party::Freelancer).unwrap();
+    env.exec(&[instructions::approve(&child, args)], Party::Outsider).unwrap();
+    let ix = instructions::fund_subcontract(&env.addresses, &child, 0);
+    let (freelancer, client) = (env.keypair(Party::Freelancer), env.keypair(Party::Client));
+    assert_error(env.exec_with(&[ix], &[&freelancer, &client]), SubcontractError::PricedMilestone);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::*;
+use pivox_client::{profile_address, Profile};
+
+fn profile(env: &TestEnv, party: Party) -> Profile {
+    env.account(&profile_address(&env.pubkey(party)).0)
+}
+
+#[test]
+fn release_records_volume_and_delivery() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    let amount = milestones()[0].amount;
+    let client = profile(&env, Party::Client);
+    let freelancer = profile(&env, Party::Freelancer);
+    assert_eq!(client.owner, env.pubkey(Party::Client));
+    assert_eq!(freelancer.owner, env.pubkey(Party::Freelancer));
+    assert_eq!(client.volume_paid, amount);
+    assert_eq!(client.volume_earned, 0);
+    assert_eq!(freelancer.volume_earned, amount);
+    assert_eq!(freelancer.milestones_delivered, 1);
+    assert_eq!(freelancer.milestones_on_time, 1);
+    assert_eq!(freelancer.on_time_rate_bps(), Some(10_000));
+}
+
+#[test]
+fn releases_after_the_deadline_are_late() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.complete_milestone(1);
+    env.release(0, Party::Client).unwrap();
+
+    env.warp_to(START_TIME + PROJECT_DURATION as i64 + 1);
+    env.release(1, Party::Client).unwrap();
+
+    let freelancer = profile(&env, Party::Freelancer);
+    assert_eq!(freelancer.milestones_delivered, 2);
+    assert_eq!(freelancer.milestones_on_time, 1);
+    assert_eq!(freelancer.on_time_rate_bps(), Some(5_000));
+}
+
+#[test]
+fn termination_with_everything_released_counts_as_completed() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    for index in 0..milestones().len() as u64 {
+        env.complete_milestone(index);
+        env.release(index, Party::Client).unwrap();
+    }
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+
+    for party in [Party::Client, Party::Freelancer] {
+        let p = profile(&env, party);
+        assert_eq!(p.contracts_completed, 1);
+        assert_eq!(p.contracts_cancelled, 0);
+    }
+    assert_eq!(profile(&env, Party::Client).volume_paid, milestones_total());
+    assert_eq!(profile(&env, Party::Freelancer).volume_earned, milestones_total());
+}
+
+#[test]
+fn early_termination_counts_as_cancelled_and_books_the_withdrawal() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    for index in 0..milestones().len() as u64 {
+        env.submit(index, Party::Freelancer).unwrap();
+    }
+    env.terminate(Party::Freelancer).unwrap();
+    env.terminate(Party::Client).unwrap();
+    assert_eq!(profile(&env, Party::Freelancer).volume_earned, 0);
+    env.withdraw(Party::Freelancer).unwrap();
+    env.withdraw(Party::Client).unwrap();
+
+    // Only the freelancer's half counts as paid; the client's half is a refund.
+    let client = profile(&env, Party::Client);
+    let freelancer = profile(&env, Party::Freelancer);
+    assert_eq!(client.contracts_cancelled, 1);
+    assert_eq!(freelancer.contracts_cancelled, 1);
+    assert_eq!(client.contracts_completed, 0);
+    assert_eq!(client.volume_paid, milestones_total() / 2);
+    assert_eq!(freelancer.volume_earned, milestones_total() / 2);
+    assert_eq!(freelancer.milestones_delivered, 0);
+    assert_eq!(freelancer.on_time_rate_bps(), None);
+}
+
+#[test]
+fn profiles_accumulate_across_contracts() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+
+    // A second contract between the same client and a different freelancer (the outsider).
+    let client = env.pubkey(Party::Client);
+    let other = env.pubkey(Party::Outsider);
+    let mut second = env.with_outsider_as_freelancer();
+    second.create_contract();
+    second.deposit(milestones_total()).unwrap();
+    second.complete_milestone(0);
+    second.release(0, Party::Client).unwrap();
+
+    let client_profile: Profile = second.account(&profile_address(&client).0);
+    let other_profile: Profile = second.account(&profile_address(&other).0);
+    assert_eq!(client_profile.volume_paid, 2 * milestones()[0].amount);
+    assert_eq!(other_profile.volume_earned, milestones()[0].amount);
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use common::*;
+use pivox_client::{
+    instructions, receipt_address, Bond, ContractAddresses, Contract, MilestoneApproval, Payee, PaymentReceipt,
+    VaultAccount, PROGRAM_ID,
+};
+use pivox_contract::{MilestoneError, ReassignError};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+const BOND: u64 = 4_000_000;
+
+fn reassign(env: &mut TestEnv, new_client: Pubkey, new_freelancer: Pubkey, bonded: bool) -> litesvm::types::TransactionResult {
+    let count = env.contract().milestone_count;
+    let ix = instructions::reassign_party(&env.addresses, &new_client, &new_freelancer, bonded, count);
+    let (client, freelancer, outsider) =
+        (env.keypair(Party::Client), env.keypair(Party::Freelancer), env.keypair(Party::Outsider));
+    env.exec_with(&[ix], &[&outsider, &client, &freelancer])
+}
+
+fn hand_to_outsider(env: &mut TestEnv, bonded: bool) -> litesvm::types::TransactionResult {
+    let (client, outsider) = (env.addresses.client, env.pubkey(Party::Outsider));
+    reassign(env, client, outsider, bonded)
+}
+
+fn is_closed(env: &TestEnv, address: &Pubkey) -> bool {
+    env.svm.get_account(address).is_none_or(|a| a.data.is_empty())
+}
+
+fn lamports(env: &TestEnv, address: &Pubkey) -> u64 {
+    env.svm.get_account(address).map_or(0, |a| a.lamports)
+}
+
+#[test]
+fn freelancer_handover_keeps_milestone_state() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    env.submit(1, Party::Freelancer).unwrap();
+    let old = env.addresses;
+    let before = env.contract();
+    let progress = |env: &TestEnv| -> Vec<_> {
+        let milestones = env.all_milestones();
+        milestones.iter().map(|m| (m.amount, m.freelancer_submitted, m.client_approved, m.is_released)).collect()
+    };
+    let progress_before = progress(&env);
+
+    hand_to_outsider(&mut env, false).unwrap();
+
+    for address in [old.contract, old.vault_account, old.milestone_approval, old.vault_ata] {
+        assert!(is_closed(&env, &address));
+    }
+    let mut env = env.with_outsider_as_freelancer();
+    let contract = env.contract();
+    assert_eq!(contract.freelancer, env.pubkey(Party::Freelancer));
+    assert_eq!(contract.vault_account, env.addresses.vault_account);
+    // The milestone accounts stay where they are; the moved contract keeps the id they derive from.
+    assert_eq!(contract.id, old.contract);
+    assert_eq!(progress(&env), progress_before);
+    assert_eq!(contract.payouts, before.payouts);
+    let remaining = milestones_total() - milestones()[0].amount;
+    assert_eq!(env.vault().balance, remaining);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), remaining);
+
+    // The new freelancer picks up the submitted milestone and is paid for it.
+    env.approve_milestone(1, Party::Client).unwrap();
+    env.confirm(1, Party::Freelancer).unwrap();
+    env.release(1, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[1].amount);
+}
+
+#[test]
+fn client_handover_rekeys_every_account() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let (outsider, freelancer) = (env.pubkey(Party::Outsider), env.addresses.freelancer);
+
+    reassign(&mut env, outsider, freelancer, false).unwrap();
+
+    let new = ContractAddresses::new(&outsider, &freelancer, &env.mint);
+    let contract: Contract = env.account(&new.contract);
+    assert_eq!(contract.client, outsider);
+    assert_eq!(contract.multisig_account, new.milestone_approval);
+    let vault: VaultAccount = env.account(&new.vault_account);
+    assert_eq!((vault.client, vault.freelancer), (outsider, freelancer));
+    assert_eq!(vault.total_deposited, milestones_total());
+    let approval: MilestoneApproval = env.account(&new.milestone_approval);
+    assert_eq!(approval.client, outsider);
+    assert!(approval.is_signed);
+    assert_eq!(approval.approved_by, vec![freelancer]);
+}
+
+#[test]
+fn every_party_must_sign() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let (client, outsider) = (env.addresses.client, env.pubkey(Party::Outsider));
+
+    let mut ix = instructions::reassign_party(&env.addresses, &client, &outsider, false, 3);
+    ix.accounts[1].is_signer = false;
+    let (client_kp, outsider_kp) = (env.keypair(Party::Client), env.keypair(Party::Outsider));
+    assert_fails(env.exec_with(&[ix], &[&outsider_kp, &client_kp]));
+    assert_eq!(env.contract().freelancer, env.addresses.freelancer);
+}
+
+#[test]
+fn exactly_one_party_changes() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let (client, freelancer, outsider) = (env.addresses.client, env.addresses.freelancer, env.pubkey(Party::Outsider));
+
+    assert_error(reassign(&mut env, outsider, outsider, false), ReassignError::InvalidReassignment);
+    // Swapping roles would leave the same two wallets on the contract.
+    assert_error(reassign(&mut env, freelancer, client, false), ReassignError::InvalidReassignment);
+}
+
+#[test]
+fn a_posted_bond_moves_with_the_contract() {
+    let mut env = TestEnv::new();
+    env.mint_to(Party::Freelancer, BOND);
+    let (client, freelancer) = (env.addresses.client, env.addresses.freelancer);
+    let args = instructions::ApproveArgs { bond_amount: BOND, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer)
+        .unwrap();
+    let old = env.addresses;
+
+    assert_error(hand_to_outsider(&mut env, false), ReassignError::MissingBondAccounts);
+    hand_to_outsider(&mut env, true).unwrap();
+
+    assert!(is_closed(&env, &old.bond));
+    assert!(is_closed(&env, &old.bond_ata));
+    let env = env.with_outsider_as_freelancer();
+    let bond: Bond = env.account(&env.addresses.bond);
+    assert_eq!(bond.contract, env.addresses.contract);
+    assert_eq!(bond.freelancer, env.pubkey(Party::Freelancer));
+    assert_eq!(bond.posted, BOND);
+    assert_eq!(env.token_balance(&env.addresses.bond_ata), BOND);
+}
+
+#[test]
+fn the_outgoing_party_gets_the_old_accounts_rent() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let old = env.addresses;
+    let closed = [old.contract, old.vault_account, old.milestone_approval, old.activity_log, old.vault_ata];
+    let rent: u64 = closed.iter().map(|address| lamports(&env, address)).sum();
+    let (freelancer_before, client_before) = (lamports(&env, &old.freelancer), lamports(&env, &old.client));
+
+    hand_to_outsider(&mut env, false).unwrap();
+
+    assert_eq!(lamports(&env, &old.freelancer), freelancer_before + rent);
+    assert_eq!(lamports(&env, &old.client), client_before);
+}
+
+#[test]
+fn receipts_stay_reachable_after_a_handover() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    let old = env.addresses;
+
+    hand_to_outsider(&mut env, false).unwrap();
+    let mut env = env.with_outsider_as_freelancer();
+    env.complete_milestone(1);
+    env.release(1, Party::Client).unwrap();
+
+    // Both receipts derive from the id the contract kept, and number on from each other.
+    let receipts: Vec<PaymentReceipt> = (0..2).map(|n| env.account(&receipt_address(&old.contract, n).0)).collect();
+    assert!(receipts.iter().all(|r| r.contract == old.contract));
+    let indices: Vec<_> = receipts.iter().map(|r| (r.number, r.milestone_index)).collect();
+    assert_eq!(indices, vec![(0, Some(0)), (1, Some(1))]);
+    assert_eq!(env.contract().receipt_count, 2);
+}
+
+#[test]
+fn a_new_bond_needs_an_old_one() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let (client, outsider) = (env.addresses.client, env.pubkey(Party::Outsider));
+
+    // No old bond ATA to move from, but the new bond accounts are passed anyway.
+    let mut ix = instructions::reassign_party(&env.addresses, &client, &outsider, true, 3);
+    ix.accounts[14] = AccountMeta::new_readonly(PROGRAM_ID, false);
+    let signers = (env.keypair(Party::Outsider), env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    assert_error(env.exec_with(&[ix], &[&signers.0, &signers.1, &signers.2]), ReassignError::NoBond);
+    assert!(is_closed(&env, &ContractAddresses::new(&client, &outsider, &env.mint).bond));
+}
+
+#[test]
+fn every_milestone_account_is_passed() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let (client, outsider) = (env.addresses.client, env.pubkey(Party::Outsider));
+
+    let ix = instructions::reassign_party(&env.addresses, &client, &outsider, false, 2);
+    let signers = (env.keypair(Party::Outsider), env.keypair(Party::Client), env.keypair(Party::Freelancer));
+    assert_error(env.exec_with(&[ix], &[&signers.0, &signers.1, &signers.2]), ReassignError::MissingMilestones);
+}
+
+#[test]
+fn payees_naming_the_outgoing_party_go_to_the_incoming_one() {
+    let mut env = TestEnv::new();
+    let (client, freelancer, outsider) = (env.addresses.client, env.addresses.freelancer, env.pubkey(Party::Outsider));
+    let partner = Pubkey::new_unique();
+    let mut planned = milestones();
+    // The outsider already shares this milestone, so the freelancer's weight is folded into theirs.
+    planned[1].payees = vec![Payee { wallet: freelancer, bps: 7_000 }, Payee { wallet: outsider, bps: 3_000 }];
+    let payees = vec![Payee { wallet: freelancer, bps: 6_000 }, Payee { wallet: partner, bps: 4_000 }];
+    let args = instructions::ApproveArgs { payees, ..approve_args(planned) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer).unwrap();
+
+    hand_to_outsider(&mut env, false).unwrap();
+
+    let env = env.with_outsider_as_freelancer();
+    let expected = vec![Payee { wallet: outsider, bps: 6_000 }, Payee { wallet: partner, bps: 4_000 }];
+    assert_eq!(env.contract().payees, expected);
+    assert_eq!(env.milestone(1).payees, vec![Payee { wallet: outsider, bps: 10_000 }]);
+    assert!(env.milestone(0).payees.is_empty());
+}
+
+#[test]
+fn pending_termination_approvals_do_not_carry_over() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.terminate(Party::Client).unwrap();
+
+    hand_to_outsider(&mut env, false).unwrap();
+
+    let mut env = env.with_outsider_as_freelancer();
+    let contract = env.contract();
+    assert!(!contract.client_approved && !contract.freelancer_approved);
+    // The new freelancer's approval alone doesn't end the contract.
+    env.terminate(Party::Freelancer).unwrap();
+    assert_eq!(env.contract().status, "active");
+}
+
+#[test]
+fn the_outgoing_pair_cannot_contract_again() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    hand_to_outsider(&mut env, false).unwrap();
+
+    // The reassigned contract's milestones still live under the old pair's contract address.
+    let (client, freelancer) = (env.addresses.client, env.addresses.freelancer);
+    let args = approve_args(milestones());
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    assert_error(
+        env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer),
+        MilestoneError::MilestoneAccountInUse,
+    );
+}
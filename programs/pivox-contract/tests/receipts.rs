@@ -0,0 +1,166 @@
+mod common;
+
+use common::*;
+use pivox_client::{instructions, receipt_address, PaymentReceipt, ReceiptKind};
+use pivox_contract::ReceiptError;
+use solana_sdk::pubkey::Pubkey;
+
+fn release_with_invoice(env: &mut TestEnv, index: u64, invoice: &str) -> litesvm::types::TransactionResult {
+    let client = env.addresses.client;
+    let number = env.contract().receipt_count;
+    let ix = instructions::release_milestone_payment(&env.addresses, &client, index, &[], None, number, invoice);
+    env.exec(&[ix], Party::Client)
+}
+
+fn receipt(env: &TestEnv, number: u64) -> PaymentReceipt {
+    env.account(&receipt_address(&env.addresses.contract, number).0)
+}
+
+fn exists(env: &TestEnv, address: &Pubkey) -> bool {
+    env.svm.get_account(address).is_some_and(|a| !a.data.is_empty())
+}
+
+#[test]
+fn releases_are_receipted_with_their_invoice() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(1);
+
+    release_with_invoice(&mut env, 1, "INV-2026-0042").unwrap();
+
+    let r = receipt(&env, 0);
+    assert_eq!(r.number, 0);
+    assert_eq!(r.contract, env.addresses.contract);
+    assert_eq!(r.kind, ReceiptKind::MilestoneRelease);
+    assert_eq!(r.milestone_index, Some(1));
+    assert_eq!((r.payer, r.payee, r.mint), (env.addresses.client, env.addresses.freelancer, env.mint));
+    assert_eq!(r.amount, milestones()[1].amount);
+    assert_eq!(r.paid_at, env.now());
+    assert_eq!(r.invoice, "INV-2026-0042");
+    assert_eq!(r.rent_payer, env.addresses.client);
+    assert_eq!(env.contract().receipt_count, 1);
+}
+
+#[test]
+fn termination_withdrawals_are_receipted() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    for index in 0..milestones().len() as u64 {
+        env.submit(index, Party::Freelancer).unwrap();
+    }
+    let first = receipt_address(&env.addresses.contract, 0).0;
+
+    // Everything was submitted, so termination itself moves no tokens.
+    env.terminate(Party::Client).unwrap();
+    env.terminate(Party::Freelancer).unwrap();
+    assert!(!exists(&env, &first));
+    assert_eq!(env.contract().receipt_count, 0);
+
+    env.withdraw(Party::Freelancer).unwrap();
+    let r = receipt(&env, 0);
+    assert_eq!(r.kind, ReceiptKind::Termination);
+    assert_eq!(r.milestone_index, None);
+    assert_eq!((r.payer, r.payee), (env.addresses.client, env.addresses.freelancer));
+    assert_eq!(r.amount, milestones_total() / 2);
+    assert_eq!(r.rent_payer, env.addresses.freelancer);
+
+    env.withdraw(Party::Client).unwrap();
+    let r = receipt(&env, 1);
+    assert_eq!(r.kind, ReceiptKind::Refund);
+    assert_eq!((r.payer, r.payee), (env.addresses.vault_account, env.addresses.client));
+    assert_eq!(r.amount, milestones_total() / 2);
+}
+
+#[test]
+fn refunds_are_receipted_from_the_vault() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    env.transfer_to_vault(7_000).unwrap();
+
+    env.reconcile(Party::Freelancer).unwrap();
+
+    let r = receipt(&env, 1);
+    assert_eq!(r.kind, ReceiptKind::Refund);
+    assert_eq!((r.payer, r.payee), (env.addresses.vault_account, env.addresses.client));
+    assert_eq!(r.amount, 7_000);
+    assert!(r.invoice.is_empty());
+}
+
+#[test]
+fn termination_refunds_are_receipted_with_the_invoice() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.submit(0, Party::Freelancer).unwrap();
+    env.terminate(Party::Client).unwrap();
+
+    let freelancer = env.addresses.freelancer;
+    let ix = instructions::complete_or_cancel_contract(&env.addresses, &freelancer, 0, "CN-7");
+    env.exec(&[ix], Party::Freelancer).unwrap();
+
+    let r = receipt(&env, 0);
+    assert_eq!(r.kind, ReceiptKind::Refund);
+    assert_eq!(r.milestone_index, None);
+    assert_eq!((r.payer, r.payee), (env.addresses.vault_account, env.addresses.client));
+    assert_eq!(r.amount, milestones_total() - milestones()[0].amount);
+    assert_eq!((r.invoice.as_str(), r.rent_payer), ("CN-7", freelancer));
+    assert_eq!(env.contract().receipt_count, 1);
+}
+
+#[test]
+fn only_the_rent_payer_closes_a_receipt() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    let address = receipt_address(&env.addresses.contract, 0).0;
+    let rent = env.svm.get_account(&address).unwrap().lamports;
+
+    let freelancer = env.addresses.freelancer;
+    assert_error(
+        env.exec(&[instructions::close_payment_receipt(&freelancer, &address)], Party::Freelancer),
+        ReceiptError::Unauthorized,
+    );
+
+    let client = env.addresses.client;
+    let lamports = |env: &TestEnv| env.svm.get_account(&client).unwrap().lamports;
+    let before = lamports(&env);
+    env.exec(&[instructions::close_payment_receipt(&client, &address)], Party::Client).unwrap();
+    assert!(!exists(&env, &address));
+    assert!(lamports(&env) > before + rent - 10_000);
+    // The counter keeps going, so numbers are never reused.
+    assert_eq!(env.contract().receipt_count, 1);
+}
+
+#[test]
+fn the_receipt_must_be_the_next_in_sequence() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    let client = env.addresses.client;
+
+    let ix = instructions::release_milestone_payment(&env.addresses, &client, 0, &[], None, 1, "");
+    assert_error(env.exec(&[ix], Party::Client), ReceiptError::InvalidReceiptAccount);
+}
+
+#[test]
+fn invoice_references_are_bounded() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+
+    assert_error(release_with_invoice(&mut env, 0, &"9".repeat(33)), ReceiptError::InvoiceTooLong);
+}
+
+#[test]
+fn a_prefunded_receipt_address_does_not_block_payment() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    env.complete_milestone(0);
+    let address = receipt_address(&env.addresses.contract, 0).0;
+    env.svm.airdrop(&address, 1_000).unwrap();
+
+    env.release(0, Party::Client).unwrap();
+    assert_eq!(receipt(&env, 0).amount, milestones()[0].amount);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::*;
+use pivox_client::{instructions, ReleasePolicy};
+use pivox_contract::{ContractError, MilestoneError};
+
+fn create_funded_with(env: &mut TestEnv, release_policy: ReleasePolicy) {
+    let (client, freelancer) = (env.pubkey(Party::Client), env.pubkey(Party::Freelancer));
+    let args = instructions::ApproveArgs { release_policy, ..approve_args(milestones()) };
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+    env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer).unwrap();
+    env.deposit(milestones_total()).unwrap();
+}
+
+/// Submits and approves milestone `index`, without the freelancer's confirmation.
+fn approved(env: &mut TestEnv, index: u64) {
+    env.submit(index, Party::Freelancer).unwrap();
+    env.approve_milestone(index, Party::Client).unwrap();
+}
+
+#[test]
+fn client_approval_is_the_default_and_only_the_client_releases() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    assert_eq!(env.contract().release_policy, ReleasePolicy::ClientApproval);
+
+    approved(&mut env, 0);
+    assert_error(env.release(0, Party::Freelancer), ContractError::Unauthorized);
+    assert_error(env.release(0, Party::Outsider), ContractError::Unauthorized);
+    env.release(0, Party::Client).unwrap();
+
+    let milestone = env.milestone(0);
+    assert!(milestone.is_released);
+    assert!(!milestone.freelancer_confirmed, "releasing doesn't confirm on the freelancer's behalf");
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount);
+}
+
+#[test]
+fn both_confirmed_waits_for_the_freelancer() {
+    let mut env = TestEnv::new();
+    create_funded_with(&mut env, ReleasePolicy::BothConfirmed);
+
+    approved(&mut env, 0);
+    assert_error(env.release(0, Party::Client), MilestoneError::NotConfirmed);
+    env.confirm(0, Party::Freelancer).unwrap();
+    assert_error(env.release(0, Party::Outsider), ContractError::Unauthorized);
+    env.release(0, Party::Freelancer).unwrap();
+
+    env.complete_milestone(1);
+    env.release(1, Party::Client).unwrap();
+    assert_eq!(env.contract().released_count, 2);
+}
+
+#[test]
+fn freelancer_only_leaves_the_release_to_the_freelancer() {
+    let mut env = TestEnv::new();
+    create_funded_with(&mut env, ReleasePolicy::FreelancerOnly);
+
+    env.submit(0, Party::Freelancer).unwrap();
+    assert_error(env.release(0, Party::Freelancer), ContractError::MilestoneError);
+    env.approve_milestone(0, Party::Client).unwrap();
+    assert_error(env.release(0, Party::Client), ContractError::Unauthorized);
+    env.release(0, Party::Freelancer).unwrap();
+    assert!(env.milestone(0).is_released);
+}
+
+#[test]
+fn permissionless_lets_anyone_crank_an_approved_release() {
+    let mut env = TestEnv::new();
+    create_funded_with(&mut env, ReleasePolicy::Permissionless);
+
+    env.submit(0, Party::Freelancer).unwrap();
+    assert_error(env.release(0, Party::Outsider), ContractError::MilestoneError);
+    env.approve_milestone(0, Party::Client).unwrap();
+    env.release(0, Party::Outsider).unwrap();
+
+    // The crank only triggers the payment; the funds still go to the freelancer.
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount);
+    assert_eq!(env.token_balance(&env.outsider_ata()), 0);
+    assert_error(env.release(0, Party::Outsider), ContractError::AlreadyReleased);
+}
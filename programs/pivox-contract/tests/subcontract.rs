@@ -0,0 +1,224 @@
+mod common;
+
+use common::*;
+use pivox_client::{
+    instructions, receipt_address, ActivityAction, ActivityLog, Contract, ContractAddresses, PaymentReceipt,
+    SubcontractLink, VaultAccount,
+};
+use pivox_contract::{ReassignError, SubcontractError};
+
+const LOGO: u64 = 20_000_000;
+const ICONS: u64 = 10_000_000;
+
+/// The parent freelancer hires the outsider for part of the parent's first milestone.
+fn create_child(env: &mut TestEnv) -> ContractAddresses {
+    let (freelancer, outsider) = (env.pubkey(Party::Freelancer), env.pubkey(Party::Outsider));
+    let child = ContractAddresses::new(&freelancer, &outsider, &env.mint);
+    let args = approve_args(vec![milestone("Logo", LOGO), milestone("Icons", ICONS)]);
+    env.exec(&[proposal(&freelancer, &outsider, &args)], Party::Freelancer).unwrap();
+    env.exec(&[instructions::approve(&child, args)], Party::Outsider).unwrap();
+    child
+}
+
+/// Funds `child` from parent milestone `index`, signed by both parties of the parent contract.
+fn fund(env: &mut TestEnv, child: &ContractAddresses, index: u8) -> litesvm::types::TransactionResult {
+    let ix = instructions::fund_subcontract(&env.addresses, child, index);
+    let (freelancer, client) = (env.keypair(Party::Freelancer), env.keypair(Party::Client));
+    env.exec_with(&[ix], &[&freelancer, &client])
+}
+
+fn funded_child(env: &mut TestEnv) -> ContractAddresses {
+    env.create_funded_contract();
+    let child = create_child(env);
+    fund(env, &child, 0).unwrap();
+    child
+}
+
+/// Submit, approve, confirm and release child milestone `index`.
+fn deliver(env: &mut TestEnv, child: &ContractAddresses, index: u64) {
+    let client = child.client;
+    env.exec(&[instructions::freelancer_submit_milestone(child, index, &[])], Party::Outsider)
+        .unwrap();
+    env.exec(&[instructions::client_approve_milestone(child, index, &[])], Party::Freelancer)
+        .unwrap();
+    env.exec(&[instructions::freelancer_confirm_milestone(child, index)], Party::Outsider)
+        .unwrap();
+    let receipt_number = env.account::<Contract>(&child.contract).receipt_count;
+    let ix = instructions::release_milestone_payment(child, &client, index, &[], None, receipt_number, "");
+    env.exec(&[ix], Party::Freelancer).unwrap();
+}
+
+fn settle(env: &mut TestEnv, child: &ContractAddresses, with_consent: bool) -> litesvm::types::TransactionResult {
+    let receipt_number = env.account::<Contract>(&child.contract).receipt_count;
+    let ix = instructions::settle_subcontract(&env.addresses, 0, child, with_consent, receipt_number);
+    let (freelancer, outsider) = (env.keypair(Party::Freelancer), env.keypair(Party::Outsider));
+    if with_consent {
+        env.exec_with(&[ix], &[&freelancer, &outsider])
+    } else {
+        env.exec_with(&[ix], &[&freelancer])
+    }
+}
+
+#[test]
+fn funding_moves_escrow_from_the_parent_milestone() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+
+    assert_eq!(
+        env.milestone(0).subcontract,
+        Some(SubcontractLink { child: child.contract, amount: LOGO + ICONS, settled: false })
+    );
+    assert_eq!(env.vault().total_released, LOGO + ICONS);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total() - LOGO - ICONS);
+
+    let child_contract: Contract = env.account(&child.contract);
+    let link = child_contract.parent.unwrap();
+    assert_eq!((link.contract, link.milestone_index), (env.addresses.contract, 0));
+    let child_vault: VaultAccount = env.account(&child.vault_account);
+    assert_eq!(child_vault.total_deposited, LOGO + ICONS);
+    assert_eq!(env.token_balance(&child.vault_ata), LOGO + ICONS);
+}
+
+#[test]
+fn funding_needs_the_parent_client() {
+    let mut env = TestEnv::new();
+    env.create_funded_contract();
+    let child = create_child(&mut env);
+
+    let mut ix = instructions::fund_subcontract(&env.addresses, &child, 0);
+    ix.accounts[1].is_signer = false;
+    assert_fails(env.exec(&[ix], Party::Freelancer));
+
+    // The subcontractor can't stand in for the parent client.
+    let mut ix = instructions::fund_subcontract(&env.addresses, &child, 0);
+    ix.accounts[1].pubkey = child.freelancer;
+    let (freelancer, outsider) = (env.keypair(Party::Freelancer), env.keypair(Party::Outsider));
+    assert_error(env.exec_with(&[ix], &[&freelancer, &outsider]), SubcontractError::Unauthorized);
+    assert!(env.milestone(0).subcontract.is_none());
+}
+
+#[test]
+fn parent_release_waits_for_the_subcontract() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+    env.complete_milestone(0);
+    assert_error(env.release(0, Party::Client), SubcontractError::Unsettled);
+
+    deliver(&mut env, &child, 0);
+    deliver(&mut env, &child, 1);
+    // Every child milestone is paid, so the parent freelancer settles alone.
+    settle(&mut env, &child, false).unwrap();
+    env.release(0, Party::Client).unwrap();
+
+    assert_eq!(env.token_balance(&child.freelancer_ata), LOGO + ICONS);
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount - LOGO - ICONS);
+    assert_eq!(env.vault().total_released, milestones()[0].amount);
+}
+
+#[test]
+fn unspent_funds_go_to_the_parent_freelancer() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+    deliver(&mut env, &child, 0);
+
+    settle(&mut env, &child, true).unwrap();
+
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), ICONS);
+    assert_eq!(env.token_balance(&env.addresses.vault_ata), milestones_total() - LOGO - ICONS);
+    assert!(env.svm.get_account(&child.vault_ata).is_none_or(|a| a.data.is_empty()));
+    let child_contract: Contract = env.account(&child.contract);
+    assert_eq!(child_contract.status, "terminated");
+    assert_eq!(
+        env.milestone(0).subcontract,
+        Some(SubcontractLink { child: child.contract, amount: LOGO + ICONS, settled: true })
+    );
+    let receipt: PaymentReceipt = env.account(&receipt_address(&child_contract.id, child_contract.receipt_count - 1).0);
+    assert_eq!((receipt.payee, receipt.amount), (env.addresses.freelancer, ICONS));
+
+    // The parent milestone pays out what is left of it after the subcontract.
+    env.complete_milestone(0);
+    env.release(0, Party::Client).unwrap();
+    assert_eq!(env.token_balance(&env.addresses.freelancer_ata), milestones()[0].amount - LOGO);
+}
+
+#[test]
+fn funding_and_settling_are_logged_on_both_contracts() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+    deliver(&mut env, &child, 0);
+    settle(&mut env, &child, true).unwrap();
+
+    let freelancer = env.addresses.freelancer;
+    let log: ActivityLog = env.account(&env.addresses.activity_log);
+    let tail: Vec<_> = log.chronological().skip(2).map(|e| (e.action, e.actor, e.milestone_index, e.amount)).collect();
+    assert_eq!(
+        tail,
+        vec![
+            (ActivityAction::SubcontractFunded, freelancer, Some(0), LOGO + ICONS),
+            (ActivityAction::SubcontractSettled, freelancer, Some(0), ICONS),
+        ]
+    );
+
+    let log: ActivityLog = env.account(&child.activity_log);
+    let entries: Vec<_> = log.chronological().map(|e| (e.action, e.amount)).collect();
+    assert_eq!(entries[1], (ActivityAction::Deposited, LOGO + ICONS));
+    assert_eq!(entries.last(), Some(&(ActivityAction::Terminated, ICONS)));
+}
+
+#[test]
+fn unfinished_subcontracts_need_the_subcontractor() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+    deliver(&mut env, &child, 0);
+
+    assert_error(settle(&mut env, &child, false), SubcontractError::ConsentRequired);
+}
+
+#[test]
+fn a_subcontract_has_one_parent() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+
+    assert_error(fund(&mut env, &child, 1), SubcontractError::InvalidChild);
+}
+
+#[test]
+fn subcontracts_close_through_settlement() {
+    let mut env = TestEnv::new();
+    let child = funded_child(&mut env);
+    let freelancer = env.pubkey(Party::Freelancer);
+
+    assert_error(
+        env.exec(&[instructions::complete_or_cancel_contract(&child, &freelancer, 0, "")], Party::Freelancer),
+        SubcontractError::SettleInstead,
+    );
+}
+
+#[test]
+fn linked_contracts_cannot_be_reassigned() {
+    let mut env = TestEnv::new();
+    funded_child(&mut env);
+    let (client, outsider) = (env.addresses.client, env.pubkey(Party::Outsider));
+
+    let ix = instructions::reassign_party(&env.addresses, &client, &outsider, false, 3);
+    let signers = [env.keypair(Party::Outsider), env.keypair(Party::Client), env.keypair(Party::Freelancer)];
+    assert_error(
+        env.exec_with(&[ix], &[&signers[0], &signers[1], &signers[2]]),
+        ReassignError::SubcontractLinked,
+    );
+}
+
+#[test]
+fn new_contracts_cannot_carry_links() {
+    let mut env = TestEnv::new();
+    let (client, freelancer) = (env.addresses.client, env.addresses.freelancer);
+    let mut planned = milestones();
+    planned[0].subcontract = Some(SubcontractLink { child: client, amount: 1, settled: false });
+    let args = approve_args(planned);
+    env.exec(&[proposal(&client, &freelancer, &args)], Party::Client).unwrap();
+
+    assert_error(
+        env.exec(&[instructions::approve(&env.addresses, args)], Party::Freelancer),
+        SubcontractError::LinkNotAllowed,
+    );
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use common::*;
+use pivox_client::{
+    instructions, milestone_template_address, MilestoneOrdering, MilestoneTemplate, ReleasePolicy, TemplateAmount,
+    TemplateMilestone,
+};
+use pivox_contract::TemplateError;
+use solana_sdk::pubkey::Pubkey;
+
+fn entry(description: &str, amount: TemplateAmount) -> TemplateMilestone {
+    TemplateMilestone { description: description.to_string(), amount }
+}
+
+fn website_build() -> Vec<TemplateMilestone> {
+    vec![
+        entry("Kick-off", TemplateAmount::Fixed(1_000_000)),
+        entry("Design", TemplateAmount::Percent(30)),
+        entry("Development", TemplateAmount::Percent(50)),
+        entry("QA", TemplateAmount::Percent(20)),
+    ]
+}
+
+fn create_template(env: &mut TestEnv, name: &str, milestones: Vec<TemplateMilestone>) -> Pubkey {
+    let client = env.pubkey(Party::Client);
+    env.exec(&[instructions::create_milestone_template(&client, name, milestones)], Party::Client)
+        .unwrap();
+    milestone_template_address(&client, name).0
+}
+
+fn approve_from_template(env: &mut TestEnv, template: Pubkey, total_budget: u64) -> litesvm::types::TransactionResult {
+    let client = env.pubkey(Party::Client);
+    let freelancer = env.pubkey(Party::Freelancer);
+    let defaults = approve_args(vec![]);
+    let milestone_count = env.account::<MilestoneTemplate>(&template).milestones.len() as u8;
+    let args = instructions::TemplateApproveArgs {
+        client_share: defaults.client_share,
+        freelancer_share: defaults.freelancer_share,
+        initial_payment: defaults.initial_payment,
+        project_start: defaults.project_start,
+        project_duration: defaults.project_duration,
+        dispute_resolution: defaults.dispute_resolution,
+        ordering: defaults.ordering,
+        release_policy: defaults.release_policy,
+        payees: defaults.payees,
+        bond_amount: defaults.bond_amount,
+        deposit: defaults.deposit,
+        template,
+        total_budget,
+        milestone_count,
+    };
+    // A budget the template can't be scaled to fails before the terms are compared.
+    let milestones = env.account::<MilestoneTemplate>(&template).instantiate(total_budget).unwrap_or_default();
+    let terms_hash = args.terms_hash(&milestones).unwrap();
+    env.exec(&[instructions::propose(&client, &freelancer, terms_hash)], Party::Client).unwrap();
+    env.exec(&[instructions::approve_from_template(&env.addresses, args)], Party::Freelancer)
+}
+
+#[test]
+fn creates_a_named_template() {
+    let mut env = TestEnv::new();
+    let address = create_template(&mut env, "Website build", website_build());
+
+    let template: MilestoneTemplate = env.account(&address);
+    assert_eq!(template.creator, env.pubkey(Party::Client));
+    assert_eq!(template.name, "Website build");
+    assert_eq!(template.milestones, website_build());
+}
+
+#[test]
+fn contract_milestones_are_instantiated_from_the_budget() {
+    let mut env = TestEnv::new();
+    let template = create_template(&mut env, "Website build", website_build());
+
+    approve_from_template(&mut env, template, 101_000_001).unwrap();
+
+    let amounts: Vec<u64> = env.all_milestones().iter().map(|m| m.amount).collect();
+    // 100_000_001 is split 30/50/20 after the fixed fee; the rounding dust lands on the last percentage.
+    assert_eq!(amounts, vec![1_000_000, 30_000_000, 50_000_000, 20_000_001]);
+    assert_eq!(amounts.iter().sum::<u64>(), 101_000_001);
+    let descriptions: Vec<String> = env.all_milestones().iter().map(|m| m.description.clone()).collect();
+    assert_eq!(descriptions, vec!["Kick-off", "Design", "Development", "QA"]);
+
+    env.deposit(101_000_001).unwrap();
+}
+
+#[test]
+fn budget_must_cover_fixed_amounts() {
+    let mut env = TestEnv::new();
+    let template = create_template(&mut env, "Website build", website_build());
+
+    assert_error(approve_from_template(&mut env, template, 999_999), TemplateError::BudgetMismatch);
+}
+
+#[test]
+fn fixed_only_templates_need_the_exact_budget() {
+    let mut env = TestEnv::new();
+    let template = create_template(
+        &mut env,
+        "Retainer",
+        vec![entry("Month 1", TemplateAmount::Fixed(10)), entry("Month 2", TemplateAmount::Fixed(10))],
+    );
+
+    assert_error(approve_from_template(&mut env, template, 21), TemplateError::BudgetMismatch);
+}
+
+#[test]
+fn approve_from_template_requires_a_template() {
+    let mut env = TestEnv::new();
+    let client = env.pubkey(Party::Client);
+    let freelancer = env.pubkey(Party::Freelancer);
+    env.exec(&[proposal(&client, &freelancer, &approve_args(vec![]))], Party::Client).unwrap();
+
+    let placeholder = Pubkey::new_unique();
+    let mut ix = instructions::approve_from_template(
+        &env.addresses,
+        instructions::TemplateApproveArgs {
+            client_share: 50,
+            freelancer_share: 50,
+            initial_payment: 0,
+            project_start: START_TIME as i128,
+            project_duration: PROJECT_DURATION,
+            dispute_resolution: "Dispute Clause".to_string(),
+            ordering: MilestoneOrdering::Any,
+            release_policy: ReleasePolicy::ClientApproval,
+            payees: vec![],
+            bond_amount: 0,
+            deposit: 0,
+            template: placeholder,
+            total_budget: 1,
+            milestone_count: 0,
+        },
+    );
+    // An optional account is omitted by passing the program id in its slot.
+    let slot = ix.accounts.iter().position(|m| m.pubkey == placeholder).unwrap();
+    ix.accounts[slot].pubkey = pivox_client::PROGRAM_ID;
+    assert_error(env.exec(&[ix], Party::Freelancer), TemplateError::MissingTemplate);
+}
+
+#[test]
+fn percentages_must_add_up_to_100() {
+    let mut env = TestEnv::new();
+    let client = env.pubkey(Party::Client);
+    let ix = instructions::create_milestone_template(
+        &client,
+        "Broken",
+        vec![entry("A", TemplateAmount::Percent(60)), entry("B", TemplateAmount::Percent(30))],
+    );
+
+    assert_error(env.exec(&[ix], Party::Client), TemplateError::InvalidPercentages);
+}
+
+#[test]
+fn template_shape_is_validated() {
+    let mut env = TestEnv::new();
+    let client = env.pubkey(Party::Client);
+
+    let empty = instructions::create_milestone_template(&client, "Empty", vec![]);
+    assert_error(env.exec(&[empty], Party::Client), TemplateError::InvalidMilestoneCount);
+
+    let zero = instructions::create_milestone_template(&client, "Zero", vec![entry("A", TemplateAmount::Fixed(0))]);
+    assert_error(env.exec(&[zero], Party::Client), TemplateError::ZeroAmount);
+
+    let long = instructions::create_milestone_template(
+        &client,
+        "Long",
+        vec![entry(&"x".repeat(101), TemplateAmount::Percent(100))],
+    );
+    assert_error(env.exec(&[long], Party::Client), TemplateError::DescriptionTooLong);
+}
+
+#[test]
+fn only_the_creator_can_close_a_template() {
+    let mut env = TestEnv::new();
+    let address = create_template(&mut env, "Website build", website_build());
+    let client = env.pubkey(Party::Client);
+
+    let mut ix = instructions::close_milestone_template(&client, "Website build");
+    ix.accounts[0].pubkey = env.pubkey(Party::Outsider);
+    assert_fails(env.exec(&[ix], Party::Outsider));
+
+    env.exec(&[instructions::close_milestone_template(&client, "Website build")], Party::Client)
+        .unwrap();
+    assert!(env.svm.get_account(&address).is_none_or(|a| a.data.is_empty()));
+}
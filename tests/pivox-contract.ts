@@ -4,11 +4,15 @@ import { PivoxContract } from "../target/types/pivox_contract";
 import { assert } from "chai";
 import {
   createMint,
+  getAccount,
   getAssociatedTokenAddress,
+  getAssociatedTokenAddressSync,
   getOrCreateAssociatedTokenAccount,
   mintTo,
+  transfer,
 } from "@solana/spl-token";
 import fs from "fs";
+import { createHash } from "crypto";
 
 function sleep(ms: number) {
   return new Promise(resolve => setTimeout(resolve, ms));
@@ -37,6 +41,8 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
   let milestoneApprovalPda: anchor.web3.PublicKey;
   let vaultAccountPda: anchor.web3.PublicKey;
   let contractPda: anchor.web3.PublicKey;
+  let clientProfilePda: anchor.web3.PublicKey;
+  let freelancerProfilePda: anchor.web3.PublicKey;
 
   // Bumps
   let vaultBump: number;
@@ -51,11 +57,59 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
 
   // Constants
   const milestones = [
-    { description: "Design Phase", amount: new anchor.BN(50_000_000), freelancerSubmitted: false, clientApproved: false, freelancerConfirmed: false, isReleased: false },
-    { description: "Development Phase", amount: new anchor.BN(30_000_000), freelancerSubmitted: false, clientApproved: false, freelancerConfirmed: false, isReleased: false },
-    { description: "Testing Phase", amount: new anchor.BN(20_000_000), freelancerSubmitted: false, clientApproved: false, freelancerConfirmed: false, isReleased: false },
+    { description: "Design Phase", amount: new anchor.BN(50_000_000), freelancerSubmitted: false, clientApproved: false, freelancerConfirmed: false, isReleased: false, dependsOn: [], subcontract: null, payees: [], pricing: null, funded: false, cancelled: false },
+    { description: "Development Phase", amount: new anchor.BN(30_000_000), freelancerSubmitted: false, clientApproved: false, freelancerConfirmed: false, isReleased: false, dependsOn: [], subcontract: null, payees: [], pricing: null, funded: false, cancelled: false },
+    { description: "Testing Phase", amount: new anchor.BN(20_000_000), freelancerSubmitted: false, clientApproved: false, freelancerConfirmed: false, isReleased: false, dependsOn: [], subcontract: null, payees: [], pricing: null, funded: false, cancelled: false },
   ];
 
+  const terms = {
+    clientShare: 50,
+    freelancerShare: 50,
+    initialPayment: new anchor.BN(0),
+    projectStart: new anchor.BN(Date.now()),
+    projectDuration: new anchor.BN(60 * 60 * 24 * 30),
+    disputeResolution: "Dispute Clause",
+    ordering: { any: {} },
+    releasePolicy: { clientApproval: {} },
+    payees: [],
+    bondAmount: new anchor.BN(0),
+    deposit: new anchor.BN(0),
+  };
+
+  // What `propose` commits the client to: sha256 of the borsh encoded terms, without the deposit, and
+  // milestones, as `ContractTerms::hash` computes it.
+  function termsHash(): number[] {
+    const count = Buffer.alloc(4);
+    count.writeUInt32LE(milestones.length);
+    const hash = createHash("sha256")
+      .update(program.coder.types.encode("ContractTerms", { ...terms, deposit: new anchor.BN(0) }))
+      .update(count);
+    milestones.forEach(m => hash.update(program.coder.types.encode("Milestone", m)));
+    return Array.from(hash.digest());
+  }
+
+  // Receipts are numbered per contract; payments must pass the next one in sequence.
+  async function nextReceiptPda(): Promise<anchor.web3.PublicKey> {
+    const { receiptCount } = await program.account.contract.fetch(contractPda);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), contractPda.toBuffer(), receiptCount.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+  }
+
+  // Each milestone lives in its own account, seeded by the contract's id (its address at creation) and index.
+  function milestonePda(contractId: anchor.web3.PublicKey, index: number): anchor.web3.PublicKey {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("milestone"), contractId.toBuffer(), Buffer.from([index])],
+      program.programId
+    )[0];
+  }
+
+  // Accounts for the milestones a contract is created with, passed as remaining accounts.
+  function newMilestoneAccounts(contractId: anchor.web3.PublicKey, count: number): anchor.web3.AccountMeta[] {
+    return Array.from({ length: count }, (_, i) => ({ pubkey: milestonePda(contractId, i), isSigner: false, isWritable: true }));
+  }
+
   before(async () => {
     // Airdrops
     async function airdropAndConfirm(
@@ -117,13 +171,21 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       [Buffer.from("contract"), client.publicKey.toBuffer(), freelancer.publicKey.toBuffer()],
       program.programId
     );
+
+    [clientProfilePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), client.publicKey.toBuffer()],
+      program.programId
+    );
+    [freelancerProfilePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), freelancer.publicKey.toBuffer()],
+      program.programId
+    );
   });
 
 
-  it("The client intializes the contract defining all the milestones", async () => {
-    const tx = await program.methods.initializeMilestoneApproval(1)
+  it("The client proposes the contract to the freelancer", async () => {
+    const tx = await program.methods.propose(termsHash())
       .accountsPartial({
-        payer: client.publicKey,
         client: client.publicKey,
         freelancer: freelancer.publicKey,
         milestoneApproval: milestoneApprovalPda,
@@ -131,26 +193,17 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       })
       .signers([client])
       .rpc();
-    console.log("✅ initializeMilestoneApproval tx:", tx);
+    console.log("✅ propose tx:", tx);
 
     const acc = await program.account.milestoneApproval.fetch(milestoneApprovalPda);
     assert.ok(acc.client.equals(client.publicKey));
-    assert.equal(acc.threshold, 1);
+    assert.equal(acc.threshold, 2);
+    assert.equal(acc.approvals, 1);
+    assert.deepEqual(acc.termsHash, termsHash());
   });
 
   it("The freelancer approves milestones and the contract comes into effect", async () => {
-    const tx = await program.methods.approve(
-      50,
-      50,
-      new anchor.BN(0),
-      new anchor.BN(Date.now()),
-      new anchor.BN(60 * 60 * 24 * 30),
-      "Dispute Clause",
-      vaultBump,
-      contractBump,
-      "active",
-      milestones
-    )
+    const tx = await program.methods.approve(terms, milestones)
     .accountsPartial({
       freelancer: freelancer.publicKey,
       client: client.publicKey,
@@ -164,31 +217,29 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
     })
+    .remainingAccounts(newMilestoneAccounts(contractPda, milestones.length))
     .signers([freelancer])
     .rpc();
     console.log("✅ approve tx:", tx);
 
     const contract = await program.account.contract.fetch(contractPda);
-    assert.equal(contract.milestones.length, 3);
+    assert.equal(contract.milestoneCount, 3);
+    const design = await program.account.milestoneAccount.fetch(milestonePda(contractPda, 0));
+    assert.equal(design.milestone.description, "Design Phase");
   });
 
-  it("Creates Vault ATA for vault PDA", async () => {
-    const ata = await getOrCreateAssociatedTokenAccount(
-      provider.connection,
-      client,             // payer
-      usdcMint,           // token mint
-      vaultAccountPda,    // vault PDA
-      true                // allow owner to be PDA
-    );
-  
-    vaultAta = ata.address;
-    console.log("✅ Vault ATA created:", vaultAta.toBase58());
+  it("Approving created the Vault ATA for the vault PDA", async () => {
+    vaultAta = getAssociatedTokenAddressSync(usdcMint, vaultAccountPda, true);
+    const ata = await getAccount(provider.connection, vaultAta);
+    assert.ok(ata.owner.equals(vaultAccountPda));
+    assert.equal(Number(ata.amount), 0);
+    console.log("✅ Vault ATA:", vaultAta.toBase58());
   });
 
   it("Client deposits total milestone funds into Vault", async () => {
     const totalMilestoneAmount = milestones.reduce((acc, m) => acc.add(m.amount), new anchor.BN(0));
 
-    const tx = await program.methods.depositFunds(totalMilestoneAmount)
+    const tx = await program.methods.depositFunds(totalMilestoneAmount, null)
       .accountsPartial({
         depositor: client.publicKey,
         depositorAta: clientAta,
@@ -197,6 +248,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
         usdcMint: usdcMint,
         vaultAccount: vaultAccountPda,
         vaultAta: vaultAta,
+        milestone: null,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -216,7 +268,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
   it("Fails deposit if amount < required", async () => {
     try {
       await program.methods
-        .depositFunds(new anchor.BN(30_000_000))
+        .depositFunds(new anchor.BN(30_000_000), null)
         .accountsPartial({
           depositor: client.publicKey,
           depositorAta: clientAta,
@@ -226,6 +278,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
           vaultAccount: vaultAccountPda,
           vaultAta,
           contract: contractPda,
+          milestone: null,
           systemProgram: anchor.web3.SystemProgram.programId,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -245,6 +298,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       .accountsPartial({
         signer: freelancer.publicKey,
         contract: contractPda,
+        milestone: milestonePda(contractPda, 0),
       })
       .signers([freelancer])
       .rpc();
@@ -257,6 +311,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       .accountsPartial({
         signer: client.publicKey,
         contract: contractPda,
+        milestone: milestonePda(contractPda, 0),
       })
       .signers([client])
       .rpc();
@@ -268,6 +323,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       .accountsPartial({
         signer: freelancer.publicKey,
         contract: contractPda,
+        milestone: milestonePda(contractPda, 0),
       })
       .signers([freelancer])
       .rpc();
@@ -278,13 +334,20 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
     const vaultBefore = await provider.connection.getTokenAccountBalance(vaultAta);
     const freelancerBefore = await provider.connection.getTokenAccountBalance(freelancerAta);
 
-    const tx4 = await program.methods.releaseMilestonePayment(new anchor.BN(0))
+    const tx4 = await program.methods.releaseMilestonePayment(new anchor.BN(0), "")
       .accountsPartial({
         signer: client.publicKey,
         contract: contractPda,
+        milestone: milestonePda(contractPda, 0),
+        receipt: await nextReceiptPda(),
         vaultAccount: vaultAccountPda,
         vaultAta: vaultAta,
         freelancerAta: freelancerAta,
+        clientAta,
+        priceFeed: null,
+        refundReceipt: null,
+        clientProfile: clientProfilePda,
+        freelancerProfile: freelancerProfilePda,
         usdcMint: usdcMint,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -314,29 +377,36 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
 
   it("Handles full lifecycle for milestone 1", async () => {
     await program.methods.freelancerSubmitMilestone(new anchor.BN(1))
-      .accountsPartial({ signer: freelancer.publicKey, contract: contractPda })
+      .accountsPartial({ signer: freelancer.publicKey, contract: contractPda, milestone: milestonePda(contractPda, 1) })
       .signers([freelancer])
       .rpc();
       await sleep(1000);
     await program.methods.clientApproveMilestone(new anchor.BN(1))
-      .accountsPartial({ signer: client.publicKey, contract: contractPda })
+      .accountsPartial({ signer: client.publicKey, contract: contractPda, milestone: milestonePda(contractPda, 1) })
       .signers([client])
       .rpc();
       await sleep(1000);
 
     await program.methods.freelancerConfirmMilestone(new anchor.BN(1))
-      .accountsPartial({ signer: freelancer.publicKey, contract: contractPda })
+      .accountsPartial({ signer: freelancer.publicKey, contract: contractPda, milestone: milestonePda(contractPda, 1) })
       .signers([freelancer])
       .rpc();
       await sleep(1000);
 
-    await program.methods.releaseMilestonePayment(new anchor.BN(1))
+    await program.methods.releaseMilestonePayment(new anchor.BN(1), "")
       .accountsPartial({
         signer: client.publicKey,
         contract: contractPda,
+        milestone: milestonePda(contractPda, 1),
+        receipt: await nextReceiptPda(),
         vaultAccount: vaultAccountPda,
         vaultAta,
         freelancerAta,
+        clientAta,
+        priceFeed: null,
+        refundReceipt: null,
+        clientProfile: clientProfilePda,
+        freelancerProfile: freelancerProfilePda,
         usdcMint,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -345,6 +415,16 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       .signers([client])
       .rpc();
 
+    const [activityLogPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("activity"), contractPda.toBuffer()],
+      program.programId
+    );
+    const log = await program.account.activityLog.fetch(activityLogPda);
+    const last = log.entries[log.entries.length - 1];
+    assert.ok("milestoneReleased" in last.action);
+    assert.equal(last.milestoneIndex, 1);
+    assert.ok(last.actor.equals(client.publicKey));
+
     console.log("✅ Milestone 1 fully processed");
   });
  
@@ -354,7 +434,7 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
   //await provider.connection.requestAirdrop(randomUser.publicKey, 1e9);
     try {
       await program.methods.freelancerSubmitMilestone(new anchor.BN(2))
-        .accountsPartial({ signer: randomUser.publicKey, contract: contractPda })
+        .accountsPartial({ signer: randomUser.publicKey, contract: contractPda, milestone: milestonePda(contractPda, 2) })
         .signers([randomUser])
         .rpc();
       assert.fail("Unauthorized submit should fail");
@@ -366,13 +446,20 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
 
   it("Fails release without confirmation", async () => {
     try {
-      await program.methods.releaseMilestonePayment(new anchor.BN(2))
+      await program.methods.releaseMilestonePayment(new anchor.BN(2), "")
         .accountsPartial({
           signer: client.publicKey,
           contract: contractPda,
+          milestone: milestonePda(contractPda, 2),
+          receipt: await nextReceiptPda(),
           vaultAccount: vaultAccountPda,
           vaultAta,
           freelancerAta,
+          clientAta,
+          priceFeed: null,
+          refundReceipt: null,
+          clientProfile: clientProfilePda,
+          freelancerProfile: freelancerProfilePda,
           usdcMint,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -389,13 +476,13 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
 
   it("Fails double submission of milestone", async () => {
     await program.methods.freelancerSubmitMilestone(new anchor.BN(2))
-      .accountsPartial({ signer: freelancer.publicKey, contract: contractPda })
+      .accountsPartial({ signer: freelancer.publicKey, contract: contractPda, milestone: milestonePda(contractPda, 2) })
       .signers([freelancer])
       .rpc();
 
     try {
       await program.methods.freelancerSubmitMilestone(new anchor.BN(2))
-        .accountsPartial({ signer: freelancer.publicKey, contract: contractPda })
+        .accountsPartial({ signer: freelancer.publicKey, contract: contractPda, milestone: milestonePda(contractPda, 2) })
         .signers([freelancer])
         .rpc();
       assert.fail("Should not allow double submission");
@@ -407,13 +494,20 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
 
   it("Fails to release already released milestone", async () => {
     try {
-      await program.methods.releaseMilestonePayment(new anchor.BN(1))
+      await program.methods.releaseMilestonePayment(new anchor.BN(1), "")
         .accountsPartial({
           signer: client.publicKey,
           contract: contractPda,
+          milestone: milestonePda(contractPda, 1),
+          receipt: await nextReceiptPda(),
           vaultAccount: vaultAccountPda,
           vaultAta,
           freelancerAta,
+          clientAta,
+          priceFeed: null,
+          refundReceipt: null,
+          clientProfile: clientProfilePda,
+          freelancerProfile: freelancerProfilePda,
           usdcMint,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -428,14 +522,51 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
     }
   });
 
+  it("Reconcile refunds tokens sent straight to the vault ATA", async () => {
+    const before = await program.account.vaultAccount.fetch(vaultAccountPda);
+    await transfer(provider.connection, client, clientAta, vaultAta, client, 1_000_000);
+
+    await program.methods.reconcile()
+      .accountsPartial({
+        signer: freelancer.publicKey,
+        contract: contractPda,
+        receipt: await nextReceiptPda(),
+        vaultAccount: vaultAccountPda,
+        vaultAta,
+        clientAta,
+        usdcMint,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([freelancer])
+      .rpc();
+
+    const after = await program.account.vaultAccount.fetch(vaultAccountPda);
+    const vaultAtaInfo = await provider.connection.getTokenAccountBalance(vaultAta);
+    assert.equal(after.balance.toString(), vaultAtaInfo.value.amount);
+    assert.equal(after.balance.toString(), before.balance.toString());
+    assert.equal(after.totalRefunded.sub(before.totalRefunded).toNumber(), 1_000_000);
+    assert.equal(
+      after.balance.toString(),
+      after.totalDeposited.sub(after.totalReleased).sub(after.totalRefunded).sub(after.totalWrittenOff).toString()
+    );
+    console.log("✅ Untracked tokens refunded to the client");
+  });
+
   it("Client and freelancer terminate contract and withdraw", async () => {
-    await program.methods.completeOrCancelContract()
+    await program.methods.completeOrCancelContract("")
       .accountsPartial({
         signer: client.publicKey,
         contract: contractPda,
+        milestone: null,
+        receipt: await nextReceiptPda(),
+        refundReceipt: null,
         vaultAccount: vaultAccountPda,
         vaultAta,
         freelancerAta,
+        clientAta,
+        clientProfile: clientProfilePda,
+        freelancerProfile: freelancerProfilePda,
         usdcMint,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -444,13 +575,19 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
       .signers([client])
       .rpc();
 
-    await program.methods.completeOrCancelContract()
+    await program.methods.completeOrCancelContract("")
       .accountsPartial({
         signer: freelancer.publicKey,
         contract: contractPda,
+        milestone: null,
+        receipt: await nextReceiptPda(),
+        refundReceipt: null,
         vaultAccount: vaultAccountPda,
         vaultAta,
         freelancerAta,
+        clientAta,
+        clientProfile: clientProfilePda,
+        freelancerProfile: freelancerProfilePda,
         usdcMint,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -461,21 +598,131 @@ let freelancer =  loadKeypair("wallets/testkeypair.json") ; //Comment out when u
 
    /* await program.methods.withdrawFunds()
       .accountsPartial({
-        client: client.publicKey,
-        freelancer: freelancer.publicKey,
+        signer: client.publicKey,
         usdcMint,
         contract: contractPda,
         vaultAccount: vaultAccountPda,
         vaultAta,
-        freelancerAta,
+        destination: clientAta,
+        receipt: await nextReceiptPda(),
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
+      .signers([client])
       .rpc();*/
 
+    const freelancerProfile = await program.account.profile.fetch(freelancerProfilePda);
+    const clientProfile = await program.account.profile.fetch(clientProfilePda);
+    assert.ok(freelancerProfile.owner.equals(freelancer.publicKey));
+    assert.equal(freelancerProfile.volumeEarned.toString(), clientProfile.volumePaid.toString());
+    assert.equal(
+      freelancerProfile.contractsCompleted.add(freelancerProfile.contractsCancelled).toNumber(),
+      clientProfile.contractsCompleted.add(clientProfile.contractsCancelled).toNumber()
+    );
+
     console.log("✅ Contract terminated, vault withdrawn");
   });
+
+  describe("Client-signed offers", () => {
+    const offerFreelancer = randomUser;
+    const nonce = new anchor.BN(Date.now());
+    let offerTerms: any;
+    let offerIxs: { ed25519Ix: anchor.web3.TransactionInstruction; accounts: any };
+
+    function buildOffer(terms: any) {
+      const message = Buffer.concat([
+        Buffer.from("PIVOX_OFFER_V1"),
+        program.programId.toBuffer(),
+        program.coder.types.encode("OfferTerms", terms),
+      ]);
+      const ed25519Ix = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: client.secretKey,
+        message,
+      });
+      const seeds = [client.publicKey.toBuffer(), offerFreelancer.publicKey.toBuffer()];
+      const accounts = {
+        freelancer: offerFreelancer.publicKey,
+        client: client.publicKey,
+        usdcMint,
+        consumedOffer: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("offer"), client.publicKey.toBuffer(), terms.nonce.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        )[0],
+        milestoneApproval: anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("milestone_approval"), ...seeds], program.programId)[0],
+        vaultAccount: anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("vault_account"), ...seeds], program.programId)[0],
+        contract: anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("contract"), ...seeds], program.programId)[0],
+        instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+      return { ed25519Ix, accounts };
+    }
+
+    before(() => {
+      const now = Math.floor(Date.now() / 1000);
+      offerTerms = {
+        client: client.publicKey,
+        freelancer: offerFreelancer.publicKey,
+        mint: usdcMint,
+        clientShare: 50,
+        freelancerShare: 50,
+        initialPayment: new anchor.BN(0),
+        projectStart: new anchor.BN(now),
+        projectDuration: new anchor.BN(60 * 60 * 24 * 30),
+        disputeResolution: "Dispute Clause",
+        milestones,
+        expiresAt: new anchor.BN(now + 60 * 60),
+        nonce,
+      };
+      offerIxs = buildOffer(offerTerms);
+    });
+
+    it("Fails to accept an expired offer", async () => {
+      const expired = { ...offerTerms, nonce: nonce.addn(1), expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) - 60) };
+      const { ed25519Ix, accounts } = buildOffer(expired);
+      try {
+        await program.methods.acceptOffer(expired)
+          .accountsPartial(accounts)
+          .remainingAccounts(newMilestoneAccounts(accounts.contract, milestones.length))
+          .preInstructions([ed25519Ix])
+          .signers([offerFreelancer])
+          .rpc();
+        assert.fail("Expired offer should fail");
+      } catch (err) {
+        logAnchorError(err, "Expired offer rejected as expected");
+      }
+    });
+
+    it("Freelancer accepts the client-signed offer in one instruction", async () => {
+      const tx = await program.methods.acceptOffer(offerTerms)
+        .accountsPartial(offerIxs.accounts)
+        .remainingAccounts(newMilestoneAccounts(offerIxs.accounts.contract, milestones.length))
+        .preInstructions([offerIxs.ed25519Ix])
+        .signers([offerFreelancer])
+        .rpc();
+      console.log("✅ acceptOffer tx:", tx);
+
+      const contract = await program.account.contract.fetch(offerIxs.accounts.contract);
+      assert.ok(contract.client.equals(client.publicKey));
+      assert.equal(contract.milestoneCount, 3);
+      const approval = await program.account.milestoneApproval.fetch(offerIxs.accounts.milestoneApproval);
+      assert.ok(approval.isSigned);
+    });
+
+    it("Fails to replay an accepted offer", async () => {
+      try {
+        await program.methods.acceptOffer(offerTerms)
+          .accountsPartial(offerIxs.accounts)
+          .remainingAccounts(newMilestoneAccounts(offerIxs.accounts.contract, milestones.length))
+          .preInstructions([offerIxs.ed25519Ix])
+          .signers([offerFreelancer])
+          .rpc();
+        assert.fail("Replayed offer should fail");
+      } catch (err) {
+        logAnchorError(err, "Replayed offer rejected as expected");
+      }
+    });
+  });
 });
 
 function logAnchorError(err: any, label: string = "") {